@@ -1,10 +1,12 @@
 //! Contains various ways of obtaining the public API for crates.
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use rustdoc_json::BuildError;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
-use public_api::{PublicApi, MINIMUM_NIGHTLY_RUST_VERSION};
+use public_api::{PublicApi, PublicApiSnapshot, MINIMUM_NIGHTLY_RUST_VERSION};
 
 use crate::{git_utils, Args, ArgsAndToolchain, Subcommand};
 
@@ -14,6 +16,13 @@ pub trait ApiSource {
     /// Do the work necessary to obtain the public API.
     fn obtain_api(&self, argst: &ArgsAndToolchain) -> Result<PublicApi>;
 
+    /// Like [`Self::obtain_api`], but bundles the public API with where it
+    /// came from, for `--save-json`. Only supported by sources that list the
+    /// working tree.
+    fn obtain_snapshot(&self, _argst: &ArgsAndToolchain) -> Result<PublicApiSnapshot> {
+        bail!("`--save-json` is not supported for this public API source")
+    }
+
     /// If this source modifies the local git repo. If that is the case, whoever
     /// uses this API source must make sure to restore the git repo to the
     /// original state afterwards. The API source itself does not do any
@@ -37,6 +46,11 @@ impl ApiSource for CurrentDir {
     fn obtain_api(&self, argst: &ArgsAndToolchain) -> Result<PublicApi> {
         public_api_for_current_dir(argst)
     }
+
+    fn obtain_snapshot(&self, argst: &ArgsAndToolchain) -> Result<PublicApiSnapshot> {
+        let json_path = rustdoc_json_for_current_dir(argst)?;
+        snapshot_from_rustdoc_json(&json_path, argst)
+    }
 }
 /// The API is obtained from a crate published to crates.io. This struct only
 /// contains the version. The name of the package is obtained via [`Args`].
@@ -61,6 +75,57 @@ impl ApiSource for PublishedCrate {
     }
 }
 
+/// Like [`PublishedCrate`], but for the previous published version of the crate
+/// in the working tree, and with the rustdoc JSON cached in
+/// `$CARGO_HOME/public-api-cache/`, or in `public-api-cache/` of the target dir
+/// if `--target-dir` is used, so that repeated CI runs don't have to build it
+/// again.
+pub struct PreviouslyPublishedCrate;
+
+impl ApiSource for PreviouslyPublishedCrate {
+    fn obtain_api(&self, argst: &ArgsAndToolchain) -> Result<PublicApi> {
+        let args = &argst.args;
+        let (package_name, version) = crate::published_crate::resolve_previous_version(args)?;
+        let cache_dir = match &args.target_dir {
+            Some(target_dir) => target_dir.join("public-api-cache"),
+            None => home::cargo_home()?.join("public-api-cache"),
+        };
+        let cached = cache_dir.join(cache_file_name(&package_name, &version, argst));
+
+        // A cached file is ignored if it can't be parsed, e.g. because it was
+        // built by a toolchain with another rustdoc JSON format version
+        if let Ok(public_api) = public_api_from_rustdoc_json(&cached, args) {
+            return Ok(public_api);
+        }
+
+        let rustdoc_json = crate::published_crate::build_rustdoc_json(Some(&version), argst)?;
+        std::fs::create_dir_all(&cache_dir)?;
+        std::fs::copy(&rustdoc_json, &cached)
+            .with_context(|| format!("Failed to cache {rustdoc_json:?} in {cached:?}"))?;
+        public_api_from_rustdoc_json(rustdoc_json, args)
+    }
+}
+
+/// The name of the cached rustdoc JSON for the given version of the package.
+/// Besides the version, the rustdoc JSON depends on the toolchain and on the
+/// features and target it is built for, so those are part of the name too.
+fn cache_file_name(package_name: &str, version: &str, argst: &ArgsAndToolchain) -> String {
+    let args = &argst.args;
+    let mut features = args.features.clone();
+    features.sort();
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (
+        &argst.toolchain,
+        features,
+        args.all_features,
+        args.no_default_features,
+        &args.target,
+    )
+        .hash(&mut hasher);
+    format!("{package_name}-{version}-{:016x}.json", hasher.finish())
+}
+
 /// The API is obtained from a git commit.
 pub struct Commit {
     commit: String,
@@ -99,8 +164,125 @@ impl RustdocJson {
 
 impl ApiSource for RustdocJson {
     fn obtain_api(&self, argst: &ArgsAndToolchain) -> Result<PublicApi> {
+        // Files written by `--save-json` are diffed like rustdoc JSON files
+        if is_saved_api(&self.path)? {
+            let snapshot = read_saved_api(&self.path)?;
+            if argst.args.verbose {
+                let generated_at = (snapshot.generated_at)
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                println!(
+                    "Using the public API of {} that was saved at {generated_at} seconds after the Unix epoch with {}",
+                    snapshot.name_and_version(),
+                    snapshot.toolchain,
+                );
+            }
+            return Ok(snapshot.to_public_api());
+        }
+
         public_api_from_rustdoc_json(&self.path, &argst.args)
     }
+
+    fn obtain_snapshot(&self, argst: &ArgsAndToolchain) -> Result<PublicApiSnapshot> {
+        if is_saved_api(&self.path)? {
+            return read_saved_api(&self.path);
+        }
+        snapshot_from_rustdoc_json(&self.path, argst)
+    }
+}
+
+/// The contents of files written by `--save-json`. The marker is serialized
+/// first, so that such files can be told apart from rustdoc JSON files by only
+/// reading their beginning.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedApi {
+    /// The version of the format of the file. Rustdoc JSON never has this key.
+    cargo_public_api_saved_api: u32,
+
+    #[serde(flatten)]
+    snapshot: PublicApiSnapshot,
+}
+
+/// The current value of [`SavedApi::cargo_public_api_saved_api`].
+const SAVED_API_FORMAT_VERSION: u32 = 1;
+
+/// Whether the file at `path` was written by `--save-json` rather than by
+/// rustdoc. Only looks at the first key, to not read large rustdoc JSON files
+/// twice.
+fn is_saved_api(path: &Path) -> Result<bool> {
+    let mut start = Vec::new();
+    std::fs::File::open(path)
+        .and_then(|file| file.take(64).read_to_end(&mut start))
+        .with_context(|| format!("Failed to read {path:?}"))?;
+    let start = String::from_utf8_lossy(&start);
+    Ok(start.trim_start().strip_prefix('{').is_some_and(|rest| {
+        rest.trim_start()
+            .starts_with("\"cargo_public_api_saved_api\"")
+    }))
+}
+
+fn read_saved_api(path: &Path) -> Result<PublicApiSnapshot> {
+    let file = std::fs::File::open(path).with_context(|| format!("Failed to read {path:?}"))?;
+    let saved: SavedApi = serde_json::from_reader(std::io::BufReader::new(file))
+        .with_context(|| format!("Failed to parse {path:?}"))?;
+    if saved.cargo_public_api_saved_api != SAVED_API_FORMAT_VERSION {
+        bail!(
+            "{path:?} was saved in format version {} but this version of `cargo public-api` only reads format version {SAVED_API_FORMAT_VERSION}",
+            saved.cargo_public_api_saved_api
+        );
+    }
+    Ok(saved.snapshot)
+}
+
+/// Writes `snapshot` to `path`, for `--save-json`.
+pub fn write_saved_api(path: &Path, snapshot: PublicApiSnapshot) -> Result<()> {
+    let file = std::fs::File::create(path).with_context(|| format!("Failed to create {path:?}"))?;
+    let saved = SavedApi {
+        cargo_public_api_saved_api: SAVED_API_FORMAT_VERSION,
+        snapshot,
+    };
+    serde_json::to_writer(std::io::BufWriter::new(file), &saved)
+        .with_context(|| format!("Failed to write {path:?}"))
+}
+
+/// Builds a [`PublicApiSnapshot`] from the rustdoc JSON at `json_path`.
+fn snapshot_from_rustdoc_json(
+    json_path: &Path,
+    argst: &ArgsAndToolchain,
+) -> Result<PublicApiSnapshot> {
+    let toolchain = (argst.toolchain.clone())
+        .or_else(|| std::env::var("RUSTUP_TOOLCHAIN").ok())
+        .unwrap_or_else(|| String::from("unknown"));
+    public_api_builder_from_args(json_path, &argst.args)
+        .build_snapshot(toolchain)
+        .with_context(|| format!("Failed to parse rustdoc JSON at {json_path:?}"))
+}
+
+/// The API is obtained by building the crate in a directory other than the
+/// current one, such as a separate checkout of a fork.
+pub struct LocalDir {
+    manifest_path: PathBuf,
+}
+
+impl LocalDir {
+    pub fn new(dir: &Path) -> Result<Self> {
+        let manifest_path = dir.join("Cargo.toml");
+        // Check during creation to detect problems before anything is built
+        if !manifest_path.is_file() {
+            bail!("The directory {dir:?} does not contain a `Cargo.toml`");
+        }
+        Ok(Self { manifest_path })
+    }
+}
+
+impl ApiSource for LocalDir {
+    fn obtain_api(&self, argst: &ArgsAndToolchain) -> Result<PublicApi> {
+        let builder = builder_from_args(argst).manifest_path(&self.manifest_path);
+        let json_path = build_rustdoc_json(builder)
+            .with_context(|| format!("Failed to build the crate at {:?}", self.manifest_path))?;
+        public_api_from_rustdoc_json(json_path, &argst.args)
+    }
 }
 
 /// Builds the public API for the library in the current working directory. Note
@@ -113,7 +295,7 @@ fn public_api_for_current_dir(argst: &ArgsAndToolchain) -> Result<PublicApi> {
 
 /// Builds the rustdoc JSON for the library in the current working directory.
 /// Also see [`public_api_for_current_dir()`].
-fn rustdoc_json_for_current_dir(argst: &ArgsAndToolchain) -> Result<PathBuf> {
+pub fn rustdoc_json_for_current_dir(argst: &ArgsAndToolchain) -> Result<PathBuf> {
     let builder = builder_from_args(argst);
     build_rustdoc_json(builder)
 }
@@ -128,11 +310,22 @@ pub fn build_rustdoc_json(builder: rustdoc_json::Builder) -> Result<PathBuf> {
 }
 
 fn public_api_builder_from_args(rustdoc_json: &Path, args: &Args) -> public_api::Builder {
+    // Keep the listing complete unless asked not to, since removing e.g. a
+    // derived `Clone` impl is a breaking change
+    let strip_derives = if args.strip_boilerplate_derives {
+        public_api::BOILERPLATE_DERIVES
+    } else {
+        &[]
+    };
     public_api::Builder::from_rustdoc_json(rustdoc_json)
         .debug_sorting(args.debug_sorting)
         .omit_blanket_impls(args.omit_blanket_impls())
         .omit_auto_trait_impls(args.omit_auto_trait_impls())
         .omit_auto_derived_impls(args.omit_auto_derived_impls())
+        .json_format_version(args.json_format_version)
+        .max_depth(args.max_depth)
+        .normalize_whitespace(args.ignore_whitespace)
+        .strip_derives(strip_derives.iter().copied())
 }
 
 /// Creates a rustdoc JSON builder based on the args to this program.
@@ -155,8 +348,11 @@ pub fn builder_from_args(argst: &ArgsAndToolchain) -> rustdoc_json::Builder {
     if let Some(package) = &args.package {
         builder = builder.package(package);
     }
-    if let Some(cap_lints) = &args.cap_lints {
-        builder = builder.cap_lints(Some(cap_lints));
+    if let Some(profile) = &args.profile {
+        builder = builder.profile(profile);
+    }
+    if let Some(cap_lints) = args.cap_lints {
+        builder = builder.cap_lints(Some(cap_lints.as_str()));
     } else if let Some(Subcommand::Diff(_)) = args.subcommand {
         // Suppress any build warning by default when diffing, because it
         // typically is undesirable to fix lints in historic versions of a crate
@@ -219,3 +415,32 @@ Try
         manifest_path
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn name_for(args: &[&str], toolchain: Option<&str>) -> String {
+        let argst = ArgsAndToolchain {
+            args: Args::parse_from(std::iter::once("cargo-public-api").chain(args.iter().copied())),
+            toolchain: toolchain.map(ToOwned::to_owned),
+        };
+        cache_file_name("krate", "1.0.0", &argst)
+    }
+
+    #[test]
+    fn cache_file_name_depends_on_features_and_toolchain() {
+        let default = name_for(&[], None);
+        assert!(default.starts_with("krate-1.0.0-"), "{default}");
+
+        assert_ne!(name_for(&["--features", "a"], None), default);
+        assert_ne!(name_for(&["--all-features"], None), default);
+        assert_ne!(name_for(&[], Some("nightly-2024-10-18")), default);
+        assert_eq!(
+            name_for(&["--features", "a", "--features", "b"], None),
+            name_for(&["--features", "b", "--features", "a"], None),
+        );
+    }
+}
@@ -47,13 +47,142 @@ pub enum Color {
 impl Color {
     pub fn active(self) -> bool {
         match self {
-            Self::Auto => std::io::stdout().is_terminal(), // We should not assume Stdout here, but good enough for now
+            // We should not assume Stdout here, but good enough for now
+            Self::Auto => std::io::stdout().is_terminal() && !no_color(),
             Self::Never => false,
             Self::Always => true,
         }
     }
 }
 
+/// Returns `true` if the `NO_COLOR` environment variable is set to a non-empty
+/// value, which means that colors should be disabled. See
+/// <https://no-color.org/>.
+fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, clap::ValueEnum)]
+#[value(rename_all = "lower")]
+pub enum Format {
+    /// One item per line, with `-` and `+` prefixes for diffs.
+    #[default]
+    Plain,
+
+    /// A self-contained HTML report.
+    Html,
+
+    /// A TOML document with `[[removed]]`, `[[changed]]` and `[[added]]`
+    /// sections.
+    Toml,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, clap::ValueEnum)]
+#[value(rename_all = "lower")]
+pub enum CapLints {
+    /// Lints are never reported.
+    Allow,
+
+    /// Lints are at most reported as warnings.
+    Warn,
+
+    /// Lints are at most reported as errors.
+    Deny,
+
+    /// Lints keep the levels that the crate gives them.
+    Forbid,
+}
+
+impl CapLints {
+    /// The level as rustc spells it, e.g. `"warn"`.
+    pub(crate) const fn as_str(self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Warn => "warn",
+            Self::Deny => "deny",
+            Self::Forbid => "forbid",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, clap::ValueEnum)]
+#[value(rename_all = "lower")]
+pub enum Severity {
+    /// Changes to only docs or whitespace, and everything more severe.
+    Cosmetic,
+
+    /// Added items, changes that do not break users, and everything more
+    /// severe.
+    Compatible,
+
+    /// Items that became `#[deprecated]`, and breaking changes.
+    Deprecation,
+
+    /// Only removed items and breaking changes.
+    Breaking,
+}
+
+impl Severity {
+    pub(crate) const fn change_severity(self) -> public_api::diff::ChangeSeverity {
+        use public_api::diff::ChangeSeverity;
+        match self {
+            Self::Cosmetic => ChangeSeverity::Cosmetic,
+            Self::Compatible => ChangeSeverity::Compatible,
+            Self::Deprecation => ChangeSeverity::Deprecation,
+            Self::Breaking => ChangeSeverity::Breaking,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, clap::ValueEnum)]
+#[value(rename_all = "lower")]
+pub enum ReportFormat {
+    /// Like the plain diff format, without colors.
+    Txt,
+
+    /// The diff serialized as JSON.
+    Json,
+
+    /// A Markdown document with one `diff` code block per section.
+    Md,
+
+    /// A self-contained HTML report.
+    Html,
+}
+
+impl ReportFormat {
+    /// The format that the extension of `path` implies, e.g. [`Self::Md`] for
+    /// `report.md`. `None` for unknown extensions.
+    pub(crate) fn from_extension(path: &std::path::Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "txt" => Some(Self::Txt),
+            "json" => Some(Self::Json),
+            "md" => Some(Self::Md),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, clap::ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum Emit {
+    /// The path to the rustdoc JSON of the crate.
+    RustdocJson,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, clap::ValueEnum)]
+#[value(rename_all = "lower")]
+pub enum MatrixFormat {
+    /// Comma separated values, with old versions as rows and new versions as
+    /// columns.
+    #[default]
+    Csv,
+
+    /// A JSON object with the versions and the matrix.
+    Json,
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, clap::ValueEnum)]
 #[value(rename_all = "kebab-case")]
 #[allow(clippy::enum_variant_names)] // We might add support for omitting other things in the future
@@ -74,8 +203,9 @@ pub enum Omit {
 
 #[cfg(test)]
 mod tests {
-    use super::DenyMethod;
+    use super::{DenyMethod, ReportFormat};
     use std::ops::Not;
+    use std::path::Path;
 
     #[test]
     fn test_deny_added() {
@@ -103,4 +233,15 @@ mod tests {
         assert!(DenyMethod::Added.deny_removed().not());
         assert!(DenyMethod::Changed.deny_removed().not());
     }
+
+    #[test]
+    fn test_report_format_from_extension() {
+        let format = |path: &str| ReportFormat::from_extension(Path::new(path));
+        assert_eq!(format("report.txt"), Some(ReportFormat::Txt));
+        assert_eq!(format("out/report.json"), Some(ReportFormat::Json));
+        assert_eq!(format("report.md"), Some(ReportFormat::Md));
+        assert_eq!(format("report.html"), Some(ReportFormat::Html));
+        assert_eq!(format("report.csv"), None);
+        assert_eq!(format("report"), None);
+    }
 }
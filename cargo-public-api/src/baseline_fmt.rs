@@ -0,0 +1,79 @@
+//! Formats baseline files canonically. See `cargo public-api fmt`.
+
+/// Formats the items of a baseline file, one item per line, canonically:
+/// leading and trailing whitespace is removed, runs of whitespace outside of
+/// string literals are collapsed to a single space, and empty lines are
+/// removed. The order of the items is kept, so that the output of `cargo
+/// public-api`, which groups e.g. `impl`s with their type, already is
+/// canonical. Formatting an already canonical baseline does not change it.
+pub fn format_baseline(baseline: &str) -> String {
+    let mut formatted = String::new();
+    for item in baseline.lines().map(normalize_whitespace) {
+        if !item.is_empty() {
+            formatted.push_str(&item);
+            formatted.push('\n');
+        }
+    }
+    formatted
+}
+
+/// Trims `item` and collapses runs of whitespace that are not within string
+/// literals, such as in `#[export_name = "a  b"]`, to a single space.
+fn normalize_whitespace(item: &str) -> String {
+    let mut normalized = String::with_capacity(item.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut pending_space = false;
+    for c in item.trim().chars() {
+        if !in_string && c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+
+        if pending_space {
+            normalized.push(' ');
+            pending_space = false;
+        }
+        normalized.push(c);
+
+        if in_string && !escaped && c == '"' {
+            in_string = false;
+        } else if !in_string && c == '"' {
+            in_string = true;
+        }
+        escaped = in_string && !escaped && c == '\\';
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn baseline_is_normalized_in_order() {
+        let baseline = "\
+pub fn krate::b()\t\n\
+\n\
+  pub   struct krate::A\n\
+#[export_name = \"a  \\\"  b\"]   pub fn krate::c()\n\
+pub struct krate::A\n";
+
+        let formatted = format_baseline(baseline);
+        assert_eq!(
+            formatted,
+            "\
+pub fn krate::b()\n\
+pub struct krate::A\n\
+#[export_name = \"a  \\\"  b\"] pub fn krate::c()\n\
+pub struct krate::A\n"
+        );
+        assert_eq!(format_baseline(&formatted), formatted);
+    }
+
+    #[test]
+    fn empty_baseline_stays_empty() {
+        assert_eq!(format_baseline(""), "");
+        assert_eq!(format_baseline("\n  \n"), "");
+    }
+}
@@ -0,0 +1,52 @@
+//! Finds the sources of published crates that Cargo has already downloaded
+//! from crates.io to the local registry cache in `~/.cargo/registry/src`.
+//! Building a published crate from the cache is faster than letting Cargo
+//! resolve and download the crate, and works without network access.
+
+use std::path::{Path, PathBuf};
+
+/// Returns the dir with the sources of version `version` of crate `name` in
+/// the local registry cache, if Cargo has downloaded it from crates.io. Caches
+/// of other registries are not used, since a crate with the same name and
+/// version can be a different crate there.
+pub fn cached_crate_source(name: &str, version: &str) -> Option<PathBuf> {
+    let registry_src = home::cargo_home().ok()?.join("registry").join("src");
+    std::fs::read_dir(registry_src)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|registry| is_crates_io(&registry.file_name().to_string_lossy()))
+        .map(|registry| registry.path().join(format!("{name}-{version}")))
+        .find(|source| source.join("Cargo.toml").is_file())
+}
+
+/// The names of the features of the crate with the sources in `source`, as
+/// returned by [`cached_crate_source`].
+pub fn crate_features(source: &Path) -> Vec<String> {
+    cargo_manifest::Manifest::from_path(source.join("Cargo.toml"))
+        .ok()
+        .and_then(|manifest| manifest.features)
+        .map(|features| features.into_keys().collect())
+        .unwrap_or_default()
+}
+
+/// Returns `true` if `registry_dir`, the name of a dir in
+/// `~/.cargo/registry/src`, is the cache of crates.io. The dir is named after
+/// the host of the index and a hash, e.g. `index.crates.io-6f17d22bba15001f`
+/// for the sparse index and `github.com-1ecc6299db9ec823` for the git index.
+fn is_crates_io(registry_dir: &str) -> bool {
+    registry_dir.starts_with("index.crates.io-") || registry_dir == "github.com-1ecc6299db9ec823"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_crates_io;
+
+    #[test]
+    fn only_crates_io_is_used() {
+        assert!(is_crates_io("index.crates.io-6f17d22bba15001f"));
+        assert!(is_crates_io("index.crates.io-1949cf8c6b5b557f"));
+        assert!(is_crates_io("github.com-1ecc6299db9ec823"));
+        assert!(!is_crates_io("github.com-88ac128001ac3a9a"));
+        assert!(!is_crates_io("my-registry.example.com-0123456789abcdef"));
+    }
+}
@@ -0,0 +1,188 @@
+//! Support for persisting default CLI args in a config file. The config is
+//! read from `public-api.toml` next to `Cargo.toml` if it exists, otherwise
+//! from the `[package.metadata.public-api]` section of `Cargo.toml`.
+//!
+//! Keys are the long names of CLI flags, and values are what would have been
+//! passed to the flag on the command line. Flags of subcommands go in a table
+//! named after the subcommand:
+//!
+//! ```toml
+//! omit = ["blanket-impls", "auto-trait-impls"]
+//!
+//! [diff]
+//! deny = ["removed"]
+//! ```
+//!
+//! Flags given on the command line take precedence over the config.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, CommandFactory};
+use toml::{Table, Value};
+
+use crate::Args;
+
+/// The name of the standalone config file.
+const CONFIG_FILE_NAME: &str = "public-api.toml";
+
+/// Returns `args` with the args from the config inserted, except for flags
+/// that are already present in `args`.
+pub fn args_with_config(args: Vec<OsString>) -> Result<Vec<OsString>> {
+    // Invalid args are reported when the final args are parsed
+    let Ok(matches) = Args::command()
+        .ignore_errors(true)
+        .try_get_matches_from(&args)
+    else {
+        return Ok(args);
+    };
+
+    let manifest_path = matches
+        .get_one::<PathBuf>("manifest_path")
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from("Cargo.toml"));
+    let Some((source, config)) = read_config(&manifest_path)? else {
+        return Ok(args);
+    };
+
+    let command = Args::command();
+    let subcommand = matches.subcommand();
+    let mut config_args = vec![];
+    for (key, value) in &config {
+        if let Some(subcommand_def) = command.find_subcommand(key) {
+            let Value::Table(table) = value else {
+                bail!("Invalid value for `{key}` in {source}: expected a table with flags for `{key}`");
+            };
+            if let Some((_, sub_matches)) = subcommand.filter(|(name, _)| name == key) {
+                for (key, value) in table {
+                    push_flag(
+                        &mut config_args,
+                        &source,
+                        subcommand_def,
+                        &[sub_matches],
+                        key,
+                        value,
+                    )?;
+                }
+            }
+        } else {
+            let all_matches: Vec<&ArgMatches> = std::iter::once(&matches)
+                .chain(subcommand.map(|(_, m)| m))
+                .collect();
+            push_flag(
+                &mut config_args,
+                &source,
+                &command,
+                &all_matches,
+                key,
+                value,
+            )?;
+        }
+    }
+
+    // Flags are inserted last so that they come after any subcommand, but
+    // before any `--`
+    let insert_at = args.iter().position(|a| a == "--").unwrap_or(args.len());
+    let mut args = args;
+    args.splice(insert_at..insert_at, config_args);
+    Ok(args)
+}
+
+/// Reads the config from `public-api.toml` or `Cargo.toml` in the same dir as
+/// `manifest_path`. Also returns a description of where the config was found,
+/// for use in error messages.
+fn read_config(manifest_path: &Path) -> Result<Option<(String, Table)>> {
+    let config_path = manifest_path.with_file_name(CONFIG_FILE_NAME);
+    if config_path.exists() {
+        let config = read_toml(&config_path)?;
+        return Ok(Some((format!("{config_path:?}"), config)));
+    }
+
+    if !manifest_path.exists() {
+        return Ok(None);
+    }
+    let mut manifest = read_toml(manifest_path)?;
+    let config = manifest
+        .remove("package")
+        .and_then(|mut package| package.as_table_mut()?.remove("metadata"))
+        .and_then(|mut metadata| metadata.as_table_mut()?.remove("public-api"));
+    match config {
+        Some(Value::Table(config)) => Ok(Some((
+            format!("[package.metadata.public-api] of {manifest_path:?}"),
+            config,
+        ))),
+        Some(_) => bail!("[package.metadata.public-api] of {manifest_path:?} must be a table"),
+        None => Ok(None),
+    }
+}
+
+fn read_toml(path: &Path) -> Result<Table> {
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {path:?}"))?;
+    contents
+        .parse()
+        .with_context(|| format!("Failed to parse {path:?}"))
+}
+
+/// Translates `key = value` from the config to CLI args for the flag `--key`
+/// of `command`, unless the flag was given on the command line according to
+/// any of `matches`.
+#[allow(clippy::manual_repeat_n)] // `repeat_n()` needs Rust 1.82
+fn push_flag(
+    config_args: &mut Vec<OsString>,
+    source: &str,
+    command: &clap::Command,
+    matches: &[&ArgMatches],
+    key: &str,
+    value: &Value,
+) -> Result<()> {
+    let invalid_key = || anyhow!("Invalid key `{key}` in {source}: there is no `--{key}` flag");
+    let arg = command
+        .get_arguments()
+        .find(|arg| arg.get_long() == Some(key))
+        .ok_or_else(invalid_key)?;
+
+    let given_on_command_line = matches
+        .iter()
+        .any(|m| m.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine));
+    if given_on_command_line {
+        return Ok(());
+    }
+
+    let invalid_value = |expected: &str| {
+        anyhow!("Invalid value for `{key}` in {source}: expected {expected}, got `{value}`")
+    };
+    let flag = format!("--{key}");
+    match arg.get_action() {
+        ArgAction::SetTrue => match value {
+            Value::Boolean(true) => config_args.push(flag.into()),
+            Value::Boolean(false) => {}
+            _ => return Err(invalid_value("`true` or `false`")),
+        },
+        ArgAction::Count => {
+            let count = value
+                .as_integer()
+                .and_then(|count| usize::try_from(count).ok())
+                .ok_or_else(|| invalid_value("a non-negative integer"))?;
+            config_args.extend(std::iter::repeat(OsString::from(&flag)).take(count));
+        }
+        _ => {
+            let values = match value {
+                Value::Array(values) => values.iter().collect(),
+                value => vec![value],
+            };
+            for value in values {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    Value::Integer(_) | Value::Float(_) | Value::Boolean(_) => value.to_string(),
+                    _ => return Err(invalid_value("a string, a number, or an array of those")),
+                };
+                config_args.push(format!("{flag}={value}").into());
+            }
+        }
+    }
+
+    Ok(())
+}
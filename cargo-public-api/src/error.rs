@@ -1,9 +1,25 @@
 use public_api::{diff::ChangedPublicItem, PublicItem};
 
+use crate::semver_bump::Bump;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("The API diff is not allowed as per --deny: {0}")]
     DiffDenied(Violations),
+
+    #[error("The API diff is not allowed as per --fail-on-changes: {0}")]
+    ChangesNotAllowed(Violations),
+
+    #[error(
+        "`{old}` to `{new}` is a {given} bump, but the API diff needs a {needed} bump, since {reason}"
+    )]
+    SemverBumpMismatch {
+        old: semver::Version,
+        new: semver::Version,
+        given: Bump,
+        needed: Bump,
+        reason: &'static str,
+    },
 }
 
 #[derive(Debug)]
@@ -47,17 +63,27 @@ impl Violations {
 impl std::fmt::Display for Violations {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if !self.added.is_empty() {
-            write!(f, "Added items not allowed: {:?} ", self.added)?;
+            write!(f, "Added items not allowed: {} ", list(&self.added))?;
         }
 
         if !self.changed.is_empty() {
-            write!(f, "Changed items not allowed: {:?} ", self.changed)?;
+            let changed = self
+                .changed
+                .iter()
+                .map(|c| format!("{} => {}", c.old, c.new));
+            write!(f, "Changed items not allowed: {} ", list(changed))?;
         }
 
         if !self.removed.is_empty() {
-            write!(f, "Removed items not allowed: {:?} ", self.removed)?;
+            write!(f, "Removed items not allowed: {} ", list(&self.removed))?;
         }
 
         Ok(())
     }
 }
+
+/// Formats `items` like `[a, b]`, with each item formatted with `Display`.
+fn list<T: std::fmt::Display>(items: impl IntoIterator<Item = T>) -> String {
+    let items: Vec<String> = items.into_iter().map(|item| item.to_string()).collect();
+    format!("[{}]", items.join(", "))
+}
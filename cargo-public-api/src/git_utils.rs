@@ -83,6 +83,26 @@ fn trimmed_stdout(mut cmd: Command) -> Result<String> {
     }
 }
 
+/// Returns all tags from `first` to `last` (inclusive), in version order. So
+/// `v0.1.0` comes before `v0.1.1` which comes before `v0.10.0`.
+pub fn tags_between(path: impl AsRef<Path>, first: &str, last: &str) -> Result<Vec<String>> {
+    let tags = trimmed_git_stdout(path, &["tag", "--list", "--sort=v:refname"])?;
+    let tags: Vec<&str> = tags.lines().collect();
+    let position = |tag: &str| {
+        tags.iter()
+            .position(|t| *t == tag)
+            .ok_or_else(|| anyhow!("No such git tag: `{tag}`"))
+    };
+    let (first_index, last_index) = (position(first)?, position(last)?);
+    if first_index > last_index {
+        return Err(anyhow!("Tag `{first}` comes after tag `{last}`"));
+    }
+    Ok(tags[first_index..=last_index]
+        .iter()
+        .map(ToString::to_string)
+        .collect())
+}
+
 /// Resolves a git reference provided at the CLI to an actual commit, allowing
 /// us to validate refs and use "relative" values like HEAD and more.
 pub fn resolve_ref(path: impl AsRef<Path>, committish: &str) -> Result<String> {
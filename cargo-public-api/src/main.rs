@@ -1,25 +1,40 @@
 // deny in CI, only warn here
 #![warn(clippy::all)]
 
+use std::collections::HashSet;
 use std::ffi::OsString;
-use std::io::{stderr, stdout};
+use std::fs::File;
+use std::io::{stderr, stdout, Write};
 use std::path::{Path, PathBuf};
 
-use anyhow::{anyhow, bail, Result};
-use api_source::{ApiSource, Commit, CurrentDir, PublishedCrate, RustdocJson};
-use arg_types::{Color, DenyMethod, Omit};
+use anyhow::{anyhow, bail, Context, Result};
+use api_source::{
+    ApiSource, Commit, CurrentDir, LocalDir, PreviouslyPublishedCrate, PublishedCrate, RustdocJson,
+};
+use arg_types::{
+    CapLints, Color, DenyMethod, Emit, Format, MatrixFormat, Omit, ReportFormat, Severity,
+};
 use git_utils::current_branch_or_commit;
 use plain::Plain;
-use public_api::diff::PublicApiDiff;
+use public_api::{
+    diff::{DiffWithContext, PublicApiDiff},
+    Compatibility, CompatibilityMatrix, PublicItem,
+};
 
 use clap::{CommandFactory, Parser};
 
 mod api_source;
 mod arg_types;
+mod baseline_fmt;
+mod cargo_registry;
+mod config;
 mod error;
 mod git_utils;
 mod plain;
 mod published_crate;
+mod report;
+mod semver_bump;
+mod toml_diff;
 mod toolchain;
 mod vendor;
 
@@ -52,10 +67,36 @@ pub struct Args {
     #[arg(global = true, short, long, action = clap::ArgAction::Count)]
     simplified: u8,
 
+    /// Omit items nested deeper than N levels below the crate root.
+    ///
+    /// For example, `--max-depth 1` only lists items at the root of the crate.
+    #[arg(global = true, long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Ignore differences in whitespace, analogous to `git diff -w`.
+    #[arg(global = true, long)]
+    ignore_whitespace: bool,
+
+    /// Omit items of impls derived for commonly derived traits such as `Debug` and `Clone`.
+    ///
+    /// Note that removing e.g. `#[derive(Clone)]` from a public type is a breaking change that
+    /// then does not show up in diffs.
+    #[arg(global = true, long)]
+    strip_boilerplate_derives: bool,
+
     /// Omit specified items.
     #[arg(global = true, long, value_enum, value_delimiter = ',')]
     omit: Option<Vec<Omit>>,
 
+    /// Include impls of auto traits such as `Send` and `Sync`, also when
+    /// `--omit auto-trait-impls` or `-ss` is given.
+    ///
+    /// A type that stops being `Send` or `Sync` breaks users of the type, so
+    /// it can be worth diffing auto trait impls even when other noisy items
+    /// are omitted.
+    #[arg(global = true, long)]
+    include_auto_impls: bool,
+
     /// Space or comma separated list of features to activate
     #[arg(global = true, long, short = 'F')]
     features: Vec<String>,
@@ -72,14 +113,29 @@ pub struct Args {
     #[arg(global = true, long)]
     target: Option<String>,
 
+    /// Build the rustdoc JSON with the given Cargo profile, e.g. a custom
+    /// `[profile.docs]`. Matters for crates whose public API depends on e.g.
+    /// `#[cfg(debug_assertions)]`.
+    #[arg(global = true, long, value_name = "NAME")]
+    profile: Option<String>,
+
     /// When to color the output.
     ///
-    /// By default, `--color=auto` is active. Using just `--color` without an
-    /// arg is equivalent to `--color=always`.
+    /// By default, `--color=auto` is active, which colors the output if stdout
+    /// is a terminal and the `NO_COLOR` environment variable is not set. Using
+    /// just `--color` without an arg is equivalent to `--color=always`.
     #[arg(global = true, long, value_enum)]
     color: Option<Option<Color>>,
 
-    /// List the public API based on the given rustdoc JSON file.
+    /// Use the given pre-built rustdoc JSON file instead of building rustdoc JSON.
+    ///
+    /// Useful in build systems where rustdoc JSON is already a build artifact. The file is used in
+    /// place of the crate in the working tree, both when listing and when diffing. To diff two
+    /// pre-built files, use `diff old.json --rustdoc-json-path new.json`.
+    ///
+    /// The rustdoc JSON must have format version 36, which is what `nightly-2024-10-18` produces
+    /// with `cargo +nightly rustdoc -- -Z unstable-options --output-format json`. Use
+    /// `--json-format-version 36` to fail early if the file has a different format version.
     ///
     /// Example:
     ///
@@ -89,10 +145,10 @@ pub struct Args {
     ///
     /// and then
     ///
-    ///     cargo public-api --rustdoc-json ~/.rustup/toolchains/nightly-x86_64-unknown-linux-gnu/share/doc/rust/json/std.json
+    ///     cargo public-api --rustdoc-json-path ~/.rustup/toolchains/nightly-x86_64-unknown-linux-gnu/share/doc/rust/json/std.json
     ///
-    #[arg(global = true, long, value_name = "RUSTDOC_JSON_PATH", hide = true)]
-    rustdoc_json: Option<String>,
+    #[arg(global = true, long, value_name = "PATH", alias = "rustdoc-json")]
+    rustdoc_json_path: Option<PathBuf>,
 
     /// Show detailed info about processing.
     ///
@@ -114,9 +170,51 @@ pub struct Args {
     #[arg(global = true, long, value_name = "PATH", hide = true)]
     target_dir: Option<PathBuf>,
 
-    /// Forwarded to rustdoc JSON build command
-    #[arg(global = true, long, hide = true)]
-    cap_lints: Option<String>,
+    /// Cap the lint levels of the rustdoc JSON build at LEVEL.
+    ///
+    /// Forwarded as `cargo rustdoc -- --cap-lints LEVEL`, so that e.g. `allow`
+    /// keeps lints of the crate from failing the build. Defaults to `warn`, or
+    /// to `allow` when diffing, since lints in historic versions of a crate can
+    /// typically not be fixed.
+    #[arg(global = true, long, value_enum, value_name = "LEVEL")]
+    cap_lints: Option<CapLints>,
+
+    /// Fail if the rustdoc JSON format version is not exactly N.
+    ///
+    /// The rustdoc JSON format changes with new nightly releases. Use this to
+    /// make sure the rustdoc JSON is not silently misinterpreted.
+    #[arg(global = true, long, value_name = "N")]
+    json_format_version: Option<u32>,
+
+    /// Only build an artifact and print its path to stdout, then exit.
+    ///
+    /// With `--emit rustdoc-json`, the path of the rustdoc JSON of the crate
+    /// is printed on a single line, so that other tools can use it, e.g. with
+    /// `cargo public-api --emit rustdoc-json | xargs my-tool`.
+    #[arg(long, value_enum, value_name = "WHAT")]
+    emit: Option<Emit>,
+
+    /// Also write the listed public API to the given file, as JSON with the
+    /// name and version of the crate, the toolchain, and a timestamp. The file
+    /// can later be diffed against like a rustdoc JSON file, e.g. with
+    /// `cargo public-api diff api.json`.
+    ///
+    /// Useful to e.g. save the public API of a release in CI, and later diff a
+    /// hotfix against it without having to build the release again.
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["emit", "diff_with_published"])]
+    save_json: Option<PathBuf>,
+
+    /// Diff the working tree against the previous published version of the
+    /// crate.
+    ///
+    /// The previous version is the highest version on crates.io that is not
+    /// newer than the version in `Cargo.toml`. Its rustdoc JSON is cached per
+    /// toolchain, features and target in `$CARGO_HOME/public-api-cache/`, or
+    /// in `public-api-cache/` of the target dir if `--target-dir` is used.
+    /// This is the typical CI check: has the public API changed relative to
+    /// what is published?
+    #[arg(long)]
+    diff_with_published: bool,
 
     #[command(subcommand)]
     subcommand: Option<Subcommand>,
@@ -132,6 +230,64 @@ struct ArgsAndToolchain {
     toolchain: Option<String>,
 }
 
+/// The subcommand used for compatibility matrices.
+#[derive(Parser, Debug)]
+struct MatrixArgs {
+    /// The range of git tags to include, on the form `first..last`.
+    #[arg(long, value_name = "FIRST..LAST")]
+    tags: String,
+
+    /// How to format the matrix.
+    #[arg(long, value_enum, default_value_t)]
+    format: MatrixFormat,
+
+    /// Force the git checkouts, discarding working tree changes.
+    #[arg(long)]
+    force: bool,
+}
+
+/// The subcommand that creates a baseline file.
+#[derive(Parser, Debug)]
+struct InitArgs {
+    /// Where to write the baseline file.
+    #[arg(long, value_name = "PATH", default_value = DEFAULT_INIT_BASELINE)]
+    baseline: PathBuf,
+
+    /// Overwrite the baseline file if it already exists.
+    #[arg(long)]
+    force: bool,
+}
+
+/// The subcommand that formats a baseline file.
+#[derive(Parser, Debug)]
+struct FmtArgs {
+    /// The baseline file to format.
+    #[arg(long, value_name = "PATH", default_value = DEFAULT_INIT_BASELINE)]
+    baseline: PathBuf,
+
+    /// Do not write the file, but fail if it is not formatted canonically.
+    #[arg(long)]
+    check: bool,
+}
+
+/// The subcommand used for pre-commit checks.
+#[derive(Parser, Debug)]
+struct CheckArgs {
+    /// The file with the expected public API, as written by `cargo public-api
+    /// > PATH`. Defaults to `public-api.txt` unless `--against` is used.
+    #[arg(long, value_name = "PATH", conflicts_with = "against")]
+    baseline: Option<PathBuf>,
+
+    /// Check the public API of `HEAD` against the given commit instead of
+    /// checking the working tree against a baseline file.
+    #[arg(long, value_name = "REF")]
+    against: Option<String>,
+
+    /// Force the git checkouts, discarding working tree changes.
+    #[arg(long, requires = "against")]
+    force: bool,
+}
+
 /// The subcommand used for diffing.
 #[derive(Parser, Debug)]
 struct DiffArgs {
@@ -142,11 +298,116 @@ struct DiffArgs {
     #[arg(long, value_enum)]
     deny: Option<Vec<DenyMethod>>,
 
+    /// Exit with failure if the public API has changed in any way, including
+    /// additions.
+    ///
+    /// This is stricter than semver requires, and is intended for crates that
+    /// promise that their public API never changes. Same as `--deny=all`, but
+    /// with a simpler mental model.
+    #[arg(long)]
+    fail_on_changes: bool,
+
+    /// Never exit with failure because of the diff. Each line of the diff is
+    /// prefixed with `warning: ` to make it clear that the diff is not
+    /// blocking.
+    ///
+    /// Useful when adopting `cargo public-api` in CI, to see what diffs look
+    /// like before enforcing anything.
+    #[arg(long, conflicts_with_all = ["fail_on_changes", "deny", "version_compare"])]
+    warn_only: bool,
+
+    /// Exit with failure if going from version OLD to version NEW is not the
+    /// semver bump that the API diff needs. Both too small bumps, such as a
+    /// patch bump for breaking changes, and too large bumps, such as a major
+    /// bump without breaking changes, are failures.
+    ///
+    /// Follows the Cargo flavor of semver, e.g. breaking changes to `0.2.3`
+    /// need a bump to `0.3.0`. Before 1.0.0, bumps that are larger than needed
+    /// are accepted, e.g. `0.2.3` to `0.3.0` when items were only added.
+    #[arg(long, num_args = 2, value_names = ["OLD", "NEW"])]
+    version_compare: Option<Vec<semver::Version>>,
+
     /// Force the diff. For example, when diffing commits, enabling this option
     /// will discard working tree changes during git checkouts of other commits.
     #[arg(long)]
     force: bool,
 
+    /// How to format the diff.
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+
+    /// Write the diff to the given file instead of to stdout.
+    #[arg(long, value_name = "PATH")]
+    output_file: Option<PathBuf>,
+
+    /// Also write the diff to the given file, in the format that the extension
+    /// of the file implies: `.txt`, `.json`, `.md` or `.html`. Unknown
+    /// extensions get plain text, with a warning. The diff is still printed
+    /// as usual. Can be given many times to write the diff in many formats.
+    #[arg(long, value_name = "PATH")]
+    report_file: Vec<PathBuf>,
+
+    /// The format of all `--report-file`s, regardless of their extensions.
+    #[arg(long, value_enum, value_name = "FORMAT", requires = "report_file")]
+    report_file_format: Option<ReportFormat>,
+
+    /// Also print the items that did not change, prefixed with a space, to
+    /// show the diff in context like `git diff` does. Only affects the plain
+    /// format.
+    #[arg(long, conflicts_with = "context")]
+    show_unchanged: bool,
+
+    /// Print up to N unchanged items above and below each removed, changed
+    /// and added item, like `git diff -U<N>` does. Adjacent means next to
+    /// each other in the sorted list of all items. Only affects the plain
+    /// format.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    context: usize,
+
+    /// Only show changes at or above the given severity, e.g. only breaking
+    /// changes with `--min-severity breaking`. Removed items are always
+    /// breaking. Changes that are filtered out also do not count for `--deny`
+    /// and `--fail-on-changes`, but they do count for `--version-compare`.
+    #[arg(long, value_enum, value_name = "LEVEL")]
+    min_severity: Option<Severity>,
+
+    /// Fuzzy diff: strip version suffixes such as `_v2`, `_2024` and `V2` from
+    /// all item names before diffing, so that e.g. `read_v3` is diffed as if it
+    /// were named the same as `read_v2`. Items are printed with the suffixes
+    /// stripped.
+    ///
+    /// Optionally takes a regex that matches the suffixes to strip, e.g.
+    /// `--strip-version='_v[0-9]+$'`.
+    #[arg(
+        long,
+        value_name = "REGEX",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = DEFAULT_STRIP_VERSION_REGEX,
+    )]
+    strip_version: Option<regex::Regex>,
+
+    /// Diff the crate in this directory against the crate in `--new-dir`,
+    /// without involving git. Useful when maintaining forks or preparing
+    /// rebases. Both directories must contain a `Cargo.toml`, and are built
+    /// with the same toolchain and features.
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "new_dir",
+        conflicts_with = "args"
+    )]
+    old_dir: Option<PathBuf>,
+
+    /// The directory with the new version of the crate. See `--old-dir`.
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "old_dir",
+        conflicts_with = "args"
+    )]
+    new_dir: Option<PathBuf>,
+
     #[clap(verbatim_doc_comment)]
     /// What to diff.
     ///
@@ -235,6 +496,82 @@ enum Subcommand {
     #[clap(verbatim_doc_comment)]
     Diff(DiffArgs),
 
+    /// Print a compatibility matrix for a range of git tags.
+    ///
+    /// Every tag is diffed against every other tag in the range. Entry `[i][j]` of the matrix is
+    /// `compatible`, `minor` or `breaking` depending on what kind of semver change it is to go from
+    /// tag `i` to tag `j`. Tags are checked out in the same way as when diffing commits.
+    ///
+    /// EXAMPLE:
+    /// ========
+    ///
+    ///     cargo public-api matrix --tags v0.1.0..v1.0.0
+    #[clap(verbatim_doc_comment)]
+    Matrix(MatrixArgs),
+
+    /// Check that the public API has no breaking changes. Intended to be used as a pre-commit hook.
+    ///
+    /// By default the public API of the working tree is compared to the baseline file
+    /// `public-api.txt`. Items that are missing compared to the baseline are breaking changes. With
+    /// `--against REF`, the public API of `HEAD` is instead diffed against the commit `REF`, with
+    /// the same git checkouts as when diffing commits.
+    ///
+    /// A summary of the changes is printed. The exit code is non-zero if there are breaking
+    /// changes.
+    ///
+    /// EXAMPLES:
+    /// =========
+    ///
+    /// Create the baseline file and commit it:
+    ///
+    ///     cargo public-api > public-api.txt
+    ///
+    /// Install as a git pre-commit hook:
+    ///
+    ///     echo 'exec cargo public-api check' > .git/hooks/pre-commit
+    ///     chmod +x .git/hooks/pre-commit
+    ///
+    /// Check `HEAD` against the previous commit:
+    ///
+    ///     cargo public-api check --against HEAD~1
+    #[clap(verbatim_doc_comment)]
+    Check(CheckArgs),
+
+    /// Create a baseline file with the current public API, to check against later.
+    ///
+    /// The public API of the working tree is written to `.public-api-baseline.txt`, or to the
+    /// file given with `--baseline`. Instructions on how to check the public API against the
+    /// baseline are printed afterwards. Add the baseline file to version control so that e.g. CI
+    /// can check against it.
+    ///
+    /// EXAMPLES:
+    /// =========
+    ///
+    /// Create the baseline file and check against it:
+    ///
+    ///     cargo public-api init
+    ///     cargo public-api check --baseline .public-api-baseline.txt
+    #[clap(verbatim_doc_comment)]
+    Init(InitArgs),
+
+    /// Format a baseline file canonically, like `rustfmt` formats code.
+    ///
+    /// Whitespace is normalized and empty lines are removed. The order of the items is kept, so
+    /// files written by `cargo public-api` are already formatted. The crate is not built. Formatting an already formatted file does not change it.
+    ///
+    /// EXAMPLES:
+    /// =========
+    ///
+    /// Format the baseline file in place:
+    ///
+    ///     cargo public-api fmt --baseline .public-api-baseline.txt
+    ///
+    /// Fail, e.g. in CI, if the baseline file is not formatted:
+    ///
+    ///     cargo public-api fmt --baseline .public-api-baseline.txt --check
+    #[clap(verbatim_doc_comment)]
+    Fmt(FmtArgs),
+
     /// Generate completion scripts for many different shells.
     ///
     /// Example on how to generate and install the completion script for zsh:
@@ -246,7 +583,7 @@ enum Subcommand {
     ///    $ autoload -U compinit && compinit
     ///    $ cargo public-api --{{Tab}}
     ///    --all-features         -- Activate all available features
-    ///    --cap-lints            -- Forwarded to rustdoc JSON build command
+    ///    --cap-lints            -- Cap the lint levels of the rustdoc JSON build at LEVEL
     ///    --color                -- When to color the output
     ///    --debug-sorting        -- Show the hidden "sorting prefix" that makes items nicely grouped
     ///    [...]
@@ -267,9 +604,42 @@ enum MainTask {
         old_api: Box<dyn ApiSource>,
         new_api: Box<dyn ApiSource>,
     },
+    /// Print the compatibility matrix of many git tags.
+    PrintMatrix {
+        tags: Vec<(String, Commit)>,
+        format: MatrixFormat,
+    },
+    /// Check the public API against a baseline.
+    Check {
+        baseline: CheckBaseline,
+    },
+    /// Write the public API to a new baseline file.
+    Init {
+        baseline: PathBuf,
+        force: bool,
+    },
+    /// Format a baseline file.
+    Fmt {
+        baseline: PathBuf,
+        check: bool,
+    },
+    /// Build rustdoc JSON and print its path.
+    EmitRustdocJson,
     GenerateShellCompletionScript(clap_complete_command::Shell),
 }
 
+/// What `cargo public-api check` compares the public API to.
+enum CheckBaseline {
+    /// The working tree is compared to a file with the expected public API.
+    File(PathBuf),
+    /// `HEAD` is diffed against the commit `against`.
+    Commit {
+        against: String,
+        old: Commit,
+        head: Commit,
+    },
+}
+
 /// This represents an action that we want to do at some point.
 pub enum Action {
     /// The `--deny` arg allows the user to disallow the occurrence of API
@@ -279,6 +649,18 @@ pub enum Action {
         deny: Vec<DenyMethod>,
     },
 
+    /// The `--fail-on-changes` arg allows the user to disallow any API
+    /// changes. We are to check that the diff is empty.
+    CheckNoChanges { diff: PublicApiDiff },
+
+    /// The `--version-compare` arg allows the user to require that the semver
+    /// bump between two versions matches the diff.
+    CheckVersionBump {
+        old: semver::Version,
+        new: semver::Version,
+        diff: PublicApiDiff,
+    },
+
     /// Doing a `--diff-git-checkouts` involves doing `git checkout`s.
     /// Afterwards, we want to restore the original branch the user was on, to
     /// not mess up their work tree.
@@ -289,6 +671,13 @@ pub enum Action {
 /// published version of a given crate.
 const LATEST_VERSION_ARG: &str = "latest";
 
+/// The baseline file that `cargo public-api init` writes by default.
+const DEFAULT_INIT_BASELINE: &str = ".public-api-baseline.txt";
+
+/// The suffixes that `--strip-version` strips by default, e.g. `_v2`, `_2024`
+/// and `V2`.
+const DEFAULT_STRIP_VERSION_REGEX: &str = "(_v?|V)[0-9]+$";
+
 fn main_() -> Result<()> {
     // We use the same underlying tracing library as the Rust compiler. Run with
     // the env var `RUST_LOG` set to e.g. `debug` to get started.
@@ -297,7 +686,7 @@ fn main_() -> Result<()> {
         .with_writer(stderr) // See https://github.com/tokio-rs/tracing/issues/2492
         .init();
 
-    let argst = get_args();
+    let argst = get_args()?;
 
     // A list of actions to perform after we have listed or diffed. Typical
     // examples: restore a git branch or check that a diff is allowed
@@ -323,6 +712,11 @@ fn main_() -> Result<()> {
             new_api.as_ref(),
             &mut final_actions,
         ),
+        MainTask::PrintMatrix { tags, format } => print_matrix(&argst, tags, format),
+        MainTask::Check { baseline } => check(&argst, &baseline),
+        MainTask::Init { baseline, force } => init(&argst, &baseline, force),
+        MainTask::Fmt { baseline, check } => fmt(&baseline, check),
+        MainTask::EmitRustdocJson => emit_rustdoc_json(&argst),
         MainTask::GenerateShellCompletionScript(shell) => {
             shell.generate(
                 &mut Args::command().bin_name("cargo-public-api"),
@@ -342,8 +736,39 @@ fn main_() -> Result<()> {
 }
 
 fn main_task(args: &Args) -> Result<MainTask> {
+    if let Some(Emit::RustdocJson) = args.emit {
+        if args.subcommand.is_some() {
+            bail!("`--emit` can not be combined with a subcommand");
+        }
+        return Ok(MainTask::EmitRustdocJson);
+    }
+
+    if args.save_json.is_some() && args.subcommand.is_some() {
+        bail!("`--save-json` can not be combined with a subcommand");
+    }
+
+    if args.diff_with_published {
+        if args.subcommand.is_some() {
+            bail!("`--diff-with-published` can not be combined with a subcommand");
+        }
+        return Ok(MainTask::print_diff(
+            PreviouslyPublishedCrate.boxed(),
+            args.working_tree_api_source(),
+        ));
+    }
+
     match &args.subcommand {
         Some(Subcommand::Diff(diff_args)) => main_task_from_diff_args(args, diff_args),
+        Some(Subcommand::Matrix(matrix_args)) => main_task_from_matrix_args(args, matrix_args),
+        Some(Subcommand::Check(check_args)) => main_task_from_check_args(args, check_args),
+        Some(Subcommand::Init(init_args)) => Ok(MainTask::Init {
+            baseline: init_args.baseline.clone(),
+            force: init_args.force,
+        }),
+        Some(Subcommand::Fmt(fmt_args)) => Ok(MainTask::Fmt {
+            baseline: fmt_args.baseline.clone(),
+            check: fmt_args.check,
+        }),
         Some(Subcommand::Completions { shell }) => {
             Ok(MainTask::GenerateShellCompletionScript(*shell))
         }
@@ -352,11 +777,7 @@ fn main_task(args: &Args) -> Result<MainTask> {
 }
 
 fn main_task_from_args(args: &Args) -> MainTask {
-    if let Some(rustdoc_json) = &args.rustdoc_json {
-        MainTask::print_list(RustdocJson::new(rustdoc_json.into()).boxed())
-    } else {
-        MainTask::print_list(CurrentDir.boxed())
-    }
+    MainTask::print_list(args.working_tree_api_source())
 }
 
 fn arg_to_api_source(arg: Option<&str>) -> Result<Box<dyn ApiSource>> {
@@ -378,6 +799,13 @@ fn main_task_from_diff_args(args: &Args, diff_args: &DiffArgs) -> Result<MainTas
         )
     }
 
+    if let (Some(old_dir), Some(new_dir)) = (&diff_args.old_dir, &diff_args.new_dir) {
+        return Ok(MainTask::print_diff(
+            LocalDir::new(old_dir)?.boxed(),
+            LocalDir::new(new_dir)?.boxed(),
+        ));
+    }
+
     let first_arg = diff_args.args.first();
     let second_arg = diff_args.args.get(1);
 
@@ -398,13 +826,23 @@ fn main_task_from_diff_args(args: &Args, diff_args: &DiffArgs) -> Result<MainTas
         (Some(first), None)
             if semver::Version::parse(first).is_ok() || first == LATEST_VERSION_ARG =>
         {
-            MainTask::print_diff(PublishedCrate::new(Some(first)).boxed(), CurrentDir.boxed())
+            MainTask::print_diff(
+                PublishedCrate::new(Some(first)).boxed(),
+                args.working_tree_api_source(),
+            )
         }
+        (Some(first), None) if is_json_file(first) => MainTask::print_diff(
+            RustdocJson::new(first.into()).boxed(),
+            args.working_tree_api_source(),
+        ),
         (Some(first), Some(second)) => MainTask::print_diff(
             arg_to_api_source(Some(first))?,
             arg_to_api_source(Some(second))?,
         ),
-        (None, _) => MainTask::print_diff(PublishedCrate::new(None).boxed(), CurrentDir.boxed()),
+        (None, _) => MainTask::print_diff(
+            PublishedCrate::new(None).boxed(),
+            args.working_tree_api_source(),
+        ),
         (Some(first), None) => {
             bail!("Invalid published crate version syntax: {first}");
         }
@@ -413,9 +851,62 @@ fn main_task_from_diff_args(args: &Args, diff_args: &DiffArgs) -> Result<MainTas
     Ok(main_task)
 }
 
+fn main_task_from_matrix_args(args: &Args, matrix_args: &MatrixArgs) -> Result<MainTask> {
+    let Some((first, last)) = matrix_args.tags.split_once("..") else {
+        bail!(
+            "Invalid tag range syntax: {}. Use: tag1..tag2",
+            matrix_args.tags
+        );
+    };
+
+    let tags = git_utils::tags_between(args.git_root()?, first, last)?
+        .into_iter()
+        .map(|tag| Commit::new(args, &tag).map(|commit| (tag, commit)))
+        .collect::<Result<_>>()?;
+
+    Ok(MainTask::PrintMatrix {
+        tags,
+        format: matrix_args.format,
+    })
+}
+
+fn main_task_from_check_args(args: &Args, check_args: &CheckArgs) -> Result<MainTask> {
+    let baseline = match (&check_args.baseline, &check_args.against) {
+        (_, Some(against)) => CheckBaseline::Commit {
+            against: against.clone(),
+            old: Commit::new(args, against)?,
+            head: Commit::new(args, "HEAD")?,
+        },
+        (Some(path), None) => CheckBaseline::File(path.clone()),
+        (None, None) => CheckBaseline::File(PathBuf::from("public-api.txt")),
+    };
+
+    Ok(MainTask::Check { baseline })
+}
+
 /// We were requested to deny diffs, so make sure there is no diff
 fn check_diff(deny: &[DenyMethod], diff: &PublicApiDiff) -> Result<()> {
-    let mut violations = crate::error::Violations::new();
+    let violations = violations(deny, diff);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(error::Error::DiffDenied(violations)))
+    }
+}
+
+/// We were requested to fail on any changes, so make sure the diff is empty
+fn check_no_changes(diff: &PublicApiDiff) -> Result<()> {
+    if diff.is_empty() {
+        Ok(())
+    } else {
+        let violations = violations(&[DenyMethod::All], diff);
+        Err(anyhow!(error::Error::ChangesNotAllowed(violations)))
+    }
+}
+
+/// The items of `diff` that are denied by `deny`
+fn violations(deny: &[DenyMethod], diff: &PublicApiDiff) -> error::Violations {
+    let mut violations = error::Violations::new();
     for d in deny {
         if d.deny_added() && !diff.added.is_empty() {
             violations.extend_added(diff.added.iter().cloned());
@@ -427,15 +918,16 @@ fn check_diff(deny: &[DenyMethod], diff: &PublicApiDiff) -> Result<()> {
             violations.extend_removed(diff.removed.iter().cloned());
         }
     }
-
-    if violations.is_empty() {
-        Ok(())
-    } else {
-        Err(anyhow!(error::Error::DiffDenied(violations)))
-    }
+    violations
 }
 
 fn print_public_items(argst: &ArgsAndToolchain, public_api: &dyn ApiSource) -> Result<()> {
+    if let Some(path) = &argst.args.save_json {
+        let snapshot = public_api.obtain_snapshot(argst)?;
+        Plain::print_items(&mut stdout(), &argst.args, snapshot.items.iter())?;
+        return api_source::write_saved_api(path, snapshot);
+    }
+
     Plain::print_items(
         &mut stdout(),
         &argst.args,
@@ -445,6 +937,12 @@ fn print_public_items(argst: &ArgsAndToolchain, public_api: &dyn ApiSource) -> R
     Ok(())
 }
 
+fn emit_rustdoc_json(argst: &ArgsAndToolchain) -> Result<()> {
+    let path = api_source::rustdoc_json_for_current_dir(argst)?;
+    println!("{}", path.display());
+    Ok(())
+}
+
 fn print_diff(
     argst: &ArgsAndToolchain,
     old: &dyn ApiSource,
@@ -460,10 +958,68 @@ fn print_diff(
 
     let old = old.obtain_api(argst)?;
     let new = new.obtain_api(argst)?;
-    let diff = PublicApiDiff::between(old, new);
+    let diff_args = argst.args.diff_args();
+    let (old, new) = match diff_args.and_then(|a| a.strip_version.as_ref()) {
+        Some(regex) => {
+            eprintln!(
+                "Fuzzy diff: version suffixes matching `{regex}` are stripped from item names"
+            );
+            let strip = |name: &str| regex.replace(name, "").into_owned();
+            (old.rename_items(strip), new.rename_items(strip))
+        }
+        None => (old, new),
+    };
+    let context = diff_args.map_or(0, |a| a.context);
+    let show_unchanged = diff_args.is_some_and(|a| a.show_unchanged);
+    let new_items: Option<Vec<PublicItem>> =
+        (show_unchanged || context > 0).then(|| new.items().cloned().collect());
+    let full_diff = PublicApiDiff::between(old, new);
+    let diff = match diff_args.and_then(|a| a.min_severity) {
+        Some(min) => full_diff.clone().with_min_severity(min.change_severity()),
+        None => full_diff.clone(),
+    };
+
+    let mut w: Box<dyn Write> = match diff_args.and_then(|a| a.output_file.as_ref()) {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(stdout()),
+    };
+    let format = diff_args.map(|a| a.format).unwrap_or_default();
+    if format == Format::Plain && diff_args.is_some_and(|a| a.warn_only) {
+        w = Box::new(plain::LinePrefix::new(w, "warning: "));
+    }
+    match format {
+        Format::Plain => match new_items {
+            Some(new_items) if show_unchanged => Plain::print_annotated_diff(
+                &mut w,
+                &argst.args,
+                &diff.with_context(diff.unchanged_items(&new_items)),
+            )?,
+            Some(new_items) if !diff.is_empty() => Plain::print_diff_with_context(
+                &mut w,
+                &argst.args,
+                &DiffWithContext::new(&diff, &new_items, context),
+            )?,
+            _ => Plain::print_diff(&mut w, &argst.args, &diff)?,
+        },
+        Format::Html => write!(w, "{}", diff.to_html())?,
+        Format::Toml => write!(w, "{}", toml_diff::diff_to_toml(&diff)?)?,
+    }
 
-    Plain::print_diff(&mut stdout(), &argst.args, &diff)?;
+    for path in diff_args.map_or(&[][..], |a| &a.report_file) {
+        let format = diff_args.and_then(|a| a.report_file_format);
+        report::write_report(path, format, &diff)?;
+    }
 
+    if diff_args.is_some_and(|a| a.fail_on_changes) {
+        final_actions.push(Action::CheckNoChanges { diff: diff.clone() });
+    }
+    if let Some([old, new]) = diff_args.and_then(|a| a.version_compare.as_deref()) {
+        final_actions.push(Action::CheckVersionBump {
+            old: old.clone(),
+            new: new.clone(),
+            diff: full_diff,
+        });
+    }
     if let Some(Some(deny)) = argst.args.diff_args().map(|a| &a.deny) {
         final_actions.push(check_diff(deny, diff));
     }
@@ -471,6 +1027,169 @@ fn print_diff(
     Ok(())
 }
 
+fn print_matrix(
+    argst: &ArgsAndToolchain,
+    tags: Vec<(String, Commit)>,
+    format: MatrixFormat,
+) -> Result<()> {
+    let mut versions = vec![];
+    for (tag, commit) in tags {
+        versions.push((tag, commit.obtain_api(argst)?));
+    }
+
+    let matrix = CompatibilityMatrix::new(versions);
+    match format {
+        MatrixFormat::Csv => print!("{}", matrix.to_csv()),
+        MatrixFormat::Json => println!("{}", matrix.to_json()),
+    }
+
+    Ok(())
+}
+
+fn check(argst: &ArgsAndToolchain, baseline: &CheckBaseline) -> Result<()> {
+    let (description, removed, added, breaking) = match baseline {
+        CheckBaseline::File(path) => {
+            let expected = std::fs::read_to_string(path).with_context(|| {
+                format!(
+                    "Failed to read the baseline file {path:?}. Create it with:\n\n    {}",
+                    update_baseline_command(&argst.args, path)
+                )
+            })?;
+            let expected: Vec<&str> = expected.lines().filter(|l| !l.is_empty()).collect();
+            let actual: Vec<String> = (argst.args.working_tree_api_source())
+                .obtain_api(argst)?
+                .items()
+                .map(ToString::to_string)
+                .collect();
+
+            // Baselines of big crates have tens of thousands of lines, so
+            // look lines up in sets rather than scanning the other side
+            let expected_set: HashSet<&str> = expected.iter().copied().collect();
+            let actual_set: HashSet<&str> = actual.iter().map(String::as_str).collect();
+            let removed: Vec<String> = expected
+                .iter()
+                .filter(|line| !actual_set.contains(*line))
+                .map(ToString::to_string)
+                .collect();
+            let added: Vec<String> = actual
+                .iter()
+                .filter(|item| !expected_set.contains(item.as_str()))
+                .cloned()
+                .collect();
+            let breaking = !removed.is_empty();
+            (format!("{path:?}"), removed, added, breaking)
+        }
+        CheckBaseline::Commit { against, old, head } => {
+            let diff = PublicApiDiff::between(old.obtain_api(argst)?, head.obtain_api(argst)?);
+            let breaking = Compatibility::of(&diff) == Compatibility::Breaking;
+            let removed = (diff.removed.iter())
+                .chain(diff.changed.iter().map(|c| &c.old))
+                .map(ToString::to_string)
+                .collect();
+            let added = (diff.changed.iter().map(|c| &c.new))
+                .chain(diff.added.iter())
+                .map(ToString::to_string)
+                .collect();
+            (format!("`{against}`"), removed, added, breaking)
+        }
+    };
+
+    if removed.is_empty() && added.is_empty() {
+        println!("The public API is unchanged compared to {description}");
+        return Ok(());
+    }
+
+    println!(
+        "The public API has {} removed and {} added items compared to {description}:",
+        removed.len(),
+        added.len(),
+    );
+    for item in &removed {
+        println!("-{item}");
+    }
+    for item in &added {
+        println!("+{item}");
+    }
+
+    if let CheckBaseline::File(path) = baseline {
+        println!(
+            "If the changes are intentional, update the baseline with: {}",
+            update_baseline_command(&argst.args, path)
+        );
+    }
+
+    if breaking {
+        bail!("The public API has breaking changes compared to {description}");
+    }
+
+    Ok(())
+}
+
+fn init(argst: &ArgsAndToolchain, baseline: &Path, force: bool) -> Result<()> {
+    if baseline.exists() && !force {
+        bail!("The baseline file {baseline:?} already exists. Use `--force` to overwrite it");
+    }
+
+    let public_api = argst.args.working_tree_api_source().obtain_api(argst)?;
+    let mut w = std::io::BufWriter::new(
+        File::create(baseline).with_context(|| format!("Failed to create {baseline:?}"))?,
+    );
+    for item in public_api.items() {
+        writeln!(w, "{item}")?;
+    }
+    w.flush()?;
+
+    let package = (argst.args.package.as_ref())
+        .map(|package| format!(" -p {package}"))
+        .unwrap_or_default();
+    let path = baseline.display();
+    println!(
+        "Wrote the public API to {baseline:?}. Add it to version control with:
+
+    git add {path}
+
+Then check the public API against it, e.g. in CI, with:
+
+    cargo public-api{package} check --baseline {path}
+
+If the public API changes intentionally, update the baseline with:
+
+    {}",
+        update_baseline_command(&argst.args, baseline),
+    );
+
+    Ok(())
+}
+
+fn fmt(baseline: &Path, check: bool) -> Result<()> {
+    let text = std::fs::read_to_string(baseline)
+        .with_context(|| format!("Failed to read the baseline file {baseline:?}"))?;
+    let formatted = baseline_fmt::format_baseline(&text);
+    if formatted == text {
+        return Ok(());
+    }
+
+    if check {
+        bail!(
+            "The baseline file {baseline:?} is not formatted. Format it with:\n\n    cargo public-api fmt --baseline {}",
+            baseline.display()
+        );
+    }
+    std::fs::write(baseline, formatted)
+        .with_context(|| format!("Failed to write the baseline file {baseline:?}"))
+}
+
+/// The command that writes the current public API to the baseline file at
+/// `path`.
+fn update_baseline_command(args: &Args, path: &Path) -> String {
+    let package = args
+        .package
+        .as_ref()
+        .map(|package| format!(" -p {package}"))
+        .unwrap_or_default();
+    format!("cargo public-api{package} > {}", path.display())
+}
+
 impl MainTask {
     fn print_list(api: Box<dyn ApiSource>) -> MainTask {
         Self::PrintList { api }
@@ -486,7 +1205,12 @@ impl MainTask {
                 old_api.changes_commit() || new_api.changes_commit()
             }
             MainTask::PrintList { api } => api.changes_commit(),
-            MainTask::GenerateShellCompletionScript(_) => false,
+            MainTask::PrintMatrix { .. } => true,
+            MainTask::Check { baseline } => matches!(baseline, CheckBaseline::Commit { .. }),
+            MainTask::Init { .. }
+            | MainTask::Fmt { .. }
+            | MainTask::EmitRustdocJson
+            | MainTask::GenerateShellCompletionScript(_) => false,
         }
     }
 }
@@ -497,6 +1221,12 @@ impl Action {
             Action::CheckDiff { deny, diff } => {
                 check_diff(deny, diff)?;
             }
+            Action::CheckNoChanges { diff } => {
+                check_no_changes(diff)?;
+            }
+            Action::CheckVersionBump { old, new, diff } => {
+                semver_bump::check(old, new, diff)?;
+            }
             Action::RestoreBranch { name } => {
                 git_checkout(args, name)?;
             }
@@ -511,7 +1241,7 @@ impl Args {
     }
 
     fn omit_auto_trait_impls(&self) -> bool {
-        self.omits(Omit::AutoTraitImpls)
+        !self.include_auto_impls && self.omits(Omit::AutoTraitImpls)
     }
 
     fn omit_auto_derived_impls(&self) -> bool {
@@ -522,10 +1252,28 @@ impl Args {
         self.omit.iter().flatten().any(|o| *o == to_omit)
     }
 
+    /// Where to get the public API of the crate in the working tree from.
+    /// Either by building rustdoc JSON, or from `--rustdoc-json-path`.
+    fn working_tree_api_source(&self) -> Box<dyn ApiSource> {
+        match &self.rustdoc_json_path {
+            Some(path) => RustdocJson::new(path.clone()).boxed(),
+            None => CurrentDir.boxed(),
+        }
+    }
+
     fn git_root(&self) -> Result<PathBuf> {
         git_utils::git_root_from_manifest_path(self.manifest_path.as_path())
     }
 
+    fn force(&self) -> bool {
+        match &self.subcommand {
+            Some(Subcommand::Diff(diff_args)) => diff_args.force,
+            Some(Subcommand::Matrix(matrix_args)) => matrix_args.force,
+            Some(Subcommand::Check(check_args)) => check_args.force,
+            _ => false,
+        }
+    }
+
     fn diff_args(&self) -> Option<&DiffArgs> {
         match &self.subcommand {
             Some(Subcommand::Diff(diff_args)) => Some(diff_args),
@@ -541,16 +1289,18 @@ impl Args {
 /// Note that we also want to support the binary being installed with a
 /// non-standard name such as `~/.cargo/bin/cargo-public-api-v0.13.0`. So we
 /// can't assume the bin name is `cargo-public-api`.
-fn get_args() -> ArgsAndToolchain {
+///
+/// Default args from the config file are also added, see [`config`].
+fn get_args() -> Result<ArgsAndToolchain> {
     let subcommand_name = subcommand_name(std::env::args_os().next().unwrap());
     let args_os = std::env::args_os()
         .enumerate()
         .filter(|(index, arg)| *index != 1 || Some(arg) != subcommand_name.as_ref())
         .map(|(_, arg)| arg);
 
-    let mut args = Args::parse_from(args_os);
+    let mut args = Args::parse_from(config::args_with_config(args_os.collect())?);
     resolve_simplified(&mut args);
-    resolve_toolchain(args)
+    Ok(resolve_toolchain(args))
 }
 
 /// Strips the `cargo-` prefix from the bin name as well as any extension. For
@@ -606,21 +1356,14 @@ fn resolve_simplified(args: &mut Args) {
 }
 
 fn is_json_file(file_name: impl AsRef<str>) -> bool {
-    Path::extension(Path::new(file_name.as_ref())).map_or(false, |a| a.eq_ignore_ascii_case("json"))
+    Path::extension(Path::new(file_name.as_ref())).is_some_and(|a| a.eq_ignore_ascii_case("json"))
 }
 
 /// Helper to reduce code duplication. We can't add [`Args`] to
 /// [`git_utils::git_checkout()`] itself, because it is used in contexts where
 /// [`Args`] is not available (namely in tests).
 fn git_checkout(args: &Args, commit: &str) -> Result<()> {
-    git_utils::git_checkout(
-        &args.git_root()?,
-        commit,
-        !args.verbose,
-        args.diff_args()
-            .map(|diff_args| diff_args.force)
-            .unwrap_or_default(),
-    )
+    git_utils::git_checkout(&args.git_root()?, commit, !args.verbose, args.force())
 }
 
 /// Wrapper to handle <https://github.com/rust-lang/rust/issues/46016>
@@ -1,7 +1,11 @@
 use std::io::{Result, Write};
 
 use nu_ansi_term::{AnsiString, AnsiStrings, Color, Style};
-use public_api::{diff::PublicApiDiff, tokens::Token, PublicItem};
+use public_api::{
+    diff::{AnnotatedDiff, AnnotatedItem, ChangedPublicItem, DiffWithContext, PublicApiDiff},
+    tokens::Token,
+    PublicItem,
+};
 
 use crate::Args;
 
@@ -21,57 +25,167 @@ impl Plain {
     }
 
     pub fn print_diff(w: &mut dyn Write, args: &Args, diff: &PublicApiDiff) -> Result<()> {
-        let use_color = color_active(args.color);
+        Self::print_diff_with_color(w, color_active(args.color), diff)
+    }
 
+    /// Like [`Self::print_diff`], but with colors only if `use_color` is
+    /// `true`, regardless of `--color`. For output that is not for terminals.
+    pub fn print_diff_with_color(
+        w: &mut dyn Write,
+        use_color: bool,
+        diff: &PublicApiDiff,
+    ) -> Result<()> {
         print_items_with_header(
             w,
             "Removed items from the public API",
             &diff.removed,
-            |w, item| {
-                if use_color {
-                    writeln!(w, "-{}", color_item(item))
-                } else {
-                    writeln!(w, "-{item}")
-                }
-            },
+            |w, item| print_removed(w, use_color, item),
         )?;
 
         print_items_with_header(
             w,
             "Changed items in the public API",
             &diff.changed,
-            |w, changed_item| {
-                if use_color {
-                    let old_tokens: Vec<&Token> = changed_item.old.tokens().collect();
-                    let new_tokens: Vec<&Token> = changed_item.new.tokens().collect();
-                    let diff_slice = diff::slice(old_tokens.as_slice(), new_tokens.as_slice());
-                    writeln!(
-                        w,
-                        "-{}\n+{}",
-                        color_item_with_diff(&diff_slice, true),
-                        color_item_with_diff(&diff_slice, false),
-                    )
-                } else {
-                    writeln!(w, "-{}\n+{}", changed_item.old, changed_item.new)
-                }
-            },
+            |w, changed_item| print_changed(w, use_color, changed_item),
         )?;
 
         print_items_with_header(
             w,
             "Added items to the public API",
             &diff.added,
-            |w, item| {
-                if use_color {
-                    writeln!(w, "+{}", color_item(item))
-                } else {
-                    writeln!(w, "+{item}")
-                }
-            },
+            |w, item| print_added(w, use_color, item),
         )?;
 
         Ok(())
     }
+
+    /// Prints all items in one list, like `git diff` prints lines in context.
+    pub fn print_annotated_diff(
+        w: &mut dyn Write,
+        args: &Args,
+        diff: &AnnotatedDiff,
+    ) -> Result<()> {
+        let use_color = color_active(args.color);
+
+        for item in &diff.items {
+            match item {
+                AnnotatedItem::Unchanged(item) => {
+                    if use_color {
+                        writeln!(w, " {}", color_item(item))?;
+                    } else {
+                        writeln!(w, " {item}")?;
+                    }
+                }
+                AnnotatedItem::Removed(item) => print_removed(w, use_color, item)?,
+                AnnotatedItem::Changed(changed_item) => print_changed(w, use_color, changed_item)?,
+                AnnotatedItem::Added(item) => print_added(w, use_color, item)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prints `diff` like its [`std::fmt::Display`] impl does, but with each
+    /// hunk in color like [`Self::print_annotated_diff`] prints it, if colors
+    /// are active.
+    pub fn print_diff_with_context(
+        w: &mut dyn Write,
+        args: &Args,
+        diff: &DiffWithContext,
+    ) -> Result<()> {
+        if !color_active(args.color) {
+            return write!(w, "{diff}");
+        }
+
+        for (index, hunk) in diff.hunks.iter().enumerate() {
+            if index > 0 {
+                writeln!(w, "...")?;
+            }
+            Self::print_annotated_diff(w, args, hunk)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes `prefix` at the start of every non-empty line written through it,
+/// e.g. to mark diff lines as warnings with `--warn-only`.
+pub struct LinePrefix<W> {
+    inner: W,
+    prefix: &'static str,
+    at_line_start: bool,
+}
+
+impl<W: Write> LinePrefix<W> {
+    pub fn new(inner: W, prefix: &'static str) -> Self {
+        Self {
+            inner,
+            prefix,
+            at_line_start: true,
+        }
+    }
+}
+
+impl<W: Write> Write for LinePrefix<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        for line in buf.split_inclusive(|b| *b == b'\n') {
+            if self.at_line_start && line != b"\n" {
+                self.inner.write_all(self.prefix.as_bytes())?;
+            }
+            self.inner.write_all(line)?;
+            self.at_line_start = line.ends_with(b"\n");
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn print_removed(w: &mut dyn Write, use_color: bool, item: &PublicItem) -> Result<()> {
+    if use_color {
+        writeln!(w, "-{}", color_item(item))
+    } else {
+        writeln!(w, "-{item}")
+    }
+}
+
+fn print_changed(
+    w: &mut dyn Write,
+    use_color: bool,
+    changed_item: &ChangedPublicItem,
+) -> Result<()> {
+    if use_color {
+        // Items that only differ in their cfg look the same without it
+        let cfg_tokens = |item: &PublicItem| match item.cfg_attribute() {
+            Some(cfg) if changed_item.changes_cfg() => {
+                vec![Token::Annotation(cfg), Token::Whitespace]
+            }
+            _ => vec![],
+        };
+        let (old_cfg, new_cfg) = (cfg_tokens(&changed_item.old), cfg_tokens(&changed_item.new));
+        let old_tokens: Vec<&Token> = old_cfg.iter().chain(changed_item.old.tokens()).collect();
+        let new_tokens: Vec<&Token> = new_cfg.iter().chain(changed_item.new.tokens()).collect();
+        let diff_slice = diff::slice(old_tokens.as_slice(), new_tokens.as_slice());
+        writeln!(
+            w,
+            "-{}\n+{}",
+            color_item_with_diff(&diff_slice, true),
+            color_item_with_diff(&diff_slice, false),
+        )
+    } else {
+        let (old, new) = changed_item.diff_lines();
+        writeln!(w, "-{old}\n+{new}")
+    }
+}
+
+fn print_added(w: &mut dyn Write, use_color: bool, item: &PublicItem) -> Result<()> {
+    if use_color {
+        writeln!(w, "+{}", color_item(item))
+    } else {
+        writeln!(w, "+{item}")
+    }
 }
 
 fn print_item(args: &Args, w: &mut dyn Write, item: &PublicItem) -> Result<()> {
@@ -118,18 +232,43 @@ fn color_item_token(token: &Token, bg: Option<Color>) -> AnsiString<'_> {
     };
     match token {
         Token::Symbol(text) => style(Style::default(), text),
+        Token::Operator(text) => style(Style::new().bold(), text),
         Token::Qualifier(text) => style(Color::Blue.into(), text),
         Token::Kind(text) => style(Color::Blue.into(), text),
+        Token::FnKeyword
+        | Token::StructKeyword
+        | Token::EnumKeyword
+        | Token::TraitKeyword
+        | Token::ImplKeyword
+        | Token::DynKeyword
+        | Token::TypeKeyword
+        | Token::ConstKeyword
+        | Token::StaticKeyword
+        | Token::ModKeyword => style(Color::Blue.into(), token.text()),
+        Token::NegativeImpl => style(Style::new().bold(), token.text()),
+        Token::SharedRef | Token::MutRef => style(Style::default(), token.text()),
+        Token::GenericOpen | Token::GenericClose => style(Style::default(), token.text()),
+        Token::PathSeparator => style(Style::default(), token.text()),
+        Token::BoundSeparator => style(Style::default(), token.text()),
+        Token::BoundCombiner | Token::OptionalBound => style(Style::new().bold(), token.text()),
+        Token::ArrayOpen | Token::ArraySemicolon | Token::ArrayClose => {
+            style(Style::default(), token.text())
+        }
+        Token::TupleOpen | Token::TupleClose => style(Style::default(), token.text()),
         Token::Whitespace => style(Style::default(), " "),
         Token::Identifier(text) => style(Color::Cyan.into(), text),
         Token::Annotation(text) => style(Style::default(), text),
         Token::Self_(text) => style(Color::Blue.into(), text),
+        Token::SelfType => style(Color::Blue.into(), token.text()),
         Token::Function(text) => style(Color::Yellow.into(), text),
         Token::Lifetime(text) => style(Color::Blue.into(), text),
         Token::Keyword(text) => style(Color::Blue.into(), text),
         Token::Generic(text) => style(Color::Green.into(), text),
         Token::Primitive(text) => style(Color::Green.into(), text),
-        Token::Type(text) => style(Color::Green.into(), text),
+        Token::Type(text) | Token::ImplTrait(text) => style(Color::Green.into(), text),
+        Token::Doc(text) => style(Style::new().dimmed(), text),
+        // Tokens added to later versions of `public-api`
+        _ => style(Style::default(), token.text()),
     }
 }
 
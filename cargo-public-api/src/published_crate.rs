@@ -11,9 +11,36 @@ use std::path::PathBuf;
 pub fn build_rustdoc_json(version: Option<&str>, argst: &ArgsAndToolchain) -> Result<PathBuf> {
     let args = &argst.args;
     let package_name = package_name_from_args(args).ok_or_else(|| anyhow!("You must specify a package with either `-p package-name` or `--manifest-path path/to/Cargo.toml`"))?;
-    let crate_ = http_get_crate(&package_name, args.verbose)?;
-    let crate_version = get_crate_version(&crate_, version)?;
-    let build_dir = build_dir(args, &crate_version);
+
+    // If Cargo has already downloaded the exact version, depend on the
+    // downloaded sources instead, so that nothing needs to be downloaded
+    let cached = version
+        .filter(|version| *version != LATEST_VERSION_ARG)
+        .and_then(|version| {
+            let source = crate::cargo_registry::cached_crate_source(&package_name, version)?;
+            Some((version, source))
+        });
+    let spec = if let Some((version, source)) = cached {
+        if args.verbose {
+            eprintln!("Using {source:?} from the local Cargo registry cache");
+        }
+        DependencySpec {
+            name: package_name,
+            version: version.to_owned(),
+            features: crate::cargo_registry::crate_features(&source),
+            path: Some(source),
+        }
+    } else {
+        let crate_ = http_get_crate(&package_name, args.verbose)?;
+        let crate_version = get_crate_version(&crate_, version)?;
+        DependencySpec {
+            name: crate_version.name().to_owned(),
+            version: crate_version.version().to_owned(),
+            features: crate_version.features().keys().cloned().collect(),
+            path: None,
+        }
+    };
+    let build_dir = build_dir(args, &spec);
     std::fs::create_dir_all(&build_dir)?;
 
     let write_file = |name: &str, contents: &str| -> std::io::Result<PathBuf> {
@@ -24,7 +51,7 @@ pub fn build_rustdoc_json(version: Option<&str>, argst: &ArgsAndToolchain) -> Re
     };
 
     write_file("lib.rs", "// empty lib")?;
-    let manifest = manifest_for(args, &crate_version)?;
+    let manifest = manifest_for(args, &spec)?;
     let manifest = write_file("Cargo.toml", &manifest)?;
 
     // Since we used `crate::builder_from_args(args)` above it means that if
@@ -37,10 +64,23 @@ pub fn build_rustdoc_json(version: Option<&str>, argst: &ArgsAndToolchain) -> Re
         .features(Vec::<&str>::new())
         .no_default_features(false)
         .manifest_path(manifest)
-        .package(crate_version.name());
+        .package(&spec.name);
     crate::api_source::build_rustdoc_json(builder)
 }
 
+/// The published crate that the dummy project depends on.
+struct DependencySpec {
+    name: String,
+    version: String,
+
+    /// The names of all features of the crate, for `--all-features`.
+    features: Vec<String>,
+
+    /// The dir with the sources of the crate in the local registry cache, if
+    /// Cargo has already downloaded it.
+    path: Option<PathBuf>,
+}
+
 fn get_crate_version(crate_: &Crate, version: Option<&str>) -> Result<Version, anyhow::Error> {
     match version {
         Some(LATEST_VERSION_ARG) | None => {
@@ -71,6 +111,38 @@ fn get_crate_version(crate_: &Crate, version: Option<&str>) -> Result<Version, a
     }
 }
 
+/// Resolves `--diff-with-published` to the highest published version of the
+/// package that is not newer than the version of the package in the working
+/// tree, i.e. the version that the working tree is a continuation of. Returns
+/// the name of the package together with the version.
+pub fn resolve_previous_version(args: &Args) -> Result<(String, String)> {
+    let package_name = package_name_from_args(args).ok_or_else(|| anyhow!("You must specify a package with either `-p package-name` or `--manifest-path path/to/Cargo.toml`"))?;
+    let current = cargo_manifest::Manifest::from_path(args.manifest_path.as_path())
+        .ok()
+        .and_then(|manifest| manifest.package?.version?.as_local())
+        .ok_or_else(|| {
+            anyhow!(
+                "Could not find the version of `{package_name}` in {:?}",
+                args.manifest_path
+            )
+        })?;
+    let current = semver::Version::parse(&current)?;
+
+    let crate_ = http_get_crate(&package_name, args.verbose)?;
+    let previous = crate_
+        .versions()
+        .iter()
+        .filter(|version| !version.is_yanked())
+        .filter_map(|version| semver::Version::parse(version.version()).ok())
+        .filter(|version| *version <= current)
+        .max()
+        .ok_or_else(|| {
+            anyhow!("Could not find a published version of `{package_name}` that is not newer than {current}")
+        })?;
+    eprintln!("Resolved `--diff-with-published` to `diff {previous}`");
+    Ok((package_name, previous.to_string()))
+}
+
 /// Returns the package name from `-p package-name` or from inside
 /// `--manifest-path Cargo.toml`.
 fn package_name_from_args(args: &Args) -> Option<String> {
@@ -85,7 +157,7 @@ fn package_name_from_args(args: &Args) -> Option<String> {
 /// For users we prefer a non-temporary dir so repeated builds can be
 /// incremental. But when tests run, they will set `args.target_dir` to a
 /// temporary dir so that tests can run in parallel without interference.
-fn build_dir(args: &Args, crate_version: &crates_index::Version) -> PathBuf {
+fn build_dir(args: &Args, spec: &DependencySpec) -> PathBuf {
     let mut build_dir = if let Some(target_dir) = &args.target_dir {
         target_dir.clone()
     } else {
@@ -94,15 +166,17 @@ fn build_dir(args: &Args, crate_version: &crates_index::Version) -> PathBuf {
 
     build_dir.push("cargo-public-api");
     build_dir.push("build-root-for-published-crates");
-    build_dir.push(crate_version.name());
+    build_dir.push(&spec.name);
     build_dir.push("-");
-    build_dir.push(crate_version.version());
+    build_dir.push(&spec.version);
     build_dir
 }
 
 /// Creates a manifest with a dependency so we can "trick" cargo into
-/// downloading the dependency for us.
-fn manifest_for(args: &Args, spec: &crates_index::Version) -> Result<String> {
+/// downloading the dependency for us. The empty `[workspace]` makes sure that
+/// the dummy project is never regarded as part of an enclosing workspace,
+/// e.g. if `--target-dir` is within one.
+fn manifest_for(args: &Args, spec: &DependencySpec) -> Result<String> {
     let setup = toml::toml! {
         [package]
         name = "crate-downloader"
@@ -110,6 +184,7 @@ fn manifest_for(args: &Args, spec: &crates_index::Version) -> Result<String> {
         edition = "2021"
         [lib]
         path = "lib.rs"
+        [workspace]
     };
 
     let Args {
@@ -121,12 +196,16 @@ fn manifest_for(args: &Args, spec: &crates_index::Version) -> Result<String> {
 
     Ok(format!(
         "{setup}\n[dependencies.{}]\n{}",
-        spec.name(),
+        spec.name,
         toml::to_string(&cargo_manifest::DependencyDetail {
-            version: Some(format!("={}", spec.version())),
+            version: Some(format!("={}", spec.version)),
+            path: spec
+                .path
+                .as_ref()
+                .map(|path| path.to_string_lossy().into_owned()),
             default_features: no_default_features.then(|| false),
             features: if *all_features {
-                Some(spec.features().keys().map(Clone::clone).collect())
+                Some(spec.features.clone())
             } else if !features.is_empty() {
                 Some(features.clone())
             } else {
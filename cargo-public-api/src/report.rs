@@ -0,0 +1,98 @@
+//! Writes diff reports to files. See `--report-file`.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use public_api::diff::PublicApiDiff;
+
+use crate::arg_types::ReportFormat;
+use crate::plain::Plain;
+
+/// Writes `diff` to `path` in `format`. Without a `format`, the format is
+/// inferred from the extension of `path`. Unknown extensions get plain text,
+/// with a warning.
+pub fn write_report(path: &Path, format: Option<ReportFormat>, diff: &PublicApiDiff) -> Result<()> {
+    let format = format
+        .or_else(|| ReportFormat::from_extension(path))
+        .unwrap_or_else(|| {
+            eprintln!("Warning: unknown report file extension of {path:?}, writing plain text");
+            ReportFormat::Txt
+        });
+
+    let report = match format {
+        ReportFormat::Txt => {
+            let mut txt = vec![];
+            Plain::print_diff_with_color(&mut txt, false, diff)?;
+            String::from_utf8(txt)?
+        }
+        ReportFormat::Json => diff.to_pretty_json(),
+        ReportFormat::Md => diff_to_md(diff),
+        ReportFormat::Html => diff.to_html(),
+    };
+    std::fs::write(path, report).with_context(|| format!("Failed to write {path:?}"))
+}
+
+/// Renders `diff` as a Markdown document with one section per category. The
+/// items of each section are in a `diff` code block, so that they are
+/// highlighted like the plain format is colored.
+fn diff_to_md(diff: &PublicApiDiff) -> String {
+    let mut md = String::from("# Public API diff\n");
+
+    let removed: Vec<String> = diff.removed.iter().map(|item| format!("-{item}")).collect();
+    push_section(&mut md, "Removed items", &removed);
+
+    let changed: Vec<String> = (diff.changed.iter())
+        .flat_map(|c| [format!("-{}", c.old), format!("+{}", c.new)])
+        .collect();
+    push_section(&mut md, "Changed items", &changed);
+
+    let added: Vec<String> = diff.added.iter().map(|item| format!("+{item}")).collect();
+    push_section(&mut md, "Added items", &added);
+
+    md
+}
+
+/// Pushes a `##` heading followed by `lines` in a `diff` code block, or by
+/// `(none)` if there are no lines.
+fn push_section(md: &mut String, heading: &str, lines: &[String]) {
+    let _ = write!(md, "\n## {heading}\n\n");
+    if lines.is_empty() {
+        md.push_str("(none)\n");
+        return;
+    }
+
+    md.push_str("```diff\n");
+    for line in lines {
+        let _ = writeln!(md, "{line}");
+    }
+    md.push_str("```\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff_to_md;
+    use public_api::diff::PublicApiDiff;
+
+    #[test]
+    fn empty_diff_to_md() {
+        assert_eq!(
+            diff_to_md(&PublicApiDiff::default()),
+            "\
+# Public API diff
+
+## Removed items
+
+(none)
+
+## Changed items
+
+(none)
+
+## Added items
+
+(none)
+"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Checks that the semver bump between two versions matches an API diff. See
+//! `--version-compare`.
+
+use anyhow::{anyhow, bail, Result};
+use public_api::{diff::PublicApiDiff, Compatibility};
+use semver::Version;
+
+use crate::error::Error;
+
+/// What part of a version that a version bump increments. Ordered from the
+/// smallest to the largest bump.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl std::fmt::Display for Bump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Major => "major",
+            Self::Minor => "minor",
+            Self::Patch => "patch",
+        })
+    }
+}
+
+/// Makes sure that going from `old` to `new` is the bump that `diff` needs.
+/// Too small bumps are errors, and so are too large bumps unless `old` is a
+/// `0.x.y` version, see [`accepts`].
+pub fn check(old: &Version, new: &Version, diff: &PublicApiDiff) -> Result<()> {
+    let given = given(old, new)?;
+    let compatibility = Compatibility::of(diff);
+    let needed = needed(old, compatibility);
+    if given == needed {
+        eprintln!("The {given} bump from `{old}` to `{new}` matches the API diff");
+        Ok(())
+    } else if accepts(old, given, needed) {
+        eprintln!(
+            "The {given} bump from `{old}` to `{new}` is larger than the {needed} bump that the API diff needs, which is fine before 1.0.0"
+        );
+        Ok(())
+    } else {
+        Err(anyhow!(Error::SemverBumpMismatch {
+            old: old.clone(),
+            new: new.clone(),
+            given,
+            needed,
+            reason: reason(compatibility),
+        }))
+    }
+}
+
+/// The bump that going from `old` to `new` is.
+fn given(old: &Version, new: &Version) -> Result<Bump> {
+    if new <= old {
+        bail!("`{new}` is not a version bump of `{old}`");
+    }
+
+    Ok(if new.major != old.major {
+        Bump::Major
+    } else if new.minor != old.minor {
+        Bump::Minor
+    } else {
+        Bump::Patch
+    })
+}
+
+/// The bump of `old` that a change with `compatibility` needs. Follows the
+/// Cargo flavor of semver, where the left-most non-zero component of a version
+/// is the one that signals breaking changes. For example, a breaking change to
+/// `0.2.3` needs a minor bump to `0.3.0`.
+fn needed(old: &Version, compatibility: Compatibility) -> Bump {
+    match (old.major, old.minor, compatibility) {
+        (0, 0, _) => Bump::Patch,
+        (0, _, Compatibility::Breaking) => Bump::Minor,
+        (0, _, _) => Bump::Patch,
+        (_, _, Compatibility::Breaking) => Bump::Major,
+        (_, _, Compatibility::Minor) => Bump::Minor,
+        (_, _, Compatibility::Compatible) => Bump::Patch,
+    }
+}
+
+/// Returns `true` if `given` is an acceptable bump of `old` when `needed` is
+/// the bump that the API diff needs. Before 1.0.0 there is no bump reserved
+/// for additions, so e.g. going from `0.2.3` to `0.3.0` when only items were
+/// added is common and accepted. From 1.0.0 on, the bump must be exactly the
+/// needed one.
+fn accepts(old: &Version, given: Bump, needed: Bump) -> bool {
+    if old.major == 0 {
+        given >= needed
+    } else {
+        given == needed
+    }
+}
+
+fn reason(compatibility: Compatibility) -> &'static str {
+    match compatibility {
+        Compatibility::Compatible => "the public API is unchanged",
+        Compatibility::Minor => "items were added or changed in backwards compatible ways",
+        Compatibility::Breaking => "items were removed or changed in breaking ways",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn needed_bump_follows_cargo_semver() {
+        let needed =
+            |old: &str, compatibility| needed(&Version::parse(old).unwrap(), compatibility);
+
+        assert_eq!(needed("1.2.3", Compatibility::Breaking), Bump::Major);
+        assert_eq!(needed("1.2.3", Compatibility::Minor), Bump::Minor);
+        assert_eq!(needed("1.2.3", Compatibility::Compatible), Bump::Patch);
+        assert_eq!(needed("0.2.3", Compatibility::Breaking), Bump::Minor);
+        assert_eq!(needed("0.2.3", Compatibility::Minor), Bump::Patch);
+        assert_eq!(needed("0.0.3", Compatibility::Breaking), Bump::Patch);
+    }
+
+    #[test]
+    fn larger_bumps_are_only_accepted_before_1_0_0() {
+        let accepts =
+            |old: &str, given, needed| accepts(&Version::parse(old).unwrap(), given, needed);
+
+        assert!(accepts("0.2.3", Bump::Minor, Bump::Patch));
+        assert!(accepts("0.2.3", Bump::Major, Bump::Minor));
+        assert!(accepts("0.0.3", Bump::Minor, Bump::Patch));
+        assert!(!accepts("0.2.3", Bump::Patch, Bump::Minor));
+        assert!(accepts("1.2.3", Bump::Minor, Bump::Minor));
+        assert!(!accepts("1.2.3", Bump::Major, Bump::Minor));
+        assert!(!accepts("1.2.3", Bump::Patch, Bump::Minor));
+    }
+
+    #[test]
+    fn given_bump() {
+        let given = |old: &str, new: &str| {
+            given(&Version::parse(old).unwrap(), &Version::parse(new).unwrap())
+        };
+
+        assert_eq!(given("1.0.0", "2.0.0").unwrap(), Bump::Major);
+        assert_eq!(given("1.0.0", "1.1.0").unwrap(), Bump::Minor);
+        assert_eq!(given("1.0.0", "1.0.1").unwrap(), Bump::Patch);
+        assert!(given("1.0.0", "1.0.0").is_err());
+    }
+}
@@ -0,0 +1,52 @@
+//! Renders a [`PublicApiDiff`] as TOML, for programmatic consumption.
+
+use anyhow::Result;
+use public_api::{diff::PublicApiDiff, PublicItem};
+use serde::Serialize;
+
+/// The TOML document. Each category becomes an array of tables, e.g.
+/// `[[removed]]`.
+#[derive(Serialize)]
+struct TomlDiff {
+    removed: Vec<TomlItem>,
+    changed: Vec<TomlChangedItem>,
+    added: Vec<TomlItem>,
+}
+
+#[derive(Serialize)]
+struct TomlChangedItem {
+    old: TomlItem,
+    new: TomlItem,
+}
+
+#[derive(Serialize)]
+struct TomlItem {
+    path: Vec<String>,
+    signature: String,
+}
+
+impl From<&PublicItem> for TomlItem {
+    fn from(item: &PublicItem) -> Self {
+        Self {
+            path: item.path().to_vec(),
+            signature: item.to_string(),
+        }
+    }
+}
+
+/// Renders `diff` as a TOML document with `[[removed]]`, `[[changed]]` and
+/// `[[added]]` sections. Every item has a `path` array and a `signature`
+/// string. Changed items have an `old` and a `new` item.
+pub fn diff_to_toml(diff: &PublicApiDiff) -> Result<String> {
+    let toml_diff = TomlDiff {
+        removed: diff.removed.iter().map(TomlItem::from).collect(),
+        changed: (diff.changed.iter())
+            .map(|c| TomlChangedItem {
+                old: TomlItem::from(&c.old),
+                new: TomlItem::from(&c.new),
+            })
+            .collect(),
+        added: diff.added.iter().map(TomlItem::from).collect(),
+    };
+    Ok(toml::to_string(&toml_diff)?)
+}
@@ -60,6 +60,16 @@ fn list_public_items_omit_blanket_impls() {
         .success();
 }
 
+#[test]
+fn list_public_items_strip_boilerplate_derives() {
+    let mut cmd = TestCmd::as_subcommand_without_args().with_test_repo();
+    cmd.arg("-ss");
+    cmd.arg("--strip-boilerplate-derives");
+    cmd.assert()
+        .stdout_or_update("./expected-output/strip-boilerplate-derives.txt")
+        .success();
+}
+
 #[test]
 fn list_public_items_omit_auto_trait_impls_impls() {
     let mut cmd = TestCmd::as_subcommand_without_args().with_test_repo();
@@ -98,6 +108,87 @@ fn list_public_items_omit_auto_derived_impls_with_triple_s() {
         .success();
 }
 
+#[test]
+fn list_public_items_include_auto_impls_with_double_s() {
+    let mut cmd = TestCmd::as_subcommand_without_args().with_test_repo();
+    cmd.arg("-ss");
+    cmd.arg("--include-auto-impls");
+    cmd.assert()
+        .stdout_or_update("./expected-output/omit-blanket-impls.txt")
+        .success();
+}
+
+#[test]
+fn config_file_provides_default_args() {
+    let mut cmd = TestCmd::as_subcommand_without_args().with_test_repo();
+    std::fs::write(
+        cmd.test_repo_path().join("public-api.toml"),
+        "omit = [\"blanket-impls\", \"auto-trait-impls\"]\n",
+    )
+    .unwrap();
+    cmd.assert()
+        .stdout_or_update("./expected-output/test_repo_api_latest.txt")
+        .success();
+}
+
+#[test]
+fn cargo_toml_metadata_provides_default_args() {
+    let mut cmd = TestCmd::as_subcommand_without_args().with_test_repo();
+    let mut manifest = OpenOptions::new()
+        .append(true)
+        .open(cmd.test_repo_path().join("Cargo.toml"))
+        .unwrap();
+    manifest
+        .write_all(b"\n[package.metadata.public-api]\nsimplified = 2\n")
+        .unwrap();
+    cmd.assert()
+        .stdout_or_update("./expected-output/test_repo_api_latest.txt")
+        .success();
+}
+
+#[test]
+fn command_line_args_override_config() {
+    let mut cmd = TestCmd::as_subcommand_without_args().with_test_repo();
+    std::fs::write(
+        cmd.test_repo_path().join("public-api.toml"),
+        "omit = [\"blanket-impls\", \"auto-trait-impls\", \"auto-derived-impls\"]\n",
+    )
+    .unwrap();
+    cmd.args(["--omit", "blanket-impls,auto-trait-impls"]);
+    cmd.assert()
+        .stdout_or_update("./expected-output/test_repo_api_latest.txt")
+        .success();
+}
+
+#[test]
+fn config_file_provides_default_subcommand_args() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    std::fs::write(
+        cmd.test_repo_path().join("public-api.toml"),
+        "[diff]\ndeny = [\"removed\"]\n",
+    )
+    .unwrap();
+    cmd.args(["diff", "v0.2.0..v0.3.0", "--force"]);
+    cmd.assert()
+        .stderr(contains("The API diff is not allowed as per --deny"))
+        .failure();
+}
+
+#[test]
+fn invalid_config_key() {
+    let mut cmd = TestCmd::as_subcommand_without_args().with_test_repo();
+    std::fs::write(
+        cmd.test_repo_path().join("public-api.toml"),
+        "no-such-flag = true\n",
+    )
+    .unwrap();
+    cmd.assert()
+        .stderr(contains(
+            "Invalid key `no-such-flag` in \"public-api.toml\": there is no `--no-such-flag` flag",
+        ))
+        .failure();
+}
+
 #[test]
 fn list_public_items_with_lint_error() {
     let mut cmd = TestCmd::new().with_separate_target_dir();
@@ -323,6 +414,22 @@ fn target_arg() {
         .success();
 }
 
+#[test]
+fn profile_arg() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["--profile", "dev"]);
+    cmd.assert()
+        .stdout_or_update("./expected-output/test_repo_api_latest.txt")
+        .success();
+}
+
+#[test]
+fn profile_arg_with_unknown_profile() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["--profile", "no-such-profile"]);
+    cmd.assert().failure();
+}
+
 #[test]
 fn virtual_manifest_error() {
     let mut cmd = TestCmd::new().with_separate_target_dir();
@@ -446,6 +553,134 @@ fn diff_public_items() {
     assert_eq!(branch_before, branch_after);
 }
 
+#[test]
+fn matrix_between_tags() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["matrix", "--tags", "v0.1.0..v0.3.0"]);
+    cmd.assert()
+        .stdout_or_update("./expected-output/matrix_v0.1.0_to_v0.3.0.csv")
+        .success();
+}
+
+#[test]
+fn check_against_unchanged_baseline() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["check", "--baseline"]);
+    cmd.arg(
+        Path::new("./tests/expected-output/test_repo_api_latest.txt")
+            .canonicalize()
+            .unwrap(),
+    );
+    cmd.assert()
+        .stdout(contains("The public API is unchanged"))
+        .success();
+}
+
+#[test]
+fn check_against_baseline_with_removed_item() {
+    let baseline_dir = tempdir().unwrap();
+    let baseline = baseline_dir.path().join("public-api.txt");
+    let mut expected =
+        std::fs::read_to_string("./tests/expected-output/test_repo_api_latest.txt").unwrap();
+    expected.push_str("pub fn example_api::removed()\n");
+    std::fs::write(&baseline, expected).unwrap();
+
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["check", "--baseline"]);
+    cmd.arg(&baseline);
+    cmd.assert()
+        .stdout(
+            contains("-pub fn example_api::removed()")
+                .and(contains("update the baseline with: cargo public-api >")),
+        )
+        .stderr(contains("The public API has breaking changes"))
+        .failure();
+}
+
+#[test]
+fn init_creates_baseline_to_check_against() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("init");
+    cmd.assert()
+        .stdout(
+            contains("git add .public-api-baseline.txt")
+                .and(contains("check --baseline .public-api-baseline.txt")),
+        )
+        .success();
+    let baseline = cmd.test_repo_path().join(".public-api-baseline.txt");
+    assert_eq!(
+        std::fs::read_to_string(&baseline).unwrap(),
+        std::fs::read_to_string("./tests/expected-output/test_repo_api_latest.txt").unwrap(),
+    );
+
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["check", "--baseline"]);
+    cmd.arg(&baseline);
+    cmd.assert()
+        .stdout(contains("The public API is unchanged"))
+        .success();
+}
+
+#[test]
+fn init_does_not_overwrite_baseline() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    std::fs::write(cmd.test_repo_path().join("api.txt"), "").unwrap();
+    cmd.args(["init", "--baseline", "api.txt"]);
+    cmd.assert()
+        .stderr(contains("already exists. Use `--force` to overwrite it"))
+        .failure();
+}
+
+#[test]
+fn fmt_formats_baseline() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    let baseline = cmd.test_repo_path().join("api.txt");
+    std::fs::write(&baseline, "pub fn krate::b()  \n\npub   fn krate::a()\n").unwrap();
+    cmd.args(["fmt", "--baseline", "api.txt", "--check"]);
+    cmd.assert().stderr(contains("is not formatted")).failure();
+
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["fmt", "--baseline"]);
+    cmd.arg(&baseline);
+    cmd.assert().success();
+    assert_eq!(
+        std::fs::read_to_string(&baseline).unwrap(),
+        "pub fn krate::b()\npub fn krate::a()\n"
+    );
+
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["fmt", "--check", "--baseline"]);
+    cmd.arg(&baseline);
+    cmd.assert().success();
+}
+
+#[test]
+fn init_writes_formatted_baseline() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("init");
+    cmd.assert().success();
+    let baseline = cmd.test_repo_path().join(".public-api-baseline.txt");
+
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["fmt", "--check", "--baseline"]);
+    cmd.arg(&baseline);
+    cmd.assert().success();
+}
+
+#[test]
+fn check_against_commit_with_breaking_change() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["check", "--against", "v0.2.0"]);
+    cmd.assert()
+        .stdout(contains(
+            "-pub fn example_api::function(v1_param: example_api::Struct, v2_param: usize)",
+        ))
+        .stderr(contains(
+            "The public API has breaking changes compared to `v0.2.0`",
+        ))
+        .failure();
+}
+
 #[test]
 fn diff_public_items_with_subcommand() {
     let mut cmd = TestCmd::new().with_test_repo();
@@ -711,6 +946,197 @@ fn deny_with_diff() {
         .failure();
 }
 
+#[test]
+fn fail_on_changes_without_diff() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.1.1");
+    cmd.arg("--fail-on-changes");
+    cmd.assert().success();
+}
+
+#[test]
+fn fail_on_changes_with_diff() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.2.0");
+    cmd.arg("--fail-on-changes");
+    cmd.assert()
+        .stderr(contains(
+            "The API diff is not allowed as per --fail-on-changes: Added items not allowed",
+        ))
+        .failure();
+}
+
+#[test]
+fn diff_show_unchanged() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.2.0");
+    cmd.arg("--show-unchanged");
+    cmd.assert()
+        .stdout_or_update("./expected-output/example_api_diff_v0.1.0_to_v0.2.0_show_unchanged.txt")
+        .success();
+}
+
+#[test]
+fn diff_context() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.2.0");
+    cmd.args(["--context", "1"]);
+    cmd.assert()
+        .stdout_or_update("./expected-output/example_api_diff_v0.1.0_to_v0.2.0_context.txt")
+        .success();
+}
+
+#[test]
+fn diff_context_with_show_unchanged() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.2.0");
+    cmd.args(["--context", "1"]);
+    cmd.arg("--show-unchanged");
+    cmd.assert()
+        .stderr(contains("cannot be used with"))
+        .failure();
+}
+
+#[test]
+fn diff_min_severity() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.2.0");
+    cmd.args(["--min-severity", "breaking"]);
+    cmd.assert()
+        .stdout_or_update(
+            "./expected-output/example_api_diff_v0.1.0_to_v0.2.0_min_severity_breaking.txt",
+        )
+        .success();
+}
+
+#[test]
+fn diff_strip_version() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.2.0");
+    cmd.arg("--strip-version");
+    cmd.assert()
+        .stdout_or_update("./expected-output/example_api_diff_v0.1.0_to_v0.2.0_strip_version.txt")
+        .stderr(contains(
+            "Fuzzy diff: version suffixes matching `(_v?|V)[0-9]+$` are stripped",
+        ))
+        .success();
+}
+
+#[test]
+fn diff_warn_only() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.2.0");
+    cmd.arg("--warn-only");
+    cmd.assert()
+        .stdout_or_update("./expected-output/example_api_diff_v0.1.0_to_v0.2.0_warn_only.txt")
+        .success();
+}
+
+#[test]
+fn diff_warn_only_with_fail_on_changes() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.2.0");
+    cmd.arg("--warn-only");
+    cmd.arg("--fail-on-changes");
+    cmd.assert()
+        .stderr(contains("cannot be used with"))
+        .failure();
+}
+
+#[test]
+fn diff_old_dir_and_new_dir() {
+    let mut cmd = TestCmd::new().with_separate_target_dir();
+    cmd.arg("diff");
+    cmd.args(["--old-dir", "../test-apis/example_api-v0.1.0"]);
+    cmd.args(["--new-dir", "../test-apis/example_api-v0.2.0"]);
+    cmd.assert()
+        .stdout_or_update("./expected-output/example_api_diff_v0.1.0_to_v0.2.0.txt")
+        .success();
+}
+
+#[test]
+fn diff_old_dir_without_cargo_toml() {
+    let mut cmd = TestCmd::new().with_separate_target_dir();
+    cmd.arg("diff");
+    cmd.args(["--old-dir", "../test-apis"]);
+    cmd.args(["--new-dir", "../test-apis/example_api-v0.2.0"]);
+    cmd.assert()
+        .stderr(contains(
+            "The directory \"../test-apis\" does not contain a `Cargo.toml`",
+        ))
+        .failure();
+}
+
+#[test]
+fn diff_old_dir_that_fails_to_build() {
+    let mut cmd = TestCmd::new().with_separate_target_dir();
+    cmd.arg("diff");
+    cmd.args(["--old-dir", "../test-apis/virtual-manifest"]);
+    cmd.args(["--new-dir", "../test-apis/example_api-v0.2.0"]);
+    cmd.assert()
+        .stderr(contains("Failed to build the crate at"))
+        .failure();
+}
+
+#[test]
+fn diff_version_compare_matching_bump() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.2.0");
+    cmd.args(["--version-compare", "0.1.0", "0.2.0"]);
+    cmd.assert()
+        .stderr(contains(
+            "The minor bump from `0.1.0` to `0.2.0` matches the API diff",
+        ))
+        .success();
+}
+
+#[test]
+fn diff_version_compare_too_small_bump() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.2.0");
+    cmd.args(["--version-compare", "0.1.0", "0.1.1"]);
+    cmd.assert()
+        .stderr(contains(
+            "`0.1.0` to `0.1.1` is a patch bump, but the API diff needs a minor bump, since items were removed or changed in breaking ways",
+        ))
+        .failure();
+}
+
+#[test]
+fn diff_version_compare_larger_bump_before_1_0_0() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.1.1");
+    cmd.args(["--version-compare", "0.1.0", "0.2.0"]);
+    cmd.assert()
+        .stderr(contains(
+            "The minor bump from `0.1.0` to `0.2.0` is larger than the patch bump that the API diff needs, which is fine before 1.0.0",
+        ))
+        .success();
+}
+
+#[test]
+fn diff_version_compare_too_large_bump() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("diff");
+    cmd.arg("v0.1.0..v0.1.1");
+    cmd.args(["--version-compare", "1.0.0", "2.0.0"]);
+    cmd.assert()
+        .stderr(contains("is a major bump, but the API diff needs a"))
+        .failure();
+}
+
 #[test]
 fn deny_with_diff_with_subcommand() {
     let mut cmd = TestCmd::new().with_test_repo();
@@ -830,6 +1256,24 @@ fn list_public_items_with_color_impl(color_arg: &str) {
         .success();
 }
 
+#[test]
+fn list_public_items_with_no_color_env() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.cmd().env("NO_COLOR", "1");
+    cmd.arg("--color=auto");
+    cmd.assert().stdout(contains("\x1b[").not()).success();
+}
+
+#[test]
+fn list_public_items_with_no_color_env_and_color_always() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.cmd().env("NO_COLOR", "1");
+    cmd.arg("--color=always");
+    cmd.assert()
+        .stdout_or_update("./expected-output/example_api_v0.3.0_colored.txt")
+        .success();
+}
+
 #[test]
 fn diff_public_items_from_files_with_subcommand() {
     // Create independent build dirs so all tests can run in parallel
@@ -847,6 +1291,127 @@ fn diff_public_items_from_files_with_subcommand() {
         .success();
 }
 
+#[test]
+fn diff_public_items_from_file_and_rustdoc_json_path() {
+    // Create independent build dirs so all tests can run in parallel
+    let build_dir = tempdir().unwrap();
+    let build_dir2 = tempdir().unwrap();
+
+    let old = rustdoc_json_path_for_crate("../test-apis/example_api-v0.1.0", &build_dir);
+    let new = rustdoc_json_path_for_crate("../test-apis/example_api-v0.2.0", &build_dir2);
+    let mut cmd = TestCmd::new().with_separate_target_dir();
+    cmd.arg("--rustdoc-json-path");
+    cmd.arg(new);
+    cmd.arg("diff");
+    cmd.arg(old);
+    cmd.assert()
+        .stdout_or_update("./expected-output/example_api_diff_v0.1.0_to_v0.2.0.txt")
+        .success();
+}
+
+#[test]
+fn diff_public_items_from_files_to_html_file() {
+    // Create independent build dirs so all tests can run in parallel
+    let build_dir = tempdir().unwrap();
+    let build_dir2 = tempdir().unwrap();
+    let report_dir = tempdir().unwrap();
+    let report = report_dir.path().join("report.html");
+
+    let old = rustdoc_json_path_for_crate("../test-apis/example_api-v0.1.0", &build_dir);
+    let new = rustdoc_json_path_for_crate("../test-apis/example_api-v0.2.0", &build_dir2);
+    let mut cmd = TestCmd::new().with_separate_target_dir();
+    cmd.arg("diff");
+    cmd.args(["--format", "html", "--output-file"]);
+    cmd.arg(&report);
+    cmd.arg(old);
+    cmd.arg(new);
+    cmd.assert().stdout("").success();
+
+    let html = std::fs::read_to_string(report).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains(
+        "<tr class=\"added\"><td><code>pub struct example_api::StructV2</code></td></tr>"
+    ));
+}
+
+#[test]
+fn diff_public_items_from_files_to_report_files() {
+    // Create independent build dirs so all tests can run in parallel
+    let build_dir = tempdir().unwrap();
+    let build_dir2 = tempdir().unwrap();
+    let report_dir = tempdir().unwrap();
+    let md = report_dir.path().join("report.md");
+    let json = report_dir.path().join("report.json");
+
+    let old = rustdoc_json_path_for_crate("../test-apis/example_api-v0.1.0", &build_dir);
+    let new = rustdoc_json_path_for_crate("../test-apis/example_api-v0.2.0", &build_dir2);
+    let mut cmd = TestCmd::new().with_separate_target_dir();
+    cmd.arg("diff");
+    cmd.arg("--report-file");
+    cmd.arg(&md);
+    cmd.arg("--report-file");
+    cmd.arg(&json);
+    cmd.arg(old);
+    cmd.arg(new);
+    cmd.assert()
+        .stdout_or_update("./expected-output/example_api_diff_v0.1.0_to_v0.2.0.txt")
+        .success();
+
+    let md = std::fs::read_to_string(md).unwrap();
+    assert!(md.starts_with("# Public API diff\n"));
+    assert!(md.contains("\n+pub struct example_api::StructV2\n"));
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(json).unwrap()).unwrap();
+    assert_eq!(json["added"].as_array().unwrap().len(), 3);
+}
+
+#[test]
+fn diff_public_items_from_files_as_toml() {
+    // Create independent build dirs so all tests can run in parallel
+    let build_dir = tempdir().unwrap();
+    let build_dir2 = tempdir().unwrap();
+
+    let old = rustdoc_json_path_for_crate("../test-apis/example_api-v0.1.0", &build_dir);
+    let new = rustdoc_json_path_for_crate("../test-apis/example_api-v0.3.0", &build_dir2);
+    let mut cmd = TestCmd::new().with_separate_target_dir();
+    cmd.args(["diff", "--format", "toml"]);
+    cmd.arg(&old);
+    cmd.arg(&new);
+    let output = cmd.assert().success().get_output().stdout.clone();
+
+    // Parse the TOML and compare with the diff made by the library
+    let parsed: toml::Table = String::from_utf8(output).unwrap().parse().unwrap();
+    let build = |json| {
+        public_api::Builder::from_rustdoc_json(json)
+            .omit_blanket_impls(true)
+            .omit_auto_trait_impls(true)
+            .build()
+            .unwrap()
+    };
+    let diff = public_api::diff::PublicApiDiff::between(build(old), build(new));
+    let item = |item: &public_api::PublicItem| {
+        let mut table = toml::Table::new();
+        table.insert("path".to_owned(), item.path().to_vec().into());
+        table.insert("signature".to_owned(), item.to_string().into());
+        toml::Value::from(table)
+    };
+    let changed = |changed: &public_api::diff::ChangedPublicItem| {
+        let mut table = toml::Table::new();
+        table.insert("old".to_owned(), item(&changed.old));
+        table.insert("new".to_owned(), item(&changed.new));
+        toml::Value::from(table)
+    };
+    let mut expected = toml::Table::new();
+    let removed: Vec<_> = diff.removed.iter().map(item).collect();
+    let changed: Vec<_> = diff.changed.iter().map(changed).collect();
+    let added: Vec<_> = diff.added.iter().map(item).collect();
+    expected.insert("removed".to_owned(), removed.into());
+    expected.insert("changed".to_owned(), changed.into());
+    expected.insert("added".to_owned(), added.into());
+    assert!(!diff.is_empty());
+    assert_eq!(parsed, expected);
+}
+
 #[test]
 fn document_private_items() {
     // Create independent build dir so all tests can run in parallel
@@ -857,7 +1422,7 @@ fn document_private_items() {
         .build()
         .unwrap();
     let mut cmd = TestCmd::new().with_separate_target_dir();
-    cmd.arg("--rustdoc-json");
+    cmd.arg("--rustdoc-json-path");
     cmd.arg(json);
     cmd.assert()
         .stdout_or_update("./expected-output/example_api-v0.3.0_document-private-items.txt")
@@ -879,6 +1444,25 @@ fn cap_lints_allow_by_default_when_diffing() {
         .success();
 }
 
+#[test]
+fn cap_lints_is_forwarded_to_cargo_rustdoc() {
+    let cap_lints_arg = |cap_lints: Option<&str>| {
+        let mut cmd = TestCmd::new().with_test_repo();
+        cmd.cmd().env("RUST_LOG", "rustdoc_json=info");
+        if let Some(cap_lints) = cap_lints {
+            cmd.args(["--cap-lints", cap_lints]);
+        }
+        let assert = cmd.assert().success();
+        let stderr = String::from_utf8_lossy(&assert.get_output().stderr).into_owned();
+        let (_, args) = stderr.split_once("Running ").unwrap();
+        let (_, cap_lints) = args.split_once(r#""--cap-lints" "#).unwrap();
+        cap_lints.split_whitespace().next().unwrap().to_owned()
+    };
+
+    assert_eq!(cap_lints_arg(None), r#""warn""#);
+    assert_eq!(cap_lints_arg(Some("deny")), r#""deny""#);
+}
+
 #[test]
 fn diff_against_published_version() {
     let mut cmd = TestCmd::new().with_test_repo();
@@ -916,6 +1500,31 @@ fn diff_between_two_published_versions() {
         .success();
 }
 
+/// Test that published versions that Cargo has already downloaded are built
+/// from the local registry cache
+#[test]
+#[ignore = "requires example_api 0.1.0 and 0.2.0 in the local Cargo registry cache"]
+fn diff_between_two_published_versions_from_registry_cache() {
+    let mut cmd = TestCmd::new(); // NOTE: No `.with_test_repo()` !;
+    cmd.arg("--verbose");
+    cmd.arg("-p");
+    cmd.arg("example_api");
+    cmd.arg("diff");
+    cmd.arg("0.1.0");
+    cmd.arg("0.2.0");
+    cmd.assert()
+        .stdout(contains(
+            "pub fn example_api::function(v1_param: example_api::Struct, v2_param: usize)",
+        ))
+        .stderr(contains(
+            "example_api-0.1.0\" from the local Cargo registry cache",
+        ))
+        .stderr(contains(
+            "example_api-0.2.0\" from the local Cargo registry cache",
+        ))
+        .success();
+}
+
 /// Test that `cargo public-api diff latest` works
 #[test]
 fn diff_against_latest_published_version() {
@@ -950,6 +1559,46 @@ fn diff_against_latest_published_version_impl(arg: Option<&str>, expected_stderr
         .success();
 }
 
+#[test]
+fn emit_rustdoc_json() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["--emit", "rustdoc-json"]);
+    let output = cmd.assert().success().get_output().stdout.clone();
+
+    let stdout = String::from_utf8(output).unwrap();
+    let path = stdout.strip_suffix('\n').unwrap();
+    assert!(!path.contains('\n'), "{stdout}");
+    assert!(path.ends_with("example_api.json"), "{path}");
+    assert!(Path::new(path).is_file(), "{path}");
+}
+
+#[test]
+fn diff_with_published() {
+    // Create a test repo. It already is at the latest version
+    let test_repo = TestRepo::new();
+    append_to_lib_rs_in_test_repo(&test_repo, "pub struct AddedSinceLatest;");
+
+    let mut cmd = TestCmd::new().with_separate_target_dir();
+    cmd.current_dir(test_repo.path());
+    cmd.arg("--diff-with-published");
+    cmd.assert()
+        .stdout_or_update("./expected-output/diff-latest.txt")
+        .stderr(contains("Resolved `--diff-with-published` to `diff 0.3.0`"))
+        .success();
+}
+
+#[test]
+fn diff_with_published_and_subcommand() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.arg("--diff-with-published");
+    cmd.arg("diff");
+    cmd.assert()
+        .stderr(contains(
+            "`--diff-with-published` can not be combined with a subcommand",
+        ))
+        .failure();
+}
+
 #[test]
 fn diff_published_explicit_package() {
     let mut cmd = TestCmd::new().with_test_repo();
@@ -1040,13 +1689,78 @@ fn list_public_items_from_json_file() {
 
     let json_file = rustdoc_json_path_for_crate("../test-apis/example_api-v0.3.0", &build_dir);
     let mut cmd = TestCmd::new().with_separate_target_dir();
-    cmd.arg("--rustdoc-json");
+    cmd.arg("--rustdoc-json-path");
     cmd.arg(json_file);
     cmd.assert()
         .stdout_or_update("./expected-output/example_api-v0.3.0.txt")
         .success();
 }
 
+#[test]
+fn save_json_and_diff_against_it() {
+    // Create independent build dirs so all tests can run in parallel
+    let build_dir = tempdir().unwrap();
+    let build_dir2 = tempdir().unwrap();
+
+    let new = rustdoc_json_path_for_crate("../test-apis/example_api-v0.3.0", &build_dir);
+    let saved = build_dir.path().join("saved.json");
+    let mut cmd = TestCmd::new().with_separate_target_dir();
+    cmd.arg("--rustdoc-json-path");
+    cmd.arg(new);
+    cmd.arg("--save-json");
+    cmd.arg(&saved);
+    cmd.assert()
+        .stdout_or_update("./expected-output/example_api-v0.3.0.txt")
+        .success();
+
+    let old = rustdoc_json_path_for_crate("../test-apis/example_api-v0.2.0", &build_dir2);
+    let mut cmd = TestCmd::new().with_separate_target_dir();
+    cmd.arg("diff");
+    cmd.arg(old);
+    cmd.arg(&saved);
+    cmd.assert()
+        .stdout_or_update("./expected-output/example_api_diff_v0.2.0_to_v0.3.0.txt")
+        .success();
+}
+
+#[test]
+fn save_json_with_subcommand() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["--save-json", "saved.json"]);
+    cmd.args(["diff", "v0.1.0..v0.2.0"]);
+    cmd.assert()
+        .stderr(contains(
+            "`--save-json` can not be combined with a subcommand",
+        ))
+        .failure();
+}
+
+#[test]
+fn save_json_with_diff_with_published() {
+    let mut cmd = TestCmd::new().with_test_repo();
+    cmd.args(["--save-json", "saved.json", "--diff-with-published"]);
+    cmd.assert()
+        .stderr(contains(
+            "the argument '--save-json <PATH>' cannot be used with '--diff-with-published'",
+        ))
+        .failure();
+}
+
+#[test]
+fn json_format_version_mismatch() {
+    // Create independent build dir so all tests can run in parallel
+    let build_dir = tempdir().unwrap();
+
+    let json_file = rustdoc_json_path_for_crate("../test-apis/example_api-v0.3.0", &build_dir);
+    let mut cmd = TestCmd::new().with_separate_target_dir();
+    cmd.arg("--rustdoc-json-path");
+    cmd.arg(json_file);
+    cmd.args(["--json-format-version", "1"]);
+    cmd.assert()
+        .stderr(contains("Expected rustdoc JSON format version 1 but found"))
+        .failure();
+}
+
 #[test]
 fn verbose() {
     let mut cmd = TestCmd::new();
@@ -0,0 +1,164 @@
+//! Benchmarks for [`PublicApiDiff::between`] and for parsing rustdoc JSON. Run
+//! with
+//! ```bash
+//! cargo bench -p public-api
+//! ```
+//!
+//! The diff fixtures are crates with 10, 100, 1000 and 10000 public
+//! functions, where a varying share of the functions differ between the old
+//! and the new version. They are generated and built to rustdoc JSON when the
+//! benchmark starts.
+//!
+//! Diffing borrowed items is compared with diffing cloned items for two
+//! versions of a crate with 10000 functions with five parameters each, where
+//! 10% of the functions differ.
+//!
+//! Cloning is benchmarked with a diff between two versions of a crate with
+//! 10000 functions with five parameters each, where all functions differ.
+//!
+//! The parsing benchmark uses the rustdoc JSON of the `public-api` crate
+//! itself, unless `PUBLIC_API_BENCH_RUSTDOC_JSON` is set to the path of the
+//! rustdoc JSON of some other crate.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use public_api::{diff::PublicApiDiff, PublicApi};
+
+/// How many percent of the functions that differ between the old and the new
+/// version of a fixture.
+const CHANGED_PERCENTS: [usize; 4] = [0, 10, 50, 100];
+
+fn diff_between(c: &mut Criterion) {
+    let mut group = c.benchmark_group("PublicApiDiff::between");
+    for items in [10, 100, 1000, 10000] {
+        let root = tempfile::tempdir().unwrap();
+        let old = public_api_for_fixture(&root.path().join("old"), &fixture_lib(items, 0, 1));
+
+        for changed_percent in CHANGED_PERCENTS {
+            let dir = root.path().join(format!("new-{changed_percent}"));
+            let new = public_api_for_fixture(&dir, &fixture_lib(items, changed_percent, 1));
+
+            let id = BenchmarkId::new(format!("{changed_percent}% changed"), items);
+            group.bench_with_input(id, &items, |b, _| {
+                b.iter_batched(
+                    || (old.clone(), new.clone()),
+                    |(old, new)| PublicApiDiff::between(old, new),
+                    BatchSize::LargeInput,
+                );
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Compares diffing items that the caller wants to keep, once by cloning them
+/// for [`PublicApiDiff::between`] and once by borrowing them with
+/// [`PublicApiDiff::between_slices`].
+fn diff_between_slices(c: &mut Criterion) {
+    let root = tempfile::tempdir().unwrap();
+    let items = 10000;
+    let old = public_api_for_fixture(&root.path().join("old"), &fixture_lib(items, 0, 5));
+    let new = public_api_for_fixture(&root.path().join("new"), &fixture_lib(items, 10, 5));
+    let old_items: Vec<_> = old.items().cloned().collect();
+    let new_items: Vec<_> = new.items().cloned().collect();
+
+    let mut group = c.benchmark_group("PublicApiDiff::between_slices");
+    group.bench_with_input(BenchmarkId::new("cloned", items), &items, |b, _| {
+        b.iter(|| PublicApiDiff::between(old.clone(), new.clone()));
+    });
+    group.bench_with_input(BenchmarkId::new("borrowed", items), &items, |b, _| {
+        b.iter(|| PublicApiDiff::between_slices(&old_items, &new_items));
+    });
+    group.finish();
+}
+
+fn diff_clone(c: &mut Criterion) {
+    let root = tempfile::tempdir().unwrap();
+    let items = 10000;
+    let old = public_api_for_fixture(&root.path().join("old"), &fixture_lib(items, 0, 5));
+    let new = public_api_for_fixture(&root.path().join("new"), &fixture_lib(items, 100, 5));
+    let diff = PublicApiDiff::between(old, new);
+
+    let mut group = c.benchmark_group("PublicApiDiff::clone");
+    group.bench_with_input(BenchmarkId::from_parameter(items), &items, |b, _| {
+        b.iter(|| diff.clone());
+    });
+    group.finish();
+}
+
+fn parse_rustdoc_json(c: &mut Criterion) {
+    let root = tempfile::tempdir().unwrap();
+    let json_path = std::env::var_os("PUBLIC_API_BENCH_RUSTDOC_JSON").map_or_else(
+        || rustdoc_json_for_manifest(Path::new("Cargo.toml"), &root.path().join("target")),
+        PathBuf::from,
+    );
+    let json = fs::read(json_path).unwrap();
+
+    c.bench_function("public_api_from_rustdoc_json_bytes", |b| {
+        b.iter(|| public_api::public_api_from_rustdoc_json_bytes(&json).unwrap());
+    });
+}
+
+/// Source code for a lib with `items` public functions with `params`
+/// parameters each, where
+/// `changed_percent` percent of the functions differ from the version with
+/// `changed_percent` 0. In every other block of ten functions, the differing
+/// functions have a changed signature. In the other blocks, they are removed
+/// and replaced by added functions.
+fn fixture_lib(items: usize, changed_percent: usize, params: usize) -> String {
+    let params = |ty: &str| {
+        (0..params)
+            .map(|p| format!("x{p}: {ty}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut lib = String::new();
+    for i in 0..items {
+        let changed = i % 10 < changed_percent / 10;
+        if !changed {
+            lib.push_str(&format!("pub fn f{i}({}) -> u32 {{ x0 }}\n", params("u32")));
+        } else if (i / 10) % 2 == 0 {
+            lib.push_str(&format!("pub fn f{i}({}) -> u64 {{ x0 }}\n", params("u64")));
+        } else {
+            lib.push_str(&format!("pub fn added{i}({}) {{}}\n", params("u8")));
+        }
+    }
+    lib
+}
+
+fn public_api_for_fixture(dir: &Path, lib: &str) -> PublicApi {
+    fs::create_dir_all(dir).unwrap();
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"fixture\"\nversion = \"0.1.0\"\nedition = \"2021\"\n[lib]\npath = \"lib.rs\"\n",
+    )
+    .unwrap();
+    fs::write(dir.join("lib.rs"), lib).unwrap();
+
+    let json = rustdoc_json_for_manifest(&dir.join("Cargo.toml"), &dir.join("target"));
+    public_api::Builder::from_rustdoc_json(json)
+        .build()
+        .unwrap()
+}
+
+fn rustdoc_json_for_manifest(manifest_path: &Path, target_dir: &Path) -> PathBuf {
+    rustdoc_json::Builder::default()
+        .manifest_path(manifest_path)
+        .toolchain("nightly")
+        .target_dir(target_dir)
+        .quiet(true)
+        .build()
+        .unwrap()
+}
+
+criterion_group!(
+    benches,
+    diff_between,
+    diff_between_slices,
+    diff_clone,
+    parse_rustdoc_json
+);
+criterion_main!(benches);
@@ -16,7 +16,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let new = public_api::Builder::from_rustdoc_json(new_json).build()?;
 
     let diff = PublicApiDiff::between(old, new);
-    println!("{diff:#?}");
+    println!("{diff:?}");
 
     Ok(())
 }
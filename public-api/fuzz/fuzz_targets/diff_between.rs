@@ -0,0 +1,18 @@
+//! Makes sure that diffing arbitrary public APIs never panics, and that the
+//! diff is consistent with its input.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use public_api::{diff::PublicApiDiff, PublicApi};
+
+fuzz_target!(|apis: (PublicApi, PublicApi)| {
+    let (old, new) = apis;
+    let old_len = old.items().count();
+    let new_len = new.items().count();
+
+    let diff = PublicApiDiff::between(old, new);
+
+    assert!(diff.removed.len() + diff.changed.len() <= old_len);
+    assert!(diff.added.len() + diff.changed.len() <= new_len);
+});
@@ -0,0 +1,15 @@
+//! Makes sure that parsing arbitrary bytes as rustdoc JSON never panics. Any
+//! problem with the input must be reported as an error.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(json) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let _ = public_api::public_api_items_count(json);
+    let _ = public_api::parse_with_format_version(json, rustdoc_types::FORMAT_VERSION);
+});
@@ -0,0 +1,64 @@
+//! Async versions of building and diffing public APIs, for use within a Tokio
+//! runtime. Requires the `async` feature.
+
+use std::future::Future;
+
+use crate::{diff::PublicApiDiff, Builder, PublicApi, Result};
+
+impl PublicApiDiff {
+    /// Like [`PublicApiDiff::between`], but computes the diff on the blocking
+    /// thread pool of the current Tokio runtime, so that diffing large public
+    /// APIs does not stall other tasks on the same thread.
+    ///
+    /// Dropping the returned future before it completes discards the diff.
+    ///
+    /// # Panics
+    ///
+    /// If called outside of a Tokio runtime.
+    pub fn between_async(
+        old: PublicApi,
+        new: PublicApi,
+    ) -> impl Future<Output = PublicApiDiff> + Send {
+        let diff = tokio::task::spawn_blocking(move || Self::between(old, new));
+        async move {
+            diff.await
+                .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+        }
+    }
+}
+
+/// Builds rustdoc JSON with `rustdoc_json_builder` and returns its public API,
+/// without blocking the current thread. Dropping the returned future before it
+/// completes kills the `cargo rustdoc` process, which makes it possible to e.g.
+/// give up on slow builds with `tokio::time::timeout()`.
+///
+/// The builder decides which crate to build and with which toolchain. The
+/// toolchain must be [`crate::MINIMUM_NIGHTLY_RUST_VERSION`] or later.
+///
+/// ```no_run
+/// # async fn f() -> public_api::Result<()> {
+/// let public_api = public_api::compute_public_api_async(
+///     rustdoc_json::Builder::default()
+///         .toolchain(public_api::MINIMUM_NIGHTLY_RUST_VERSION)
+///         .manifest_path("Cargo.toml"),
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// If building the rustdoc JSON fails, or if it can not be parsed.
+///
+/// # Panics
+///
+/// If polled outside of a Tokio runtime.
+pub async fn compute_public_api_async(
+    rustdoc_json_builder: rustdoc_json::Builder,
+) -> Result<PublicApi> {
+    let json_path = rustdoc_json_builder.quiet(true).build_async().await?;
+    tokio::task::spawn_blocking(move || Builder::from_rustdoc_json(json_path).build())
+        .await
+        .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+}
@@ -0,0 +1,165 @@
+use std::fmt::Write;
+
+use crate::{diff::PublicApiDiff, PublicApi};
+
+/// How compatible a new version of a public API is with an old version, in
+/// semver terms.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compatibility {
+    /// The public API is unchanged. A PATCH change.
+    Compatible,
+
+    /// Items have been added, or changed in backwards compatible ways. A MINOR
+    /// change.
+    Minor,
+
+    /// Items have been removed, or changed in ways that can break users. A
+    /// MAJOR change.
+    Breaking,
+}
+
+impl Compatibility {
+    /// Classifies `diff`. See the variant docs for what each variant means.
+    /// Uses [`crate::diff::ChangedPublicItem::is_semver_compatible`] to tell
+    /// compatible changes from breaking changes.
+    #[must_use]
+    pub fn of(diff: &PublicApiDiff) -> Self {
+        if !diff.removed.is_empty() || diff.changed.iter().any(|c| !c.is_semver_compatible()) {
+            Self::Breaking
+        } else if diff.is_empty() {
+            Self::Compatible
+        } else {
+            Self::Minor
+        }
+    }
+
+    /// The name of the variant in lowercase, e.g. `"breaking"`.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Compatible => "compatible",
+            Self::Minor => "minor",
+            Self::Breaking => "breaking",
+        }
+    }
+}
+
+/// Describes how compatible each version in a set of versions of a public API
+/// is with every other version. Entry `[i][j]` tells what kind of change it is
+/// to go from version `i` to version `j`.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct CompatibilityMatrix {
+    versions: Vec<String>,
+    matrix: Vec<Vec<Compatibility>>,
+}
+
+impl CompatibilityMatrix {
+    /// Diffs every version against every other version. Each version is given
+    /// as a name, e.g. a git tag, together with its public API.
+    #[must_use]
+    pub fn new(versions: Vec<(String, PublicApi)>) -> Self {
+        let matrix = versions
+            .iter()
+            .map(|(_, old)| {
+                versions
+                    .iter()
+                    .map(|(_, new)| {
+                        Compatibility::of(&PublicApiDiff::between(old.clone(), new.clone()))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            versions: versions.into_iter().map(|(name, _)| name).collect(),
+            matrix,
+        }
+    }
+
+    /// The names of the versions, in the order they were given to
+    /// [`Self::new`].
+    #[must_use]
+    pub fn versions(&self) -> &[String] {
+        &self.versions
+    }
+
+    /// What kind of change it is to go from version `old` to version `new`,
+    /// where both are indices into [`Self::versions`].
+    ///
+    /// # Panics
+    ///
+    /// If any of the indices is out of bounds.
+    #[must_use]
+    pub fn get(&self, old: usize, new: usize) -> Compatibility {
+        self.matrix[old][new]
+    }
+
+    /// Renders the matrix as CSV. Rows are old versions and columns are new
+    /// versions.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for version in &self.versions {
+            let _ = write!(csv, ",{version}");
+        }
+        csv.push('\n');
+
+        for (version, row) in self.versions.iter().zip(&self.matrix) {
+            csv.push_str(version);
+            for compatibility in row {
+                let _ = write!(csv, ",{}", compatibility.as_str());
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Renders the matrix as JSON on the form `{"versions": [...], "matrix":
+    /// [[...], ...]}`.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("matrix is always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Compatibility, CompatibilityMatrix};
+    use crate::{public_item::test_item, tokens::Token, PublicApi};
+
+    #[test]
+    fn matrix() {
+        let v1 = api(&["a"]);
+        let v2 = api(&["a", "b"]);
+        let v3 = api(&["b"]);
+
+        let matrix = CompatibilityMatrix::new(vec![
+            ("v1".to_owned(), v1),
+            ("v2".to_owned(), v2),
+            ("v3".to_owned(), v3),
+        ]);
+
+        assert_eq!(matrix.get(0, 0), Compatibility::Compatible);
+        assert_eq!(matrix.get(0, 1), Compatibility::Minor);
+        assert_eq!(matrix.get(1, 0), Compatibility::Breaking);
+        assert_eq!(matrix.get(0, 2), Compatibility::Breaking);
+        assert_eq!(
+            matrix.to_csv(),
+            ",v1,v2,v3\n\
+            v1,compatible,minor,breaking\n\
+            v2,breaking,compatible,breaking\n\
+            v3,breaking,minor,compatible\n"
+        );
+    }
+
+    fn api(names: &[&str]) -> PublicApi {
+        PublicApi {
+            items: names
+                .iter()
+                .map(|name| test_item([*name], vec![Token::identifier(*name)]))
+                .collect(),
+            missing_item_ids: vec![],
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use crate::{tokens::Token, PublicItem};
+
+/// How many public items have doc comments. Items only have docs if
+/// [`crate::Builder::include_docs`] is enabled, so build the items with it.
+/// Every item counts, so consider omitting impls that are rarely documented
+/// with e.g. [`crate::Builder::omit_blanket_impls`].
+///
+/// ```no_run
+/// # let json_path = std::path::PathBuf::new();
+/// use public_api::PublicApiCoverage;
+///
+/// let public_api = public_api::Builder::from_rustdoc_json(json_path)
+///     .include_docs(true)
+///     .build()?;
+/// let items: Vec<_> = public_api.into_items().collect();
+/// let coverage = PublicApiCoverage::from_items(&items);
+/// println!("{:.1}% documented", coverage.coverage_percentage());
+/// # Ok::<(), public_api::Error>(())
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct PublicApiCoverage {
+    /// The number of items that have a doc comment.
+    pub documented_count: usize,
+
+    /// The number of items in total.
+    pub total_count: usize,
+}
+
+impl PublicApiCoverage {
+    /// Counts the items of `items` that have a [`Token::Doc`].
+    #[must_use]
+    pub fn from_items(items: &[PublicItem]) -> Self {
+        Self {
+            documented_count: items
+                .iter()
+                .filter(|item| item.tokens().any(|token| matches!(token, Token::Doc(_))))
+                .count(),
+            total_count: items.len(),
+        }
+    }
+
+    /// The percentage of items that are documented, from `0.0` to `100.0`.
+    /// Without any items, nothing lacks docs, so the coverage is `100.0`.
+    #[must_use]
+    pub fn coverage_percentage(&self) -> f64 {
+        if self.total_count == 0 {
+            return 100.0;
+        }
+
+        #[allow(clippy::cast_precision_loss)] // Item counts are small
+        let percentage = self.documented_count as f64 * 100.0 / self.total_count as f64;
+        percentage
+    }
+
+    /// How the coverage changed from `old_items` to `new_items`, e.g. from
+    /// one release to the next. Useful for CI checks that the coverage does
+    /// not decrease.
+    #[must_use]
+    pub fn delta(old_items: &[PublicItem], new_items: &[PublicItem]) -> CoverageDelta {
+        CoverageDelta {
+            old: Self::from_items(old_items),
+            new: Self::from_items(new_items),
+        }
+    }
+}
+
+/// How the [`PublicApiCoverage`] changed between two versions of a public API.
+/// Returned by [`PublicApiCoverage::delta`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+pub struct CoverageDelta {
+    /// The coverage of the old version.
+    pub old: PublicApiCoverage,
+
+    /// The coverage of the new version.
+    pub new: PublicApiCoverage,
+}
+
+impl CoverageDelta {
+    /// The change of [`PublicApiCoverage::coverage_percentage`], in percentage
+    /// points. Negative if the coverage decreased.
+    #[must_use]
+    pub fn percentage_point_change(&self) -> f64 {
+        self.new.coverage_percentage() - self.old.coverage_percentage()
+    }
+
+    /// Returns `true` if a smaller fraction of the items is documented than
+    /// before.
+    #[must_use]
+    pub fn is_decrease(&self) -> bool {
+        self.percentage_point_change() < 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoverageDelta, PublicApiCoverage};
+    use crate::{public_item::test_item, tokens::Token, PublicItem};
+
+    #[test]
+    fn coverage() {
+        let items = [
+            item("a", true),
+            item("b", false),
+            item("c", true),
+            item("d", true),
+        ];
+
+        let coverage = PublicApiCoverage::from_items(&items);
+        assert_eq!(
+            coverage,
+            PublicApiCoverage {
+                documented_count: 3,
+                total_count: 4,
+            }
+        );
+        assert!((coverage.coverage_percentage() - 75.0).abs() < f64::EPSILON);
+
+        let empty = PublicApiCoverage::from_items(&[]);
+        assert!((empty.coverage_percentage() - 100.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn delta() {
+        let old = [item("a", true), item("b", true)];
+        let new = [
+            item("a", true),
+            item("b", true),
+            item("c", false),
+            item("d", false),
+        ];
+
+        let delta = PublicApiCoverage::delta(&old, &new);
+        assert_eq!(
+            delta,
+            CoverageDelta {
+                old: PublicApiCoverage {
+                    documented_count: 2,
+                    total_count: 2,
+                },
+                new: PublicApiCoverage {
+                    documented_count: 2,
+                    total_count: 4,
+                },
+            }
+        );
+        assert!((delta.percentage_point_change() + 50.0).abs() < f64::EPSILON);
+        assert!(delta.is_decrease());
+        assert!(!PublicApiCoverage::delta(&new, &old).is_decrease());
+    }
+
+    fn item(name: &str, documented: bool) -> PublicItem {
+        let mut tokens = vec![];
+        if documented {
+            tokens.push(Token::doc("/// Docs."));
+        }
+        tokens.push(Token::identifier(name));
+        test_item([name], tokens)
+    }
+}
@@ -1,4 +1,4 @@
-use rustdoc_types::{Crate, Id, Item};
+use rustdoc_types::{Crate, Id, Item, Type};
 
 /// The [`Crate`] type represents the deserialized form of the rustdoc JSON
 /// input. This wrapper adds some helpers and state on top.
@@ -30,6 +30,21 @@ impl<'c> CrateWrapper<'c> {
         })
     }
 
+    /// The path where the item with `id` is defined, e.g. `["dep", "Foo"]`, if
+    /// the item is defined in another crate and the path is known.
+    pub fn foreign_definition_path(&self, id: &Id) -> Option<&'c [String]> {
+        self.crate_
+            .paths
+            .get(id)
+            .filter(|summary| summary.crate_id != 0)
+            .map(|summary| summary.path.as_slice())
+    }
+
+    /// Returns `true` if `type_` is `PhantomData<...>`.
+    pub fn is_phantom_data(&self, type_: &Type) -> bool {
+        crate::render::is_phantom_data(self.crate_, type_)
+    }
+
     pub fn missing_item_ids(&self) -> Vec<u32> {
         self.missing_ids.iter().map(|m| m.0).collect()
     }
@@ -0,0 +1,195 @@
+use std::path::Path;
+
+use crate::{diff::PublicApiDiff, PublicApi, PublicItem};
+
+/// Stores many versions of a public API compactly, e.g. the public API of
+/// every release of a crate. Only the difference to the previous version is
+/// stored for each version, and versions are reconstructed on demand with
+/// [`PublicApiDiff::apply`]. The store is saved in a compact binary format.
+///
+/// ```
+/// use public_api::DeltaStore;
+///
+/// # fn example(v1: Vec<public_api::PublicItem>, v2: Vec<public_api::PublicItem>) -> public_api::Result<()> {
+/// let mut store = DeltaStore::new();
+/// store.add_version("v1.0.0", v1)?;
+/// store.add_version("v2.0.0", v2)?;
+/// store.save("api-history.bin".as_ref())?;
+///
+/// let store = DeltaStore::load("api-history.bin".as_ref())?;
+/// let v1_items = store.get_version("v1.0.0")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DeltaStore {
+    versions: Vec<Version>,
+
+    /// The items of the last version, so that adding a version does not
+    /// require reconstructing the previous version
+    #[serde(skip)]
+    latest: Vec<PublicItem>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct Version {
+    label: String,
+
+    /// The diff from the previous version. The first version is stored as a
+    /// diff from an empty public API.
+    diff: PublicApiDiff,
+}
+
+impl DeltaStore {
+    /// Creates an empty store.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `items` as a new version labeled `label`, e.g. `"v1.2.3"`. Only
+    /// the difference to the previously added version is stored.
+    ///
+    /// # Errors
+    ///
+    /// [`crate::Error::DuplicateVersionLabel`] if the store already has a
+    /// version labeled `label`.
+    pub fn add_version(&mut self, label: &str, items: Vec<PublicItem>) -> crate::Result<()> {
+        if self.labels().any(|l| l == label) {
+            return Err(crate::Error::DuplicateVersionLabel(label.to_owned()));
+        }
+
+        let previous = std::mem::take(&mut self.latest);
+        let diff = PublicApiDiff::between(public_api(previous.clone()), public_api(items));
+        self.latest = diff.apply(previous)?;
+        self.versions.push(Version {
+            label: label.to_owned(),
+            diff,
+        });
+        Ok(())
+    }
+
+    /// Reconstructs the items of the version labeled `label`, sorted like
+    /// [`crate::Builder::sorted`] sorts them. Returns `Ok(None)` if there is no
+    /// such version.
+    ///
+    /// # Errors
+    ///
+    /// [`crate::Error::ItemMissingWhenApplyingDiff`] if the versions of the
+    /// store are inconsistent.
+    pub fn get_version(&self, label: &str) -> crate::Result<Option<Vec<PublicItem>>> {
+        let Some(index) = self.labels().position(|l| l == label) else {
+            return Ok(None);
+        };
+        let items = self.versions[..=index]
+            .iter()
+            .try_fold(vec![], |items, version| version.diff.apply(items))?;
+        Ok(Some(items))
+    }
+
+    /// The labels of all versions, in the order they were added.
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.versions.iter().map(|v| v.label.as_str())
+    }
+
+    /// Serializes the store to bytes with [`bincode`]. Use
+    /// [`Self::from_bytes`] to deserialize.
+    ///
+    /// # Errors
+    ///
+    /// [`crate::Error::DeltaStoreError`] if the store can't be serialized.
+    pub fn to_bytes(&self) -> crate::Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// Deserializes a store serialized with [`Self::to_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// [`crate::Error::DeltaStoreError`] if `bytes` is not a serialized store,
+    /// and [`crate::Error::ItemMissingWhenApplyingDiff`] if the versions of the
+    /// store are inconsistent.
+    pub fn from_bytes(bytes: &[u8]) -> crate::Result<Self> {
+        let mut store: Self = bincode::deserialize(bytes)?;
+        for version in &store.versions {
+            store.latest = version.diff.apply(std::mem::take(&mut store.latest))?;
+        }
+        Ok(store)
+    }
+
+    /// Writes the store to the file at `path`, see [`Self::to_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// If the store can't be serialized or the file can't be written.
+    pub fn save(&self, path: &Path) -> crate::Result<()> {
+        Ok(std::fs::write(path, self.to_bytes()?)?)
+    }
+
+    /// Reads a store from the file at `path`, see [`Self::from_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read or does not contain a valid store.
+    pub fn load(path: &Path) -> crate::Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+fn public_api(items: Vec<PublicItem>) -> PublicApi {
+    PublicApi {
+        items,
+        missing_item_ids: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeltaStore;
+    use crate::{public_item::test_item, tokens::Token, PublicItem};
+
+    #[test]
+    fn versions_round_trip() {
+        let v1 = items(&[("a", "fn a()"), ("b", "fn b()")]);
+        let v2 = items(&[("a", "fn a(x: u8)"), ("c", "fn c()")]);
+        let v3 = items(&[]);
+
+        let mut store = DeltaStore::new();
+        store.add_version("v1", v1.clone()).unwrap();
+        store.add_version("v2", v2.clone()).unwrap();
+        store.add_version("v3", v3.clone()).unwrap();
+        let store = DeltaStore::from_bytes(&store.to_bytes().unwrap()).unwrap();
+
+        assert_eq!(store.labels().collect::<Vec<_>>(), ["v1", "v2", "v3"]);
+        assert_eq!(store.get_version("v1").unwrap(), Some(v1));
+        assert_eq!(store.get_version("v2").unwrap(), Some(v2.clone()));
+        assert_eq!(store.get_version("v3").unwrap(), Some(v3));
+        assert_eq!(store.get_version("v4").unwrap(), None);
+        assert_eq!(store.versions[1].diff.added, [v2[1].clone()]);
+    }
+
+    #[test]
+    fn invalid_bytes_fail() {
+        assert!(matches!(
+            DeltaStore::from_bytes(b"not a store"),
+            Err(crate::Error::DeltaStoreError(_))
+        ));
+    }
+
+    #[test]
+    fn duplicate_label_fails() {
+        let mut store = DeltaStore::new();
+        store.add_version("v1", items(&[])).unwrap();
+        assert!(matches!(
+            store.add_version("v1", items(&[])),
+            Err(crate::Error::DuplicateVersionLabel(label)) if label == "v1"
+        ));
+    }
+
+    fn items(items: &[(&str, &str)]) -> Vec<PublicItem> {
+        items
+            .iter()
+            .map(|(name, text)| test_item([*name], vec![Token::identifier(*text)]))
+            .collect()
+    }
+}
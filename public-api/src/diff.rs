@@ -4,17 +4,16 @@
 //! additional helpers for that.
 
 use crate::{
-    public_item::{PublicItem, PublicItemPath},
-    PublicApi,
+    public_item::{Bound, PublicItem, PublicItemPath},
+    tokens::Token,
+    PublicApi, PublicApiSet,
 };
-use hashbag::HashBag;
-use std::collections::HashMap;
-
-type ItemsWithPath = HashMap<PublicItemPath, Vec<PublicItem>>;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// An item has changed in the public API. Two [`PublicItem`]s are considered
 /// the same if their `path` is the same.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct ChangedPublicItem {
     /// How the item used to look.
     pub old: PublicItem,
@@ -32,14 +31,555 @@ impl ChangedPublicItem {
             ordering => ordering,
         }
     }
+
+    /// Returns `true` if the item went from `unsafe impl` to `impl` or vice
+    /// versa. Removing the `unsafe` of an `unsafe impl` is likely a breaking
+    /// change for code that relied on the guarantee, so such changes deserve
+    /// extra scrutiny.
+    #[must_use]
+    pub fn changes_unsafe_impl(&self) -> bool {
+        self.old.is_unsafe_impl() != self.new.is_unsafe_impl()
+    }
+
+    /// Returns `true` if the [`PublicItem::cfg_conditions`] of the item
+    /// changed, e.g. if it moved from one feature to another.
+    #[must_use]
+    pub fn changes_cfg(&self) -> bool {
+        self.old.cfg_conditions != self.new.cfg_conditions
+    }
+
+    /// The old and the new item as they are printed in a diff. If
+    /// [`Self::changes_cfg`], each item is preceded by its
+    /// [`PublicItem::cfg_attribute`], since the items may look the same
+    /// otherwise.
+    #[must_use]
+    pub fn diff_lines(&self) -> (String, String) {
+        let line = |item: &PublicItem| match item.cfg_attribute().filter(|_| self.changes_cfg()) {
+            Some(cfg) => format!("{cfg} {item}"),
+            None => item.to_string(),
+        };
+        (line(&self.old), line(&self.new))
+    }
+
+    /// What kind of change this is. Items can only get different paths if
+    /// [`PublicApiDiff::between_with_rename_detection`] is used.
+    #[must_use]
+    pub fn change_kind(&self) -> ChangeKind {
+        if self.old.path != self.new.path {
+            ChangeKind::Renamed {
+                old_path: self.old.path_string(),
+                new_path: self.new.path_string(),
+            }
+        } else if self.changes_cfg() && self.old.tokens == self.new.tokens {
+            ChangeKind::CfgChanged
+        } else if self.changes_dyn_impl() {
+            ChangeKind::DynImplChanged
+        } else if self.old.lifetime_param_count() != self.new.lifetime_param_count() {
+            ChangeKind::LifetimeParamsChanged {
+                old_count: self.old.lifetime_param_count(),
+                new_count: self.new.lifetime_param_count(),
+            }
+        } else if let Some(array_change) = array_change(&self.old, &self.new) {
+            array_change
+        } else if bound_count(&self.new) > bound_count(&self.old) {
+            ChangeKind::BoundAdded
+        } else {
+            ChangeKind::Modified
+        }
+    }
+
+    /// Returns `true` if the return type changed from `impl Trait` to `dyn
+    /// Trait`, e.g. `Box<dyn Trait>`, or vice versa.
+    fn changes_dyn_impl(&self) -> bool {
+        let return_type_keyword = |item: &PublicItem| {
+            item.return_type_tokens()?
+                .iter()
+                .find(|token| matches!(token, Token::ImplKeyword | Token::DynKeyword))
+                .cloned()
+        };
+        match (
+            return_type_keyword(&self.old),
+            return_type_keyword(&self.new),
+        ) {
+            (Some(old), Some(new)) => old != new,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if only the [`Token::Doc`] of the item changed, i.e. if
+    /// the items are equal when their docs are disregarded.
+    #[must_use]
+    pub fn is_doc_only_change(&self) -> bool {
+        let without_docs = |item: &PublicItem| {
+            item.tokens()
+                .filter(|token| !matches!(token, Token::Doc(_)))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        !self.changes_cfg() && without_docs(&self.old) == without_docs(&self.new)
+    }
+
+    /// Returns `true` if only the [`Token::Whitespace`]s of the item changed,
+    /// i.e. if the items are equal when whitespace is disregarded.
+    #[must_use]
+    pub fn is_whitespace_only_change(&self) -> bool {
+        let without_whitespace = |item: &PublicItem| {
+            item.tokens()
+                .filter(|token| **token != Token::Whitespace)
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        !self.changes_cfg() && without_whitespace(&self.old) == without_whitespace(&self.new)
+    }
+
+    /// The number of tokens that must be inserted, removed or replaced to turn
+    /// the old item into the new item, i.e. the Levenshtein distance between
+    /// their tokens. For example 1 for a change from `pub fn f(x: u8)` to `pub
+    /// fn f(x: u16)`. See [`PublicApiDiff::between_with_tolerance`].
+    #[must_use]
+    pub fn edit_distance(&self) -> usize {
+        token_edit_distance(&self.old.tokens, &self.new.tokens)
+    }
+
+    /// Returns `true` if the change is provably backwards compatible in semver
+    /// terms, i.e. if its [`Self::severity`] is milder than
+    /// [`ChangeSeverity::Breaking`]. See
+    /// [`PublicApiDiff::between_semver_compatible`] for which changes are
+    /// considered compatible. All other changes are conservatively considered
+    /// to be breaking.
+    #[must_use]
+    pub fn is_semver_compatible(&self) -> bool {
+        self.severity() < ChangeSeverity::Breaking
+    }
+
+    /// Returns `true` if nothing but the [`CompatibilityTraits`] of the item
+    /// changed, and only in backwards compatible ways.
+    fn only_changes_compatibility_traits(&self) -> bool {
+        let old = CompatibilityTraits::of(&self.old);
+        let new = CompatibilityTraits::of(&self.new);
+
+        let adds_non_exhaustive = new.non_exhaustive && !old.non_exhaustive;
+        let removes_const = old.const_fn && !new.const_fn;
+        let adds_unsafe = new.unsafe_fn && !old.unsafe_fn;
+        // An item with fewer conditions exists in at least the same builds
+        let adds_cfg =
+            (self.new.cfg_conditions.iter()).any(|c| !self.old.cfg_conditions.contains(c));
+
+        old.remaining_text == new.remaining_text
+            && !adds_non_exhaustive
+            && !removes_const
+            && !adds_unsafe
+            && !adds_cfg
+    }
+
+    /// How severe the change is for users of the item. See [`ChangeSeverity`]
+    /// for what each level means. Changes that can not be classified as any
+    /// of the milder levels are conservatively considered to be
+    /// [`ChangeSeverity::Breaking`].
+    #[must_use]
+    pub fn severity(&self) -> ChangeSeverity {
+        if self.is_doc_only_change() || self.is_whitespace_only_change() {
+            return ChangeSeverity::Cosmetic;
+        }
+
+        let old = CompatibilityTraits::of(&self.old);
+        let new = CompatibilityTraits::of(&self.new);
+        let only_adds_deprecation = new.deprecated
+            && !old.deprecated
+            && !self.changes_cfg()
+            && old.remaining_text == new.remaining_text
+            && old.non_exhaustive == new.non_exhaustive
+            && old.const_fn == new.const_fn
+            && old.unsafe_fn == new.unsafe_fn;
+
+        if only_adds_deprecation {
+            ChangeSeverity::Deprecation
+        } else if self.only_changes_compatibility_traits() || self.only_relaxes_bounds() {
+            ChangeSeverity::Compatible
+        } else {
+            ChangeSeverity::Breaking
+        }
+    }
+
+    /// Returns `true` if the only change is that bounds were removed, e.g.
+    /// from `T: Clone + Debug` to `T: Clone`, or that relaxing bounds such as
+    /// `?Sized` were added. Never `true` for items of traits, since the
+    /// implementors of the trait would then have stricter requirements than
+    /// the trait, which is an error.
+    fn only_relaxes_bounds(&self) -> bool {
+        if self.new.is_trait_item()
+            || self.changes_cfg()
+            || without_bounds(&self.old) != without_bounds(&self.new)
+        {
+            return false;
+        }
+
+        let old_bounds = self.old.bounds();
+        let new_bounds = self.new.bounds();
+        let traits_of = |bounds: &[Bound], param: &str| {
+            bounds
+                .iter()
+                .filter(|bound| bound.param == param)
+                .flat_map(|bound| bound.traits.clone())
+                .collect::<HashSet<_>>()
+        };
+        let keeps_or_removes_bounds = new_bounds.iter().all(|bound| {
+            let old_traits = traits_of(&old_bounds, &bound.param);
+            bound
+                .traits
+                .iter()
+                .all(|t| t.starts_with('?') || old_traits.contains(t))
+        });
+        let keeps_relaxing_bounds = old_bounds.iter().all(|bound| {
+            let new_traits = traits_of(&new_bounds, &bound.param);
+            bound
+                .traits
+                .iter()
+                .all(|t| !t.starts_with('?') || new_traits.contains(t))
+        });
+        keeps_or_removes_bounds && keeps_relaxing_bounds
+    }
+}
+
+/// How severe a [`ChangedPublicItem`] is for users of the item, from least to
+/// most severe. See [`ChangedPublicItem::severity`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum ChangeSeverity {
+    /// Only the docs or the whitespace of the item changed, e.g. `pub fn
+    /// f(x:u8)` became `pub fn f(x: u8)`.
+    Cosmetic,
+
+    /// The change does not break any users, e.g. because a bound was removed,
+    /// as when `pub fn f<T: Clone>(t: T)` became `pub fn f<T>(t: T)`. Removing
+    /// a bound from an item of a trait is [`Self::Breaking`] though, since it
+    /// breaks the implementors of the trait.
+    Compatible,
+
+    /// The item became `#[deprecated]`. Users keep compiling, but with
+    /// warnings.
+    Deprecation,
+
+    /// The change can break users, e.g. because the type of a parameter
+    /// changed or a parameter was removed.
+    Breaking,
+}
+
+/// The traits of an item that matter when figuring out if a change to the
+/// item is semver compatible. See [`ChangedPublicItem::is_semver_compatible`].
+struct CompatibilityTraits {
+    /// If the item is `#[deprecated]`. Deprecating an item does not break
+    /// anything.
+    deprecated: bool,
+
+    /// If the item is `#[non_exhaustive]`.
+    non_exhaustive: bool,
+
+    /// If the item is a `const fn`.
+    const_fn: bool,
+
+    /// If the item is an `unsafe fn`.
+    unsafe_fn: bool,
+
+    /// The text of all tokens except the ones that correspond to the traits
+    /// above. [`Token::SelfType`] is expanded like [`PublicItem::normalize`]
+    /// expands it, so that changing e.g. a return type from `Self` to the
+    /// explicit type is not considered to be a change.
+    remaining_text: String,
+}
+
+impl CompatibilityTraits {
+    fn of(item: &PublicItem) -> Self {
+        let is_fn = item.is_function();
+
+        let mut traits = Self {
+            deprecated: false,
+            non_exhaustive: false,
+            const_fn: false,
+            unsafe_fn: false,
+            remaining_text: String::new(),
+        };
+
+        // Qualifiers such as `const` can also appear after the kind, e.g.
+        // for const generics. Only the qualifiers of the item itself matter.
+        let mut in_header = true;
+        // Changes to docs never break anything
+        let expanded = item.expand_self_type(item.tokens().cloned().collect());
+        let mut tokens = expanded
+            .iter()
+            .filter(|token| !matches!(token, Token::Doc(_)))
+            .peekable();
+        while let Some(token) = tokens.next() {
+            let qualifier = match token {
+                Token::Annotation(text) if text.starts_with("#[deprecated") => {
+                    Some(&mut traits.deprecated)
+                }
+                Token::Annotation(text) if text == "#[non_exhaustive]" => {
+                    Some(&mut traits.non_exhaustive)
+                }
+                Token::Qualifier(text) if in_header && is_fn && text == "const" => {
+                    Some(&mut traits.const_fn)
+                }
+                Token::Qualifier(text) if in_header && is_fn && text == "unsafe" => {
+                    Some(&mut traits.unsafe_fn)
+                }
+                _ => None,
+            };
+
+            if let Some(qualifier) = qualifier {
+                *qualifier = true;
+                // Skip the whitespace that follows the annotation or qualifier
+                tokens.next_if_eq(&&Token::Whitespace);
+            } else {
+                in_header &= !token.is_kind();
+                traits.remaining_text.push_str(token.text());
+            }
+        }
+
+        traits
+    }
+}
+
+/// What kind of change a [`ChangedPublicItem`] represents. See
+/// [`ChangedPublicItem::change_kind`].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    /// The item has the same path as before, but looks different.
+    Modified,
+
+    /// The item was renamed or moved, from `old_path` to `new_path`. See
+    /// [`PublicApiDiff::between_with_rename_detection`].
+    Renamed {
+        /// The path of the item before the rename, e.g. `krate::Foo`.
+        old_path: String,
+
+        /// The path of the item after the rename, e.g. `krate::Bar`.
+        new_path: String,
+    },
+
+    /// Only the [`PublicItem::cfg_conditions`] of the item changed, e.g. it
+    /// moved from one feature to another. The item looks the same otherwise.
+    CfgChanged,
+
+    /// The return type changed from `impl Trait` to `dyn Trait`, e.g.
+    /// `Box<dyn Trait>`, or vice versa. Always a breaking change, since the
+    /// two are different types with different capabilities.
+    DynImplChanged,
+
+    /// The number of lifetime parameters changed, e.g. from `pub fn f<'a>(x:
+    /// &'a u8, y: &'a u8)` to `pub fn f<'a, 'b>(x: &'a u8, y: &'b u8)`. See
+    /// [`PublicItem::lifetime_param_count`]. Such changes are easy to overlook
+    /// but can break callers.
+    LifetimeParamsChanged {
+        /// The number of lifetime parameters before the change.
+        old_count: usize,
+
+        /// The number of lifetime parameters after the change.
+        new_count: usize,
+    },
+
+    /// A bound was added to a generic parameter, e.g. `pub fn f<T>(t: T)`
+    /// became `pub fn f<T: Clone>(t: T)`. Often a breaking change, since
+    /// callers may use types that do not satisfy the new bound. Relaxing
+    /// bounds such as `?Sized` are not counted.
+    BoundAdded,
+
+    /// Only the length of an array type changed, e.g. from `pub fn f(key: [u8;
+    /// 16])` to `pub fn f(key: [u8; 32])`.
+    ArraySizeChanged {
+        /// The length of the first array whose length changed, before the
+        /// change.
+        old_size: String,
+
+        /// The length of the first array whose length changed, after the
+        /// change.
+        new_size: String,
+    },
+
+    /// Only the element type of an array type changed, e.g. from `pub fn
+    /// f(key: [u8; 32])` to `pub fn f(key: [u16; 32])`.
+    ArrayElementTypeChanged,
+}
+
+/// An array type of an item, as found by [`array_parts`].
+#[derive(PartialEq)]
+struct ArrayType<'a> {
+    /// The tokens of the element type. Nested arrays are represented by
+    /// their [`Token::ArrayOpen`] only.
+    element: Vec<&'a Token>,
+
+    /// The text of the length, e.g. `32`.
+    size: String,
+}
+
+/// Splits the tokens of `item` into the tokens that are not part of array
+/// types, and the array types, in the order they are opened.
+fn array_parts(item: &PublicItem) -> (Vec<&Token>, Vec<ArrayType<'_>>) {
+    let mut outside = vec![];
+    let mut arrays: Vec<ArrayType<'_>> = vec![];
+    // The indices of the arrays that are open, and if their length has begun
+    let mut open: Vec<(usize, bool)> = vec![];
+    for token in item.tokens() {
+        match (token, open.last_mut()) {
+            (Token::ArrayOpen, parent) => {
+                match parent {
+                    Some((index, false)) => arrays[*index].element.push(token),
+                    _ => outside.push(token),
+                }
+                open.push((arrays.len(), false));
+                arrays.push(ArrayType {
+                    element: vec![],
+                    size: String::new(),
+                });
+            }
+            (Token::ArraySemicolon, Some((_, in_size))) => *in_size = true,
+            (Token::ArrayClose, Some(_)) => {
+                open.pop();
+            }
+            (token, Some((index, false))) => arrays[*index].element.push(token),
+            (token, Some((index, true))) => arrays[*index].size.push_str(token.text().trim()),
+            (token, None) => outside.push(token),
+        }
+    }
+    (outside, arrays)
+}
+
+/// The [`ChangeKind::ArraySizeChanged`] or
+/// [`ChangeKind::ArrayElementTypeChanged`] if `old` and `new` only differ in
+/// the lengths or only in the element types of their array types.
+fn array_change(old: &PublicItem, new: &PublicItem) -> Option<ChangeKind> {
+    let (old_outside, old_arrays) = array_parts(old);
+    let (new_outside, new_arrays) = array_parts(new);
+    if old_outside != new_outside || old_arrays.len() != new_arrays.len() {
+        return None;
+    }
+
+    let pairs = || old_arrays.iter().zip(&new_arrays);
+    if pairs().all(|(old, new)| old.element == new.element) {
+        pairs()
+            .find(|(old, new)| old.size != new.size)
+            .map(|(old, new)| ChangeKind::ArraySizeChanged {
+                old_size: old.size.clone(),
+                new_size: new.size.clone(),
+            })
+    } else if pairs().all(|(old, new)| old.size == new.size) {
+        Some(ChangeKind::ArrayElementTypeChanged)
+    } else {
+        None
+    }
+}
+
+/// The number of bounds of `item`, e.g. 2 for `pub fn f<T: Clone + Debug>(t:
+/// T)`, not counting relaxing bounds such as `?Sized`.
+fn bound_count(item: &PublicItem) -> usize {
+    let mut tokens = item
+        .tokens()
+        .filter(|token| **token != Token::Whitespace)
+        .peekable();
+    let mut count = 0;
+    while let Some(token) = tokens.next() {
+        if matches!(token, Token::BoundSeparator | Token::BoundCombiner)
+            && tokens.peek() != Some(&&Token::OptionalBound)
+        {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// The tokens of `item` without whitespace and without the bounds of generic
+/// parameters, e.g. `pub fn f<T>(t: T)` for `pub fn f<T: Clone>(t: T)`.
+fn without_bounds(item: &PublicItem) -> Vec<&Token> {
+    let mut output = vec![];
+    let mut tokens = item
+        .tokens()
+        .filter(|token| **token != Token::Whitespace)
+        .peekable();
+    while let Some(token) = tokens.next() {
+        if *token != Token::BoundSeparator {
+            output.push(token);
+            continue;
+        }
+
+        // Skip the bounds, which end with a `,`, `>`, `=` or `{` that is not
+        // nested within brackets
+        let mut depth = 0_usize;
+        while let Some(next) = tokens.peek() {
+            match next.text() {
+                "<" | "(" | "[" => depth += 1,
+                ">" | ")" | "]" if depth > 0 => depth -= 1,
+                "," | ">" | ")" | "]" | "=" | "{" if depth == 0 => break,
+                _ => {}
+            }
+            tokens.next();
+        }
+    }
+    output
+}
+
+/// Options for [`PublicApiDiff::between_with_options`]. Use
+/// [`DiffOptions::default`] and then the builder methods to create an instance.
+#[derive(Copy, Clone, Debug)]
+pub struct DiffOptions {
+    ignore_doc_changes: bool,
+    ignore_whitespace: bool,
+    similarity_matching: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            ignore_doc_changes: false,
+            ignore_whitespace: false,
+            similarity_matching: true,
+        }
+    }
+}
+
+impl DiffOptions {
+    /// If `true`, items whose only change is to their docs are not reported as
+    /// changed. Only relevant if [`crate::Builder::include_docs`] is enabled.
+    ///
+    /// The default value is `false`.
+    #[must_use]
+    pub fn ignore_doc_changes(mut self, ignore_doc_changes: bool) -> Self {
+        self.ignore_doc_changes = ignore_doc_changes;
+        self
+    }
+
+    /// If `true`, items whose only change is to their [`Token::Whitespace`]
+    /// tokens are not reported as changed.
+    ///
+    /// The default value is `false`.
+    #[must_use]
+    pub fn ignore_whitespace(mut self, ignore_whitespace: bool) -> Self {
+        self.ignore_whitespace = ignore_whitespace;
+        self
+    }
+
+    /// If `true`, removed and added items that share the same path, such as
+    /// associated fns of different trait impls, are paired up into changed
+    /// items by how similar they are, most similar first. If `false`, they are
+    /// paired up in the order they are sorted in, which is faster but may pair
+    /// up the wrong items.
+    ///
+    /// The default value is `true`.
+    #[must_use]
+    pub fn similarity_matching(mut self, similarity_matching: bool) -> Self {
+        self.similarity_matching = similarity_matching;
+        self
+    }
 }
 
 /// The return value of [`Self::between`]. To quickly get a sense of what it
-/// contains, you can pretty-print it:
+/// contains, you can print its items:
 /// ```txt
-/// println!("{:#?}", public_api_diff);
+/// for item in &public_api_diff.added {
+///     println!("+{item}");
+/// }
 /// ```
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct PublicApiDiff {
     /// Items that have been removed from the public API. A MAJOR change, in
     /// semver terminology. Sorted.
@@ -51,263 +591,2357 @@ pub struct PublicApiDiff {
     /// `ExplicitType`.
     pub changed: Vec<ChangedPublicItem>,
 
-    /// Items that have been added to public API. A MINOR change, in semver
-    /// terminology. Sorted.
-    pub added: Vec<PublicItem>,
-}
+    /// Items that have been added to public API. A MINOR change, in semver
+    /// terminology. Sorted.
+    pub added: Vec<PublicItem>,
+}
+
+impl PublicApiDiff {
+    /// Allows you to diff the public API between two arbitrary versions of a
+    /// library, e.g. different releases. The input parameters `old` and `new`
+    /// is the output of two different invocations of
+    /// [`crate::Builder::build`].
+    ///
+    /// Runs in `O(n log n)` time, where `n` is the number of items. Except
+    /// that items that share the same path, such as associated fns of
+    /// different trait impls, are paired up with each other in quadratic time.
+    #[must_use]
+    pub fn between(old: PublicApi, new: PublicApi) -> Self {
+        Self::between_with_options(old, new, DiffOptions::default())
+    }
+
+    /// Like [`Self::between`], but borrows the items, so that callers that
+    /// want to keep their items do not need to clone them first. Only the
+    /// items that end up in the diff are cloned.
+    #[must_use]
+    pub fn between_slices(old: &[PublicItem], new: &[PublicItem]) -> Self {
+        Self::between_refs(
+            old.iter().collect(),
+            new.iter().collect(),
+            DiffOptions::default(),
+        )
+    }
+
+    /// Like [`Self::between`], but for [`PublicApiSet`]s, which make it
+    /// explicit that the order of items does not matter but duplicates do.
+    #[must_use]
+    pub fn between_sets(old: &PublicApiSet, new: &PublicApiSet) -> Self {
+        Self::between_refs(
+            old.iter().collect(),
+            new.iter().collect(),
+            DiffOptions::default(),
+        )
+    }
+
+    fn between_refs(
+        mut old: Vec<&PublicItem>,
+        mut new: Vec<&PublicItem>,
+        options: DiffOptions,
+    ) -> Self {
+        // Sort the items so that identical items end up in the same group
+        // when merging old and new. Since the number of items in each group is
+        // compared, we do not lose public items that happen to have the same
+        // representation due to limitations or bugs
+        old.sort_unstable_by_key(|item| tokens_and_cfg(item));
+        new.sort_unstable_by_key(|item| tokens_and_cfg(item));
+
+        // First figure out what items have been removed and what have been
+        // added. Later we will match added and removed items with the same
+        // path and construct a list of changed items. A changed item is an
+        // item with the same path that has been both removed and added.
+        let mut all_removed: Vec<&PublicItem> = vec![];
+        let mut all_added: Vec<&PublicItem> = vec![];
+        for (old_items, new_items) in merge_groups(&old, &new, tokens_and_cfg) {
+            all_removed.extend(old_items.iter().skip(new_items.len()));
+            all_added.extend(new_items.iter().skip(old_items.len()));
+        }
+        all_removed.sort_by(|a, b| a.sortable_path.cmp(&b.sortable_path));
+        all_added.sort_by(|a, b| a.sortable_path.cmp(&b.sortable_path));
+
+        // The result we return from this function will be put in these vectors
+        let mut removed: Vec<PublicItem> = vec![];
+        let mut changed: Vec<ChangedPublicItem> = vec![];
+        let mut added: Vec<PublicItem> = vec![];
+
+        // OK, we are ready to do some actual heavy lifting. Go through all
+        // paths and look for changed items. The remaining items are either
+        // purely removed or purely added.
+        for (removed_items, added_items) in merge_groups(&all_removed, &all_added, sortable_path) {
+            // If many items have the same path, e.g. because they are
+            // associated fns of different trait impls, pair up the most
+            // similar items first
+            let pairs = if options.similarity_matching {
+                most_similar_pairs(removed_items, added_items)
+            } else {
+                (0..removed_items.len().min(added_items.len()))
+                    .map(|index| (index, index))
+                    .collect()
+            };
+            changed.extend(
+                pairs
+                    .iter()
+                    .map(|&(old_index, new_index)| ChangedPublicItem {
+                        old: removed_items[old_index].clone(),
+                        new: added_items[new_index].clone(),
+                    }),
+            );
+
+            let (paired_old, paired_new): (HashSet<usize>, HashSet<usize>) =
+                pairs.into_iter().unzip();
+            let unpaired = |items: &[&PublicItem], paired: &HashSet<usize>| {
+                (items.iter().enumerate())
+                    .filter(|(index, _)| !paired.contains(index))
+                    .map(|(_, item)| (*item).clone())
+                    .collect::<Vec<_>>()
+            };
+            removed.extend(unpaired(removed_items, &paired_old));
+            added.extend(unpaired(added_items, &paired_new));
+        }
+
+        if options.ignore_doc_changes {
+            changed.retain(|changed| !changed.is_doc_only_change());
+        }
+        if options.ignore_whitespace {
+            changed.retain(|changed| !changed.is_whitespace_only_change());
+        }
+
+        // Make output predictable and stable
+        removed.sort_by(PublicItem::grouping_cmp);
+        changed.sort_by(ChangedPublicItem::grouping_cmp);
+        added.sort_by(PublicItem::grouping_cmp);
+
+        Self {
+            removed,
+            changed,
+            added,
+        }
+    }
+
+    /// Like [`Self::between`], but allows you to customize how items are
+    /// compared. See [`DiffOptions`].
+    #[must_use]
+    pub fn between_with_options(old: PublicApi, new: PublicApi, options: DiffOptions) -> Self {
+        Self::between_refs(
+            old.items.iter().collect(),
+            new.items.iter().collect(),
+            options,
+        )
+    }
+
+    /// Like [`Self::between`], but for [`crate::PublicApiSnapshot`]s. Use
+    /// [`crate::PublicApiSnapshot::name_and_version`] to describe what was
+    /// diffed, e.g. `example_api 1.0.0` to `example_api 1.1.0`.
+    #[must_use]
+    pub fn between_snapshots(
+        old: &crate::PublicApiSnapshot,
+        new: &crate::PublicApiSnapshot,
+    ) -> Self {
+        Self::between(old.to_public_api(), new.to_public_api())
+    }
+
+    /// Builds the public APIs of the rustdoc JSON strings `old_json` and
+    /// `new_json` with the default [`crate::Builder`] options, and diffs them
+    /// with [`Self::between_with_options`]. A shorthand for when the rustdoc
+    /// JSON is already in memory.
+    ///
+    /// # Errors
+    ///
+    /// [`crate::Error::SerdeJsonError`] if either string is not valid rustdoc
+    /// JSON.
+    pub fn between_strings(
+        old_json: &str,
+        new_json: &str,
+        options: DiffOptions,
+    ) -> crate::Result<Self> {
+        let old = crate::public_api_from_rustdoc_json_bytes(old_json.as_bytes())?;
+        let new = crate::public_api_from_rustdoc_json_bytes(new_json.as_bytes())?;
+        Ok(Self::between_with_options(old, new, options))
+    }
+
+    /// Like [`Self::between`], but only reports changes that would force a
+    /// MAJOR version bump in semver terms. This means that
+    ///
+    /// * all added items are left out, since adding items is always a MINOR
+    ///   change. This covers e.g. new fields of `#[non_exhaustive]` structs
+    ///   and new variants of `#[non_exhaustive]` enums.
+    ///
+    /// * all removed items are kept, since removing items is always a MAJOR
+    ///   change.
+    ///
+    /// * changed items are kept unless the change is provably backwards
+    ///   compatible. The following changes are considered compatible:
+    ///   * Removing `#[non_exhaustive]` from an item
+    ///   * Making a `fn` a `const fn`
+    ///   * Making an `unsafe fn` a safe `fn`
+    ///   * Changing a type from `Self` to the type that `Self` stands for, or
+    ///     vice versa
+    ///   * Removing bounds, unless the item belongs to a trait
+    ///   * Deprecating an item, or changing only its docs or whitespace
+    ///
+    /// This is the same as keeping changes whose
+    /// [`ChangedPublicItem::severity`] is [`ChangeSeverity::Breaking`].
+    ///
+    /// Since Rust has no default values for function parameters, adding a
+    /// parameter to a function is always considered to be a breaking change.
+    #[must_use]
+    pub fn between_semver_compatible(old: PublicApi, new: PublicApi) -> Self {
+        let mut diff = Self::between(old, new);
+        diff.changed
+            .retain(|changed| !changed.is_semver_compatible());
+        diff.added.clear();
+        diff
+    }
+
+    /// Like [`Self::between`], but removed and added items that look the same
+    /// except for their paths are considered to be renamed or moved, and are
+    /// reported as [`ChangedPublicItem`]s with a [`ChangeKind::Renamed`]
+    /// [`ChangedPublicItem::change_kind`].
+    ///
+    /// How similar items must be is controlled by `threshold`, which is
+    /// compared against a similarity score like
+    /// [`PublicItem::similarity_score`] that disregards the paths of the items
+    /// but takes the order of their tokens into account. A `threshold` of
+    /// `1.0` only matches items that are identical except for their paths, and
+    /// lower values also match items that changed in other ways. Only items of
+    /// the same kind, e.g. `struct`s, are ever matched.
+    #[must_use]
+    pub fn between_with_rename_detection(old: PublicApi, new: PublicApi, threshold: f64) -> Self {
+        let mut diff = Self::between(old, new);
+
+        let mut added = std::mem::take(&mut diff.added);
+        for old in std::mem::take(&mut diff.removed) {
+            let best_match = added
+                .iter()
+                .enumerate()
+                .filter(|(_, new)| new.kind_text() == old.kind_text())
+                .map(|(index, new)| (index, old.similarity_score_ignoring_path(new)))
+                .filter(|(_, score)| *score >= threshold)
+                .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+            match best_match {
+                Some((index, _)) => diff.changed.push(ChangedPublicItem {
+                    old,
+                    new: added.remove(index),
+                }),
+                None => diff.removed.push(old),
+            }
+        }
+        diff.added = added;
+
+        // Make output predictable and stable
+        diff.changed.sort_by(ChangedPublicItem::grouping_cmp);
+        diff
+    }
+
+    /// Like [`Self::between`], but a removed and an added item of the same kind
+    /// and at the same path are reported as one [`ChangedPublicItem`] if at
+    /// most `max_edit_distance` tokens differ between them. See
+    /// [`ChangedPublicItem::edit_distance`]. Useful to not get spurious
+    /// removed and added items for small changes that also change how items
+    /// are grouped, such as a changed bound of an `impl`. Use
+    /// [`Self::between_with_tolerance_across_paths`] to also match items at
+    /// different paths. The closest items are matched first. Takes quadratic
+    /// time in the number of removed and added items.
+    #[must_use]
+    pub fn between_with_tolerance(
+        old: PublicApi,
+        new: PublicApi,
+        max_edit_distance: usize,
+    ) -> Self {
+        Self::between(old, new).pair_up_within_tolerance(max_edit_distance, true)
+    }
+
+    /// Like [`Self::between_with_tolerance`], but items at different paths are
+    /// matched too, so that e.g. a removed `pub fn krate::f(x: i32)` and an
+    /// added `pub fn krate::g(x: i32)` are reported as one
+    /// [`ChangedPublicItem`] with a `max_edit_distance` of 1. Items must still
+    /// be of the same kind.
+    #[must_use]
+    pub fn between_with_tolerance_across_paths(
+        old: PublicApi,
+        new: PublicApi,
+        max_edit_distance: usize,
+    ) -> Self {
+        Self::between(old, new).pair_up_within_tolerance(max_edit_distance, false)
+    }
+
+    /// Turns removed and added items of the same kind into changed items if at
+    /// most `max_edit_distance` tokens differ between them and, if
+    /// `same_path`, they have the same path.
+    fn pair_up_within_tolerance(mut self, max_edit_distance: usize, same_path: bool) -> Self {
+        let removed = std::mem::take(&mut self.removed);
+        let added = std::mem::take(&mut self.added);
+
+        let mut candidates = vec![];
+        for (old_index, old) in removed.iter().enumerate() {
+            for (new_index, new) in added.iter().enumerate() {
+                if old.kind_text() != new.kind_text() || (same_path && old.path != new.path) {
+                    continue;
+                }
+                let distance = token_edit_distance(&old.tokens, &new.tokens);
+                if distance <= max_edit_distance {
+                    candidates.push((distance, old_index, new_index));
+                }
+            }
+        }
+
+        // Pair up the closest items first, so that an item is not taken by a
+        // more distant item just because that item comes first
+        candidates.sort_unstable();
+        let mut removed: Vec<Option<PublicItem>> = removed.into_iter().map(Some).collect();
+        let mut added: Vec<Option<PublicItem>> = added.into_iter().map(Some).collect();
+        for (_, old_index, new_index) in candidates {
+            if removed[old_index].is_none() || added[new_index].is_none() {
+                continue;
+            }
+            self.changed.push(ChangedPublicItem {
+                old: removed[old_index].take().expect("checked above"),
+                new: added[new_index].take().expect("checked above"),
+            });
+        }
+        self.removed = removed.into_iter().flatten().collect();
+        self.added = added.into_iter().flatten().collect();
+
+        // Make output predictable and stable
+        self.changed.sort_by(ChangedPublicItem::grouping_cmp);
+        self
+    }
+
+    /// Like [`Self::between`], but items of `old` whose paths start with the
+    /// `old_prefix` of one of the `(old_prefix, new_prefix)` pairs of
+    /// `mappings` are paired with the items of `new` at the same path with
+    /// `new_prefix` instead. For example with the mapping
+    /// `("krate::old_name", "krate::new_name")`, a removed
+    /// `krate::old_name::Foo` and an added `krate::new_name::Foo` are reported
+    /// as a [`ChangedPublicItem`] with a [`ChangeKind::Renamed`]
+    /// [`ChangedPublicItem::change_kind`]. Useful when a module was renamed,
+    /// since all of its items would otherwise be reported as removed and
+    /// added. Prefixes only match whole path components, and the first
+    /// mapping that matches is used.
+    #[must_use]
+    pub fn between_with_path_mapping(
+        old: PublicApi,
+        new: PublicApi,
+        mappings: &[(String, String)],
+    ) -> Self {
+        Self::between(old, new)
+            .pair_up_by_path(|old| map_path_prefix(old, mappings), ToOwned::to_owned)
+    }
+
+    /// Like [`Self::between`], but paths are compared case-insensitively, so
+    /// that e.g. a removed `krate::Foo` and an added `krate::foo` are reported
+    /// as a [`ChangedPublicItem`] with a [`ChangeKind::Renamed`]
+    /// [`ChangedPublicItem::change_kind`]. Useful for code generators that
+    /// are inconsistent in how they case names. The items of the diff keep
+    /// their original paths.
+    #[must_use]
+    pub fn between_ignore_case(old: PublicApi, new: PublicApi) -> Self {
+        Self::between(old, new).pair_up_by_path(|old| Some(old.to_lowercase()), str::to_lowercase)
+    }
+
+    /// Turns removed and added items into changed items if the `old_key` of
+    /// the path of the removed item is the `new_key` of the path of the added
+    /// item. Removed items without an `old_key` stay removed. Among added
+    /// items with the same key, the most similar one is picked.
+    fn pair_up_by_path(
+        mut self,
+        old_key: impl Fn(&str) -> Option<String>,
+        new_key: impl Fn(&str) -> String,
+    ) -> Self {
+        let mut added: Vec<(String, PublicItem)> = std::mem::take(&mut self.added)
+            .into_iter()
+            .map(|new| (new_key(&new.path_string()), new))
+            .collect();
+        for old in std::mem::take(&mut self.removed) {
+            let key = old_key(&old.path_string());
+            let best_match = added
+                .iter()
+                .enumerate()
+                .filter(|(_, (new_key, _))| key.as_ref() == Some(new_key))
+                .map(|(index, (_, new))| (index, old.similarity_score_ignoring_path(new)))
+                .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+            match best_match {
+                Some((index, _)) => self.changed.push(ChangedPublicItem {
+                    old,
+                    new: added.remove(index).1,
+                }),
+                None => self.removed.push(old),
+            }
+        }
+        self.added = added.into_iter().map(|(_, new)| new).collect();
+
+        // Make output predictable and stable
+        self.changed.sort_by(ChangedPublicItem::grouping_cmp);
+        self
+    }
+
+    /// Applies the diff to `old_items`, i.e. removes the removed items,
+    /// replaces the old version of changed items with the new version, and
+    /// adds the added items. If `old_items` are the items of the `old` public
+    /// API the diff was made from, the items of the `new` public API are
+    /// returned, sorted like [`crate::Builder::sorted`] sorts them.
+    ///
+    /// # Errors
+    ///
+    /// [`crate::Error::ItemMissingWhenApplyingDiff`] if an item that is
+    /// removed or changed is not in `old_items`.
+    pub fn apply(&self, old_items: Vec<PublicItem>) -> crate::Result<Vec<PublicItem>> {
+        apply(
+            old_items,
+            &self.removed,
+            self.changed.iter().map(|c| (&c.old, &c.new)),
+            &self.added,
+        )
+    }
+
+    /// The opposite of [`Self::apply`]. Reverts the diff by removing the added
+    /// items, replacing the new version of changed items with the old version,
+    /// and adding back the removed items. It always holds that
+    /// `diff.apply_inverse(diff.apply(old_items)?)? == old_items` for sorted
+    /// `old_items`.
+    ///
+    /// # Errors
+    ///
+    /// [`crate::Error::ItemMissingWhenApplyingDiff`] if an item that is added
+    /// or changed is not in `new_items`.
+    pub fn apply_inverse(&self, new_items: Vec<PublicItem>) -> crate::Result<Vec<PublicItem>> {
+        apply(
+            new_items,
+            &self.added,
+            self.changed.iter().map(|c| (&c.new, &c.old)),
+            &self.removed,
+        )
+    }
+
+    /// Asserts that `actual` equals `expected`, for use in tests. Unlike
+    /// `assert_eq!`, a mismatch panics with only the items that differ,
+    /// marked `<` if they are only in `expected` and `>` if they are only in
+    /// `actual`, for each of the removed, changed and added categories.
+    ///
+    /// # Panics
+    ///
+    /// If `actual` does not equal `expected`.
+    #[track_caller]
+    pub fn assert_eq(actual: &Self, expected: &Self) {
+        if actual == expected {
+            return;
+        }
+
+        let changed = |diff: &Self| -> Vec<String> {
+            (diff.changed.iter())
+                .map(|c| format!("{} => {}", c.old, c.new))
+                .collect()
+        };
+        let mut message = String::from("Diffs are not equal (< expected / > actual):\n");
+        for (category, actual, expected) in [
+            (
+                "removed",
+                to_strings(&actual.removed),
+                to_strings(&expected.removed),
+            ),
+            ("changed", changed(actual), changed(expected)),
+            (
+                "added",
+                to_strings(&actual.added),
+                to_strings(&expected.added),
+            ),
+        ] {
+            let only_expected = multiset_difference(&expected, &actual);
+            let only_actual = multiset_difference(&actual, &expected);
+            if only_expected.is_empty() && only_actual.is_empty() {
+                continue;
+            }
+            message.push_str(&format!("{category}:\n"));
+            for item in only_expected {
+                message.push_str(&format!("< {item}\n"));
+            }
+            for item in only_actual {
+                message.push_str(&format!("> {item}\n"));
+            }
+        }
+        panic!("{message}");
+    }
+
+    /// Check whether the diff is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.changed.is_empty() && self.added.is_empty()
+    }
+
+    /// A stability score between `0.0` and `1.0` for dashboards, where `1.0`
+    /// means that nothing changed. `old_count` is the number of items in the
+    /// old public API, since a diff does not know about unchanged items. The
+    /// score is computed as
+    ///
+    /// ```txt
+    /// 1.0 - (removed + changed + added) / (old_count + added)
+    /// ```
+    ///
+    /// and is `1.0` if both the old and the new API are empty.
+    #[must_use]
+    pub fn score(&self, old_count: usize) -> f64 {
+        let total = old_count + self.added.len();
+        if total == 0 {
+            return 1.0;
+        }
+
+        let differences = self.removed.len() + self.changed.len() + self.added.len();
+        #[allow(clippy::cast_precision_loss)] // Item counts are small
+        let score = 1.0 - differences as f64 / total as f64;
+        score.clamp(0.0, 1.0)
+    }
+
+    /// Keeps only the parts of the diff that are at least as severe as `min`,
+    /// e.g. only breaking changes for [`ChangeSeverity::Breaking`]. Changed
+    /// items are filtered by their [`ChangedPublicItem::severity`]. Removed
+    /// items are always breaking and are always kept. Added items are
+    /// [`ChangeSeverity::Compatible`], so they are only kept if `min` is
+    /// `Compatible` or milder.
+    #[must_use]
+    pub fn with_min_severity(mut self, min: ChangeSeverity) -> Self {
+        self.changed.retain(|changed| changed.severity() >= min);
+        if ChangeSeverity::Compatible < min {
+            self.added.clear();
+        }
+        self
+    }
+
+    /// The changed items of the diff, partitioned by their
+    /// [`ChangedPublicItem::change_kind`]. Useful for reports such as "all
+    /// renamed items". Within each kind, items keep the order of
+    /// [`Self::changed`].
+    #[must_use]
+    pub fn grouped_by_change_kind(&self) -> BTreeMap<ChangeKind, Vec<&ChangedPublicItem>> {
+        let mut groups: BTreeMap<ChangeKind, Vec<&ChangedPublicItem>> = BTreeMap::new();
+        for changed in &self.changed {
+            groups
+                .entry(changed.change_kind())
+                .or_default()
+                .push(changed);
+        }
+        groups
+    }
+
+    /// The items of `all_items`, the items of the new public API, that are
+    /// neither added nor changed in the diff. Items that occur more than once
+    /// are only left out as many times as they were added or changed. Can be
+    /// passed to [`Self::with_context`].
+    #[must_use]
+    pub fn unchanged_items(&self, all_items: &[PublicItem]) -> Vec<PublicItem> {
+        let mut not_unchanged: HashMap<&PublicItem, usize> = HashMap::new();
+        for item in self.added.iter().chain(self.changed.iter().map(|c| &c.new)) {
+            *not_unchanged.entry(item).or_default() += 1;
+        }
+        all_items
+            .iter()
+            .filter(|item| match not_unchanged.get_mut(item) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Combines the diff with `unchanged`, the items that are in both the old
+    /// and the new public API, so that the diff can be shown in context like
+    /// `git diff` shows it. All items end up in one list, sorted like
+    /// [`crate::Builder::sorted`] sorts them.
+    #[must_use]
+    pub fn with_context(&self, unchanged: Vec<PublicItem>) -> AnnotatedDiff {
+        let mut items: Vec<AnnotatedItem> = unchanged
+            .into_iter()
+            .map(AnnotatedItem::Unchanged)
+            .chain(self.removed.iter().cloned().map(AnnotatedItem::Removed))
+            .chain(self.changed.iter().cloned().map(AnnotatedItem::Changed))
+            .chain(self.added.iter().cloned().map(AnnotatedItem::Added))
+            .collect();
+        items.sort_by(|a, b| a.sort_key().grouping_cmp(b.sort_key()));
+        AnnotatedDiff { items }
+    }
+}
+
+/// A [`PublicApiDiff`] together with the items that did not change. Returned
+/// by [`PublicApiDiff::with_context`]. Implements [`std::fmt::Display`], which
+/// prints one item per line, prefixed with ` ` if unchanged, `-` if removed
+/// and `+` if added. Changed items are printed as a removed old item followed
+/// by an added new item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnnotatedDiff {
+    /// All items, sorted.
+    pub items: Vec<AnnotatedItem>,
+}
+
+impl std::fmt::Display for AnnotatedDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for item in &self.items {
+            match item {
+                AnnotatedItem::Unchanged(item) => writeln!(f, " {item}")?,
+                AnnotatedItem::Removed(item) => writeln!(f, "-{item}")?,
+                AnnotatedItem::Changed(changed) => {
+                    let (old, new) = changed.diff_lines();
+                    writeln!(f, "-{old}\n+{new}")?;
+                }
+                AnnotatedItem::Added(item) => writeln!(f, "+{item}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An item of an [`AnnotatedDiff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnnotatedItem {
+    /// The item is the same in the old and the new public API.
+    Unchanged(PublicItem),
+
+    /// The item has been removed, see [`PublicApiDiff::removed`].
+    Removed(PublicItem),
+
+    /// The item has been changed, see [`PublicApiDiff::changed`].
+    Changed(ChangedPublicItem),
+
+    /// The item has been added, see [`PublicApiDiff::added`].
+    Added(PublicItem),
+}
+
+impl AnnotatedItem {
+    /// The item to sort by. Changed items are sorted by their new version.
+    fn sort_key(&self) -> &PublicItem {
+        match self {
+            Self::Unchanged(item) | Self::Removed(item) | Self::Added(item) => item,
+            Self::Changed(changed) => &changed.new,
+        }
+    }
+}
+
+/// A [`PublicApiDiff`] where each removed, changed and added item is shown
+/// together with up to `context` unchanged items above and below it, like
+/// `git diff -U<context>` shows lines. Changes that are close to each other
+/// end up in the same hunk. Implements [`std::fmt::Display`], which prints the
+/// hunks like [`AnnotatedDiff`] does, separated by lines with `...`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffWithContext {
+    /// The groups of changes and the unchanged items around them, sorted.
+    pub hunks: Vec<AnnotatedDiff>,
+}
+
+impl DiffWithContext {
+    /// Creates the hunks of `diff`. The unchanged items are the items of
+    /// `all_items`, the items of the new public API, that are neither added
+    /// nor changed in `diff`. Items are adjacent if they are next to each
+    /// other when all items are sorted like [`crate::Builder::sorted`] sorts
+    /// them.
+    #[must_use]
+    pub fn new(diff: &PublicApiDiff, all_items: &[PublicItem], context: usize) -> Self {
+        let items = diff.with_context(diff.unchanged_items(all_items)).items;
+
+        // The ranges of items to show, with overlapping and adjacent ranges
+        // merged
+        let mut ranges: Vec<(usize, usize)> = vec![];
+        let changes = items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| !matches!(item, AnnotatedItem::Unchanged(_)))
+            .map(|(index, _)| index);
+        for index in changes {
+            let start = index.saturating_sub(context);
+            let end = index.saturating_add(context).min(items.len() - 1);
+            match ranges.last_mut() {
+                Some((_, last_end)) if start <= last_end.saturating_add(1) => *last_end = end,
+                _ => ranges.push((start, end)),
+            }
+        }
+
+        Self {
+            hunks: ranges
+                .into_iter()
+                .map(|(start, end)| AnnotatedDiff {
+                    items: items[start..=end].to_vec(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for DiffWithContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, hunk) in self.hunks.iter().enumerate() {
+            if index > 0 {
+                writeln!(f, "...")?;
+            }
+            write!(f, "{hunk}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Constructs a [`PublicApiDiff`] item by item. Mainly intended for tests that
+/// want to compare the result of [`PublicApiDiff::between`] with an expected
+/// diff.
+///
+/// ```
+/// use public_api::diff::{DiffBuilder, PublicApiDiff};
+/// use public_api::{PublicApi, PublicItem};
+///
+/// fn assert_diff(
+///     old: PublicApi,
+///     new: PublicApi,
+///     removed_item: PublicItem,
+///     added_item: PublicItem,
+/// ) -> public_api::Result<()> {
+///     let expected = DiffBuilder::new()
+///         .removed(removed_item)
+///         .added(added_item)
+///         .build()?;
+///     assert_eq!(PublicApiDiff::between(old, new), expected);
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DiffBuilder {
+    diff: PublicApiDiff,
+}
+
+impl DiffBuilder {
+    /// Create a builder for an empty diff.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `item` to [`PublicApiDiff::removed`].
+    #[must_use]
+    pub fn removed(mut self, item: PublicItem) -> Self {
+        self.diff.removed.push(item);
+        self
+    }
+
+    /// Adds the change from `old` to `new` to [`PublicApiDiff::changed`].
+    #[must_use]
+    pub fn changed(mut self, old: PublicItem, new: PublicItem) -> Self {
+        self.diff.changed.push(ChangedPublicItem { old, new });
+        self
+    }
+
+    /// Adds `item` to [`PublicApiDiff::added`].
+    #[must_use]
+    pub fn added(mut self, item: PublicItem) -> Self {
+        self.diff.added.push(item);
+        self
+    }
+
+    /// Builds the diff. The items are sorted in the same way as by
+    /// [`PublicApiDiff::between`], so that the diffs can be compared.
+    ///
+    /// # Errors
+    ///
+    /// [`crate::Error::ItemInManyDiffCategories`] if the same item is in more
+    /// than one of the removed, changed and added categories.
+    pub fn build(self) -> crate::Result<PublicApiDiff> {
+        let mut diff = self.diff;
+
+        let removed: HashSet<&PublicItem> = diff.removed.iter().collect();
+        let changed: HashSet<&PublicItem> = (diff.changed.iter())
+            .flat_map(|c| [&c.old, &c.new])
+            .collect();
+        let added: HashSet<&PublicItem> = diff.added.iter().collect();
+        let in_many = (removed.intersection(&changed))
+            .chain(removed.intersection(&added))
+            .chain(changed.intersection(&added))
+            .next();
+        if let Some(item) = in_many {
+            return Err(crate::Error::ItemInManyDiffCategories(item.to_string()));
+        }
+
+        // Make output predictable and stable
+        diff.removed.sort_by(PublicItem::grouping_cmp);
+        diff.changed.sort_by(ChangedPublicItem::grouping_cmp);
+        diff.added.sort_by(PublicItem::grouping_cmp);
+        Ok(diff)
+    }
+}
+
+/// Helper for [`PublicApiDiff::apply`] and [`PublicApiDiff::apply_inverse`].
+/// Removes `removed` from `items`, replaces the first item of each pair in
+/// `changed` with the second, and adds `added`.
+fn apply<'a>(
+    mut items: Vec<PublicItem>,
+    removed: &[PublicItem],
+    changed: impl Iterator<Item = (&'a PublicItem, &'a PublicItem)>,
+    added: &[PublicItem],
+) -> crate::Result<Vec<PublicItem>> {
+    let position = |items: &[PublicItem], item: &PublicItem| {
+        (items.iter().position(|i| i == item))
+            .ok_or_else(|| crate::Error::ItemMissingWhenApplyingDiff(item.to_string()))
+    };
+
+    for item in removed {
+        items.swap_remove(position(&items, item)?);
+    }
+    for (from, to) in changed {
+        let index = position(&items, from)?;
+        items[index] = to.clone();
+    }
+    items.extend(added.iter().cloned());
+
+    // Make output predictable and stable
+    items.sort_by(PublicItem::grouping_cmp);
+    Ok(items)
+}
+
+/// Pairs up the items of `old` and `new` by [`PublicItem::similarity_score`],
+/// most similar pair first, until either runs out of items. Returns the indices
+/// of the paired items. Each score is computed only once, so that pairing up
+/// many items with the same path stays fast.
+fn most_similar_pairs(old: &[&PublicItem], new: &[&PublicItem]) -> Vec<(usize, usize)> {
+    let mut candidates: Vec<(f64, usize, usize)> = (old.iter().enumerate())
+        .flat_map(|(old_index, old_item)| {
+            (new.iter().enumerate()).map(move |(new_index, new_item)| {
+                (old_item.similarity_score(new_item), old_index, new_index)
+            })
+        })
+        .collect();
+    // The sort is stable, so equally similar pairs stay in sorted order
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut old_is_paired = vec![false; old.len()];
+    let mut new_is_paired = vec![false; new.len()];
+    let mut pairs = vec![];
+    for (_, old_index, new_index) in candidates {
+        if !old_is_paired[old_index] && !new_is_paired[new_index] {
+            old_is_paired[old_index] = true;
+            new_is_paired[new_index] = true;
+            pairs.push((old_index, new_index));
+        }
+    }
+    pairs
+}
+
+fn to_strings(items: &[PublicItem]) -> Vec<String> {
+    items.iter().map(ToString::to_string).collect()
+}
+
+/// The strings of `a` that are not in `b`, counting duplicates.
+fn multiset_difference<'a>(a: &'a [String], b: &[String]) -> Vec<&'a str> {
+    let mut b: Vec<&str> = b.iter().map(String::as_str).collect();
+    a.iter()
+        .map(String::as_str)
+        .filter(|item| match b.iter().position(|other| other == item) {
+            Some(index) => {
+                b.swap_remove(index);
+                false
+            }
+            None => true,
+        })
+        .collect()
+}
+
+/// The Levenshtein distance between `a` and `b`, counted in tokens.
+fn token_edit_distance(a: &[Token], b: &[Token]) -> usize {
+    // Only the previous row of the distance matrix is needed
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_token) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_token) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_token != b_token);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Replaces the `old_prefix` of the first `(old_prefix, new_prefix)` of
+/// `mappings` that is a prefix of `path` with `new_prefix`. Returns `None` if
+/// no mapping matches. See [`PublicApiDiff::between_with_path_mapping`].
+fn map_path_prefix(path: &str, mappings: &[(String, String)]) -> Option<String> {
+    mappings.iter().find_map(|(old_prefix, new_prefix)| {
+        let rest = path.strip_prefix(old_prefix.as_str())?;
+        (rest.is_empty() || rest.starts_with("::")).then(|| format!("{new_prefix}{rest}"))
+    })
+}
+
+/// Merges `old` and `new`, which must both be sorted by `key`, into groups
+/// of items that have the same key. Each group consists of the items from
+/// `old` and the items from `new` with that key, and either can be empty. Runs
+/// in linear time.
+fn merge_groups<'a, 'i, K: Ord + Copy>(
+    mut old: &'a [&'i PublicItem],
+    mut new: &'a [&'i PublicItem],
+    key: fn(&'i PublicItem) -> K,
+) -> Vec<(&'a [&'i PublicItem], &'a [&'i PublicItem])> {
+    let mut groups = vec![];
+    loop {
+        let group_key = match (old.first(), new.first()) {
+            (Some(old_item), Some(new_item)) => key(old_item).min(key(new_item)),
+            (Some(item), None) | (None, Some(item)) => key(item),
+            (None, None) => return groups,
+        };
+
+        let split = |items: &mut &'a [&'i PublicItem]| {
+            let len = items
+                .iter()
+                .take_while(|item| key(item).cmp(&group_key) == Ordering::Equal)
+                .count();
+            let (group, rest) = items.split_at(len);
+            *items = rest;
+            group
+        };
+        groups.push((split(&mut old), split(&mut new)));
+    }
+}
+
+/// What makes items equal, see the [`PartialEq`] impl of [`PublicItem`].
+fn tokens_and_cfg(item: &PublicItem) -> (&[Token], &[String]) {
+    (&item.tokens, &item.cfg_conditions)
+}
+
+fn sortable_path(item: &PublicItem) -> &PublicItemPath {
+    &item.sortable_path
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::public_item::{test_fn, test_item, with_fn_qualifier};
+    use crate::tokens::Token;
+
+    use super::*;
+
+    #[test]
+    fn single_and_only_item_removed() {
+        let old = api([item_with_path("foo")]);
+        let new = api([]);
+
+        let actual = PublicApiDiff::between(old, new);
+        let expected = PublicApiDiff {
+            removed: vec![item_with_path("foo")],
+            changed: vec![],
+            added: vec![],
+        };
+        assert_eq!(actual, expected);
+        assert!(!actual.is_empty());
+    }
+
+    #[test]
+    fn single_and_only_item_added() {
+        let old = api([]);
+        let new = api([item_with_path("foo")]);
+
+        let actual = PublicApiDiff::between(old, new);
+        let expected = PublicApiDiff {
+            removed: vec![],
+            changed: vec![],
+            added: vec![item_with_path("foo")],
+        };
+        assert_eq!(actual, expected);
+        assert!(!actual.is_empty());
+    }
+
+    #[test]
+    fn middle_item_added() {
+        let old = api([item_with_path("1"), item_with_path("3")]);
+        let new = api([
+            item_with_path("1"),
+            item_with_path("2"),
+            item_with_path("3"),
+        ]);
+
+        let actual = PublicApiDiff::between(old, new);
+        let expected = PublicApiDiff {
+            removed: vec![],
+            changed: vec![],
+            added: vec![item_with_path("2")],
+        };
+        assert_eq!(actual, expected);
+        assert!(!actual.is_empty());
+    }
+
+    #[test]
+    fn middle_item_removed() {
+        let old = api([
+            item_with_path("1"),
+            item_with_path("2"),
+            item_with_path("3"),
+        ]);
+        let new = api([item_with_path("1"), item_with_path("3")]);
+
+        let actual = PublicApiDiff::between(old, new);
+        let expected = PublicApiDiff {
+            removed: vec![item_with_path("2")],
+            changed: vec![],
+            added: vec![],
+        };
+        assert_eq!(actual, expected);
+        assert!(!actual.is_empty());
+    }
+
+    #[test]
+    fn many_identical_items() {
+        let old = api([
+            item_with_path("1"),
+            item_with_path("2"),
+            item_with_path("2"),
+            item_with_path("3"),
+            item_with_path("3"),
+            item_with_path("3"),
+            fn_with_param_type(&["a", "b"], "i32"),
+            fn_with_param_type(&["a", "b"], "i32"),
+        ]);
+        let new = api([
+            item_with_path("1"),
+            item_with_path("2"),
+            item_with_path("3"),
+            item_with_path("4"),
+            item_with_path("4"),
+            fn_with_param_type(&["a", "b"], "i64"),
+            fn_with_param_type(&["a", "b"], "i64"),
+        ]);
+
+        let actual = PublicApiDiff::between(old, new);
+        let expected = PublicApiDiff {
+            removed: vec![
+                item_with_path("2"),
+                item_with_path("3"),
+                item_with_path("3"),
+            ],
+            changed: vec![
+                ChangedPublicItem {
+                    old: fn_with_param_type(&["a", "b"], "i32"),
+                    new: fn_with_param_type(&["a", "b"], "i64"),
+                },
+                ChangedPublicItem {
+                    old: fn_with_param_type(&["a", "b"], "i32"),
+                    new: fn_with_param_type(&["a", "b"], "i64"),
+                },
+            ],
+            added: vec![item_with_path("4"), item_with_path("4")],
+        };
+        assert_eq!(actual, expected);
+        assert!(!actual.is_empty());
+    }
+
+    /// Regression test for
+    /// <https://github.com/cargo-public-api/cargo-public-api/issues/50>
+    #[test]
+    fn no_off_by_one_diff_skewing() {
+        let old = api([
+            fn_with_param_type(&["a", "b"], "i8"),
+            fn_with_param_type(&["a", "b"], "i32"),
+            fn_with_param_type(&["a", "b"], "i64"),
+        ]);
+        // Same as `old` but with a new item with the same path added on top.
+        // The diffing algorithm needs to figure out that only one item has been
+        // added, rather than showing that of three items changed.
+        let new = api([
+            fn_with_param_type(&["a", "b"], "u8"), // The only new item!
+            fn_with_param_type(&["a", "b"], "i8"),
+            fn_with_param_type(&["a", "b"], "i32"),
+            fn_with_param_type(&["a", "b"], "i64"),
+        ]);
+        let expected = PublicApiDiff {
+            removed: vec![],
+            changed: vec![],
+            added: vec![fn_with_param_type(&["a", "b"], "u8")],
+        };
+        let actual = PublicApiDiff::between(old, new);
+        assert_eq!(actual, expected);
+        assert!(!actual.is_empty());
+    }
+
+    #[test]
+    fn most_similar_items_with_same_path_are_paired() {
+        let old_fn = fn_with_param_type(&["a", "b"], "i32");
+        let new_fn = fn_with_param_type(&["a", "b"], "i64");
+        let old_struct = test_item(
+            vec!["a".to_owned(), "b".to_owned()],
+            vec![
+                q("pub"),
+                w(),
+                k("struct"),
+                w(),
+                i("a"),
+                Token::path_separator(),
+                t("b"),
+            ],
+        );
+        let new_struct = non_exhaustive(&old_struct);
+
+        let old = api([old_fn.clone(), old_struct.clone()]);
+        let new = api([new_struct.clone(), new_fn.clone()]);
+
+        let actual = PublicApiDiff::between(old, new);
+        let expected = PublicApiDiff {
+            removed: vec![],
+            changed: vec![
+                ChangedPublicItem {
+                    old: old_fn,
+                    new: new_fn,
+                },
+                ChangedPublicItem {
+                    old: old_struct,
+                    new: new_struct,
+                },
+            ],
+            added: vec![],
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn renamed_struct_is_detected() {
+        let foo = struct_item(&["krate", "Foo"]);
+        let bar = struct_item(&["krate", "Bar"]);
+        let unrelated = fn_with_param_type(&["krate", "f"], "i32");
+
+        let old = api([foo.clone()]);
+        let new = api([bar.clone(), unrelated.clone()]);
+
+        let actual = PublicApiDiff::between_with_rename_detection(old, new, 1.0);
+        let expected = PublicApiDiff {
+            removed: vec![],
+            changed: vec![ChangedPublicItem { old: foo, new: bar }],
+            added: vec![unrelated],
+        };
+        assert_eq!(actual, expected);
+        assert_eq!(
+            actual.changed[0].change_kind(),
+            ChangeKind::Renamed {
+                old_path: "krate::Foo".to_owned(),
+                new_path: "krate::Bar".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn rename_detection_threshold() {
+        let old = || api([fn_with_param_type(&["krate", "f"], "i32")]);
+        let new = || api([fn_with_param_type(&["krate", "g"], "i64")]);
+
+        let strict = PublicApiDiff::between_with_rename_detection(old(), new(), 1.0);
+        assert_eq!((strict.removed.len(), strict.changed.len()), (1, 0));
+
+        let lenient = PublicApiDiff::between_with_rename_detection(old(), new(), 0.5);
+        assert_eq!((lenient.removed.len(), lenient.changed.len()), (0, 1));
+    }
+
+    #[test]
+    fn rename_detection_considers_token_order() {
+        let item = |name: &str, types: [&str; 2]| {
+            let mut tokens = vec![q("pub"), w(), k("fn"), w(), i("krate")];
+            tokens.extend([Token::path_separator(), i(name), s("(")]);
+            tokens.extend([i("a"), s(":"), w(), t(types[0]), s(","), w()]);
+            tokens.extend([i("b"), s(":"), w(), t(types[1]), s(")")]);
+            test_item(vec!["krate".to_owned(), name.to_owned()], tokens)
+        };
+        let f = item("f", ["A", "B"]);
+        let same_order = item("g", ["A", "B"]);
+        let other_order = item("h", ["B", "A"]);
+
+        let old = api([f.clone()]);
+        let new = api([same_order.clone(), other_order.clone()]);
+        let actual = PublicApiDiff::between_with_rename_detection(old, new, 0.5);
+        let expected = PublicApiDiff {
+            removed: vec![],
+            changed: vec![ChangedPublicItem {
+                old: f.clone(),
+                new: same_order,
+            }],
+            added: vec![other_order.clone()],
+        };
+        assert_eq!(actual, expected);
+        assert!(f.similarity_score_ignoring_path(&other_order) < 1.0);
+    }
+
+    #[test]
+    fn rename_detection_keeps_tokens_named_like_path() {
+        // `pub fn krate::Foo(x: Foo)` and `pub fn krate::Bar(x: Foo)` only
+        // differ in their paths, but the `Foo` parameter type is kept
+        let item = |name: &str| {
+            let mut tokens = vec![q("pub"), w(), k("fn"), w(), i("krate")];
+            tokens.extend([Token::path_separator(), i(name), s("(")]);
+            tokens.extend([i("x"), s(":"), w(), i("Foo"), s(")")]);
+            test_item(vec!["krate".to_owned(), name.to_owned()], tokens)
+        };
+        let foo = item("Foo");
+        let bar = item("Bar");
+        let mut baz = item("Baz");
+        baz.tokens = baz
+            .tokens()
+            .map(|token| {
+                if *token == i("Foo") {
+                    i("u8")
+                } else {
+                    token.clone()
+                }
+            })
+            .collect();
+
+        assert!((foo.similarity_score_ignoring_path(&bar) - 1.0).abs() < f64::EPSILON);
+        assert!(foo.similarity_score_ignoring_path(&baz) < 1.0);
+    }
+
+    #[test]
+    fn items_within_tolerance_are_matched() {
+        // An `impl` whose bound changed is grouped elsewhere, so `between()`
+        // reports it as removed and added
+        let old_impl = impl_item(&["impl"]);
+        let mut new_impl = impl_item(&["unsafe", "impl"]);
+        new_impl.sortable_path = vec!["020-unsafe impl Send for Foo".to_owned()];
+        let old = || api([old_impl.clone()]);
+        let new = || api([new_impl.clone()]);
+
+        let exact = PublicApiDiff::between_with_tolerance(old(), new(), 0);
+        assert_eq!((exact.removed.len(), exact.changed.len()), (1, 0));
+
+        let tolerant = PublicApiDiff::between_with_tolerance(old(), new(), 2);
+        let expected = PublicApiDiff {
+            removed: vec![],
+            changed: vec![ChangedPublicItem {
+                old: old_impl,
+                new: new_impl,
+            }],
+            added: vec![],
+        };
+        assert_eq!(tolerant, expected);
+        assert_eq!(tolerant.changed[0].edit_distance(), 2);
+    }
+
+    #[test]
+    fn items_within_tolerance_across_paths_are_matched() {
+        let f = fn_with_param_type(&["krate", "f"], "i32");
+        let g = fn_with_param_type(&["krate", "g"], "i32");
+        let h = fn_with_param_type(&["krate", "h"], "i64");
+        let old = || api([f.clone()]);
+        let new = || api([h.clone(), g.clone()]);
+
+        let same_path = PublicApiDiff::between_with_tolerance(old(), new(), 1);
+        assert_eq!((same_path.removed.len(), same_path.changed.len()), (1, 0));
+
+        let tolerant = PublicApiDiff::between_with_tolerance_across_paths(old(), new(), 1);
+        let expected = PublicApiDiff {
+            removed: vec![],
+            changed: vec![ChangedPublicItem {
+                old: f.clone(),
+                new: g.clone(),
+            }],
+            added: vec![h.clone()],
+        };
+        assert_eq!(tolerant, expected);
+        assert_eq!(tolerant.changed[0].edit_distance(), 1);
+
+        let changed = ChangedPublicItem { old: f, new: h };
+        assert_eq!(changed.edit_distance(), 2);
+    }
+
+    #[test]
+    fn items_of_different_kinds_within_tolerance_are_not_matched() {
+        let module = test_item(
+            ["krate", "a"],
+            vec![
+                q("pub"),
+                w(),
+                k("mod"),
+                w(),
+                i("krate"),
+                Token::path_separator(),
+                i("a"),
+            ],
+        );
+        let s = struct_item(&["krate", "b"]);
+        assert_eq!(token_edit_distance(&module.tokens, &s.tokens), 2);
+
+        let actual = PublicApiDiff::between_with_tolerance_across_paths(
+            api([module.clone()]),
+            api([s.clone()]),
+            2,
+        );
+        let expected = PublicApiDiff {
+            removed: vec![module],
+            changed: vec![],
+            added: vec![s],
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn closest_items_within_tolerance_are_matched_first() {
+        // `f` comes first and is closest to `g`, but `h` is even closer to `g`
+        // and can't be matched with anything else
+        let f = test_fn(&["krate", "f"], &[("a", "i32"), ("b", "i32")]);
+        let h = test_fn(&["krate", "h"], &[("a", "i32"), ("b", "u8")]);
+        let g = test_fn(&["krate", "g"], &[("a", "i32"), ("b", "u8")]);
+        let k = test_fn(&["krate", "k"], &[("c", "i64"), ("b", "i32")]);
+        assert_eq!(token_edit_distance(&f.tokens, &g.tokens), 1 + 1);
+        assert_eq!(token_edit_distance(&h.tokens, &g.tokens), 1);
+        assert_eq!(token_edit_distance(&f.tokens, &k.tokens), 1 + 2);
+
+        let old = api([f.clone(), h.clone()]);
+        let new = api([g.clone(), k.clone()]);
+        let actual = PublicApiDiff::between_with_tolerance_across_paths(old, new, 3);
+        let expected = PublicApiDiff {
+            removed: vec![],
+            changed: vec![
+                ChangedPublicItem { old: f, new: k },
+                ChangedPublicItem { old: h, new: g },
+            ],
+            added: vec![],
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn token_edit_distance_counts_tokens() {
+        let tokens = [i("a"), w(), i("b"), w(), i("c")];
+        assert_eq!(token_edit_distance(&tokens, &tokens), 0);
+        assert_eq!(token_edit_distance(&tokens, &[]), 5);
+        assert_eq!(token_edit_distance(&[], &tokens), 5);
+        assert_eq!(token_edit_distance(&tokens, &[i("a"), w(), i("c")]), 2);
+        assert_eq!(
+            token_edit_distance(&tokens, &[i("a"), w(), i("x"), w(), i("c")]),
+            1
+        );
+    }
+
+    #[test]
+    fn renamed_module_is_mapped() {
+        let old_foo = struct_item(&["krate", "old_name", "Foo"]);
+        let old_bar = fn_with_param_type(&["krate", "old_name", "bar"], "i32");
+        let new_foo = struct_item(&["krate", "new_name", "Foo"]);
+        let new_bar = fn_with_param_type(&["krate", "new_name", "bar"], "i64");
+        let old_prefix_only = struct_item(&["krate", "old_name_2", "Foo"]);
+
+        let old = || api([old_foo.clone(), old_bar.clone(), old_prefix_only.clone()]);
+        let new = || api([new_foo.clone(), new_bar.clone()]);
+
+        let plain = PublicApiDiff::between(old(), new());
+        assert_eq!((plain.removed.len(), plain.changed.len()), (3, 0));
+
+        let mappings = [("krate::old_name".to_owned(), "krate::new_name".to_owned())];
+        let actual = PublicApiDiff::between_with_path_mapping(old(), new(), &mappings);
+        let expected = PublicApiDiff {
+            removed: vec![old_prefix_only],
+            changed: vec![
+                ChangedPublicItem {
+                    old: old_foo,
+                    new: new_foo,
+                },
+                ChangedPublicItem {
+                    old: old_bar,
+                    new: new_bar,
+                },
+            ],
+            added: vec![],
+        };
+        assert_eq!(actual, expected);
+        assert_eq!(
+            actual.changed[0].change_kind(),
+            ChangeKind::Renamed {
+                old_path: "krate::old_name::Foo".to_owned(),
+                new_path: "krate::new_name::Foo".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn case_is_ignored() {
+        let upper = struct_item(&["foo", "Bar"]);
+        let lower = struct_item(&["foo", "bar"]);
+        let other = struct_item(&["foo", "Baz"]);
+
+        let plain = PublicApiDiff::between(api([upper.clone()]), api([lower.clone()]));
+        assert_eq!((plain.removed.len(), plain.added.len()), (1, 1));
+
+        let actual = PublicApiDiff::between_ignore_case(
+            api([upper.clone()]),
+            api([lower.clone(), other.clone()]),
+        );
+        let expected = PublicApiDiff {
+            removed: vec![],
+            changed: vec![ChangedPublicItem {
+                old: upper,
+                new: lower,
+            }],
+            added: vec![other],
+        };
+        assert_eq!(actual, expected);
+        assert_eq!(
+            actual.changed[0].change_kind(),
+            ChangeKind::Renamed {
+                old_path: "foo::Bar".to_owned(),
+                new_path: "foo::bar".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn diff_with_context_groups_changes_into_hunks() {
+        let items: Vec<PublicItem> = ["a", "b", "c", "d", "e", "f", "g", "h"]
+            .into_iter()
+            .map(item_with_path)
+            .collect();
+        let removed = item_with_path("bb");
+        let diff = PublicApiDiff {
+            removed: vec![removed.clone()],
+            changed: vec![],
+            added: vec![items[2].clone(), items[7].clone()],
+        };
+
+        let with_context = DiffWithContext::new(&diff, &items, 1);
+        assert_eq!(with_context.hunks.len(), 2);
+        assert_eq!(with_context.to_string(), " b\n-bb\n+c\n d\n...\n g\n+h\n");
+
+        let without_context = DiffWithContext::new(&diff, &items, 0);
+        assert_eq!(without_context.to_string(), "-bb\n+c\n...\n+h\n");
+
+        let with_all = DiffWithContext::new(&diff, &items, usize::MAX);
+        assert_eq!(
+            with_all.hunks,
+            [diff.with_context(vec![
+                items[0].clone(),
+                items[1].clone(),
+                items[3].clone(),
+                items[4].clone(),
+                items[5].clone(),
+                items[6].clone(),
+            ])]
+        );
+
+        let no_changes = PublicApiDiff::default();
+        assert_eq!(DiffWithContext::new(&no_changes, &items, 1).hunks, []);
+    }
+
+    #[test]
+    fn regular_change_is_modified() {
+        let changed = ChangedPublicItem {
+            old: fn_with_param_type(&["a", "b"], "i32"),
+            new: fn_with_param_type(&["a", "b"], "i64"),
+        };
+        assert_eq!(changed.change_kind(), ChangeKind::Modified);
+    }
+
+    #[test]
+    fn diff_with_context() {
+        let a = item_with_path("a");
+        let b = item_with_path("b");
+        let c = item_with_path("c");
+        let changed = ChangedPublicItem {
+            old: fn_with_param_type(&["d"], "i32"),
+            new: fn_with_param_type(&["d"], "i64"),
+        };
+        let diff = PublicApiDiff {
+            removed: vec![c.clone()],
+            changed: vec![changed.clone()],
+            added: vec![a.clone()],
+        };
+
+        let annotated = diff.with_context(vec![b.clone()]);
+        assert_eq!(
+            annotated.items,
+            [
+                AnnotatedItem::Added(a),
+                AnnotatedItem::Unchanged(b),
+                AnnotatedItem::Removed(c),
+                AnnotatedItem::Changed(changed),
+            ]
+        );
+        assert_eq!(
+            annotated.to_string(),
+            "+a\n b\n-c\n-pub fn d(x: i32)\n+pub fn d(x: i64)\n"
+        );
+    }
+
+    #[test]
+    fn dyn_impl_change_is_detected() {
+        let taking_and_returning = |param: Vec<Token>, return_type: Vec<Token>| {
+            let mut tokens = vec![q("pub"), w(), Token::FnKeyword, w()];
+            tokens.extend([i("krate"), Token::path_separator(), Token::function("f")]);
+            tokens.push(s("("));
+            tokens.extend(param);
+            tokens.extend([s(")"), w(), Token::operator("->"), w()]);
+            tokens.extend(return_type);
+            test_item(vec!["krate".to_owned(), "f".to_owned()], tokens)
+        };
+        let returning = |return_type: Vec<Token>| taking_and_returning(vec![], return_type);
+        let impl_trait = returning(vec![Token::ImplKeyword, w(), t("Trait")]);
+        let dyn_trait = returning(vec![
+            t("Box"),
+            s("<"),
+            Token::DynKeyword,
+            w(),
+            t("Trait"),
+            s(">"),
+        ]);
+        let other_impl_trait = returning(vec![Token::ImplKeyword, w(), t("Other")]);
+
+        let changed = ChangedPublicItem {
+            old: impl_trait.clone(),
+            new: dyn_trait.clone(),
+        };
+        assert_eq!(changed.change_kind(), ChangeKind::DynImplChanged);
+        assert!(!changed.is_semver_compatible());
+
+        let changed = ChangedPublicItem {
+            old: dyn_trait,
+            new: impl_trait.clone(),
+        };
+        assert_eq!(changed.change_kind(), ChangeKind::DynImplChanged);
+
+        let changed = ChangedPublicItem {
+            old: impl_trait,
+            new: other_impl_trait,
+        };
+        assert_eq!(changed.change_kind(), ChangeKind::Modified);
+
+        // The `->` of an `impl Fn() -> ..` param is not the return type
+        let callback_returning = |callback_return_type: Vec<Token>| {
+            let mut param = vec![i("f"), s(":"), w(), Token::ImplKeyword, w(), t("Fn")];
+            param.extend([s("("), s(")"), w(), Token::operator("->"), w()]);
+            param.extend(callback_return_type);
+            taking_and_returning(param, vec![t("u8")])
+        };
+        let changed = ChangedPublicItem {
+            old: callback_returning(vec![Token::ImplKeyword, w(), t("Trait")]),
+            new: callback_returning(vec![
+                t("Box"),
+                s("<"),
+                Token::DynKeyword,
+                w(),
+                t("Trait"),
+                s(">"),
+            ]),
+        };
+        assert_eq!(changed.change_kind(), ChangeKind::Modified);
+    }
+
+    #[test]
+    fn cfg_change_is_detected() {
+        let gated = |conditions: &[&str]| PublicItem {
+            cfg_conditions: conditions.iter().map(|c| (*c).to_owned()).collect(),
+            ..item_with_path("krate::f")
+        };
+        let moved = ChangedPublicItem {
+            old: gated(&["feature = \"a\""]),
+            new: gated(&["feature = \"b\""]),
+        };
+        assert_eq!(moved.change_kind(), ChangeKind::CfgChanged);
+        assert!(!moved.is_doc_only_change());
+        assert_eq!(moved.severity(), ChangeSeverity::Breaking);
+        assert_eq!(
+            moved.diff_lines(),
+            (
+                "#[cfg(feature = \"a\")] krate::f".to_owned(),
+                "#[cfg(feature = \"b\")] krate::f".to_owned()
+            )
+        );
+
+        let ungated = ChangedPublicItem {
+            old: gated(&["feature = \"a\"", "unix"]),
+            new: gated(&[]),
+        };
+        assert_eq!(ungated.change_kind(), ChangeKind::CfgChanged);
+        assert_eq!(ungated.severity(), ChangeSeverity::Compatible);
+        assert_eq!(
+            ungated.diff_lines(),
+            (
+                "#[cfg(all(feature = \"a\", unix))] krate::f".to_owned(),
+                "krate::f".to_owned()
+            )
+        );
+
+        let modified = ChangedPublicItem {
+            old: gated(&["feature = \"a\""]),
+            new: gated(&["feature = \"a\""]),
+        };
+        assert_eq!(
+            modified.diff_lines(),
+            ("krate::f".to_owned(), "krate::f".to_owned())
+        );
+    }
+
+    #[test]
+    fn bound_added_is_detected() {
+        let unbounded = fn_with_bounds(vec![]);
+        let clone = fn_with_bounds(vec![Token::BoundSeparator, w(), t("Clone")]);
+        let clone_and_debug = fn_with_bounds(vec![
+            Token::BoundSeparator,
+            w(),
+            t("Clone"),
+            w(),
+            Token::BoundCombiner,
+            w(),
+            t("Debug"),
+        ]);
+        let clone_and_maybe_sized = fn_with_bounds(vec![
+            Token::BoundSeparator,
+            w(),
+            t("Clone"),
+            w(),
+            Token::BoundCombiner,
+            w(),
+            Token::OptionalBound,
+            t("Sized"),
+        ]);
+
+        let change_kind = |old: &PublicItem, new: &PublicItem| {
+            ChangedPublicItem {
+                old: old.clone(),
+                new: new.clone(),
+            }
+            .change_kind()
+        };
+        assert_eq!(change_kind(&unbounded, &clone), ChangeKind::BoundAdded);
+        assert_eq!(
+            change_kind(&clone, &clone_and_debug),
+            ChangeKind::BoundAdded
+        );
+        assert_eq!(change_kind(&clone, &unbounded), ChangeKind::Modified);
+        assert_eq!(
+            change_kind(&clone, &clone_and_maybe_sized),
+            ChangeKind::Modified
+        );
+    }
+
+    #[test]
+    fn severity_of_each_level() {
+        let severity = |old: &PublicItem, new: &PublicItem| {
+            ChangedPublicItem {
+                old: old.clone(),
+                new: new.clone(),
+            }
+            .severity()
+        };
+
+        // Cosmetic
+        let f_u8 = fn_with_param_type(&["a", "f"], "u8");
+        let mut tokens = vec![Token::Doc("/// Old".to_owned())];
+        tokens.extend(f_u8.tokens().cloned());
+        let documented = test_item(vec!["a".to_owned(), "f".to_owned()], tokens);
+        assert_eq!(severity(&f_u8, &documented), ChangeSeverity::Cosmetic);
+        let mut tokens = f_u8.tokens().cloned().collect::<Vec<_>>();
+        tokens.retain(|token| *token != Token::Whitespace);
+        let compact = test_item(vec!["a".to_owned(), "f".to_owned()], tokens);
+        assert_eq!(severity(&f_u8, &compact), ChangeSeverity::Cosmetic);
+
+        // Deprecation
+        let mut tokens = vec![Token::Annotation("#[deprecated]".to_owned()), w()];
+        tokens.extend(f_u8.tokens().cloned());
+        let deprecated = test_item(vec!["a".to_owned(), "f".to_owned()], tokens);
+        assert_eq!(severity(&f_u8, &deprecated), ChangeSeverity::Deprecation);
+        assert_eq!(severity(&deprecated, &f_u8), ChangeSeverity::Compatible);
+
+        // Compatible
+        let unbounded = fn_with_bounds(vec![]);
+        let clone = fn_with_bounds(vec![Token::BoundSeparator, w(), t("Clone")]);
+        let maybe_sized = fn_with_bounds(vec![
+            Token::BoundSeparator,
+            w(),
+            Token::OptionalBound,
+            t("Sized"),
+        ]);
+        assert_eq!(severity(&clone, &unbounded), ChangeSeverity::Compatible);
+        assert_eq!(
+            severity(&unbounded, &maybe_sized),
+            ChangeSeverity::Compatible
+        );
+
+        // Breaking
+        assert_eq!(severity(&unbounded, &clone), ChangeSeverity::Breaking);
+        assert_eq!(severity(&maybe_sized, &unbounded), ChangeSeverity::Breaking);
+        let f_u16 = fn_with_param_type(&["a", "f"], "u16");
+        assert_eq!(severity(&f_u8, &f_u16), ChangeSeverity::Breaking);
+        let no_params = test_item(
+            vec!["a".to_owned(), "f".to_owned()],
+            vec![
+                q("pub"),
+                w(),
+                k("fn"),
+                w(),
+                i("a"),
+                Token::path_separator(),
+                i("f"),
+                s("()"),
+            ],
+        );
+        assert_eq!(severity(&f_u8, &no_params), ChangeSeverity::Breaking);
+
+        assert!(ChangeSeverity::Cosmetic < ChangeSeverity::Compatible);
+        assert!(ChangeSeverity::Deprecation < ChangeSeverity::Breaking);
+    }
+
+    #[test]
+    fn removing_bound_of_trait_method_is_breaking() {
+        // `pub fn krate::Tr::m<T: Clone>(t: T)` in `pub trait krate::Tr`
+        let method = |bounds: Vec<Token>| {
+            let mut tokens = vec![q("pub"), w(), Token::FnKeyword, w()];
+            tokens.extend([i("krate"), Token::path_separator(), t("Tr")]);
+            tokens.extend([Token::path_separator(), Token::function("m")]);
+            tokens.extend([Token::GenericOpen, Token::generic("T")]);
+            tokens.extend(bounds);
+            tokens.extend([Token::GenericClose, s("("), i("t"), s(":"), w()]);
+            tokens.extend([Token::generic("T"), s(")")]);
+            PublicItem {
+                sortable_path: vec![
+                    "004-krate".to_owned(),
+                    "014-Tr".to_owned(),
+                    "012-m".to_owned(),
+                ],
+                associated_item_of: Some(vec!["krate".to_owned(), "Tr".to_owned()]),
+                enclosing_type: Some(vec!["krate".to_owned(), "Tr".to_owned()]),
+                ..test_item(["krate", "Tr", "m"], tokens)
+            }
+        };
+        let removes_bound = ChangedPublicItem {
+            old: method(vec![Token::BoundSeparator, w(), t("Clone")]),
+            new: method(vec![]),
+        };
+
+        assert_eq!(removes_bound.severity(), ChangeSeverity::Breaking);
+        assert!(!removes_bound.is_semver_compatible());
+
+        // The same change to a method of an inherent impl is compatible
+        let in_impl = |item: PublicItem| PublicItem {
+            sortable_path: vec![
+                "004-krate".to_owned(),
+                "006-Tr".to_owned(),
+                "020-impl".to_owned(),
+                "012-m".to_owned(),
+            ],
+            ..item
+        };
+        let removes_bound = ChangedPublicItem {
+            old: in_impl(removes_bound.old),
+            new: in_impl(removes_bound.new),
+        };
+        assert_eq!(removes_bound.severity(), ChangeSeverity::Compatible);
+        assert!(removes_bound.is_semver_compatible());
+    }
+
+    #[test]
+    fn is_semver_compatible_agrees_with_severity() {
+        let f_u8 = fn_with_param_type(&["a", "f"], "u8");
+        let mut tokens = vec![Token::Annotation("#[deprecated]".to_owned()), w()];
+        tokens.extend(f_u8.tokens().cloned());
+        let deprecated = test_item(vec!["a".to_owned(), "f".to_owned()], tokens);
+        let unbounded = fn_with_bounds(vec![]);
+        let clone = fn_with_bounds(vec![Token::BoundSeparator, w(), t("Clone")]);
+
+        for (old, new) in [
+            (&f_u8, &deprecated),
+            (&deprecated, &f_u8),
+            (&f_u8, &with_fn_qualifier(&f_u8, "const")),
+            (&clone, &unbounded),
+            (&unbounded, &clone),
+            (&f_u8, &fn_with_param_type(&["a", "f"], "u16")),
+        ] {
+            let changed = ChangedPublicItem {
+                old: old.clone(),
+                new: new.clone(),
+            };
+            assert_eq!(
+                changed.is_semver_compatible(),
+                changed.severity() != ChangeSeverity::Breaking,
+                "{changed:?}"
+            );
+        }
+
+        let relaxes_bound = ChangedPublicItem {
+            old: clone,
+            new: unbounded,
+        };
+        let diff = PublicApiDiff {
+            changed: vec![relaxes_bound],
+            ..PublicApiDiff::default()
+        };
+        assert_eq!(crate::Compatibility::of(&diff), crate::Compatibility::Minor);
+    }
+
+    #[test]
+    fn min_severity_filters_changes() {
+        let f_u8 = fn_with_param_type(&["a", "f"], "u8");
+        let mut tokens = vec![Token::Doc("/// Docs".to_owned())];
+        tokens.extend(f_u8.tokens().cloned());
+        let g_documented = test_item(vec!["a".to_owned(), "g".to_owned()], tokens);
+        let g = test_item(
+            vec!["a".to_owned(), "g".to_owned()],
+            f_u8.tokens().cloned().collect(),
+        );
+        let diff = PublicApiDiff {
+            removed: vec![fn_with_param_type(&["a", "removed"], "u8")],
+            changed: vec![
+                ChangedPublicItem {
+                    old: f_u8.clone(),
+                    new: fn_with_param_type(&["a", "f"], "u16"),
+                },
+                ChangedPublicItem {
+                    old: g,
+                    new: g_documented,
+                },
+            ],
+            added: vec![fn_with_param_type(&["a", "added"], "u8")],
+        };
+
+        let cosmetic = diff.clone().with_min_severity(ChangeSeverity::Cosmetic);
+        assert_eq!(cosmetic, diff);
 
-impl PublicApiDiff {
-    /// Allows you to diff the public API between two arbitrary versions of a
-    /// library, e.g. different releases. The input parameters `old` and `new`
-    /// is the output of two different invocations of
-    /// [`crate::Builder::build`].
-    #[must_use]
-    pub fn between(old: PublicApi, new: PublicApi) -> Self {
-        // We must use a HashBag, because with a HashSet we would lose public
-        // items that happen to have the same representation due to limitations
-        // or bugs
-        let old = old.into_items().collect::<HashBag<_>>();
-        let new = new.into_items().collect::<HashBag<_>>();
+        let compatible = diff.clone().with_min_severity(ChangeSeverity::Compatible);
+        assert_eq!(compatible.changed.len(), 1);
+        assert_eq!(compatible.added, diff.added);
 
-        // First figure out what items have been removed and what have been
-        // added. Later we will match added and removed items with the same path
-        // and construct a list of changed items. A changed item is an item with
-        // the same path that has been both removed and added.
-        let all_removed = old.difference(&new);
-        let all_added = new.difference(&old);
+        let breaking = diff.clone().with_min_severity(ChangeSeverity::Breaking);
+        assert_eq!(breaking.removed, diff.removed);
+        assert_eq!(breaking.changed, diff.changed[..1]);
+        assert!(breaking.added.is_empty());
+    }
 
-        // Convert the data to make it easier to work with
-        let mut removed_paths: ItemsWithPath = bag_to_path_map(all_removed);
-        let mut added_paths: ItemsWithPath = bag_to_path_map(all_added);
+    #[test]
+    fn array_changes_are_detected() {
+        let with_param = |param: Vec<Token>| {
+            let mut tokens = vec![q("pub"), w(), Token::FnKeyword, w()];
+            tokens.extend([
+                i("krate"),
+                Token::path_separator(),
+                Token::function("f"),
+                s("("),
+            ]);
+            tokens.extend([i("key"), s(":"), w()]);
+            tokens.extend(param);
+            tokens.push(s(")"));
+            test_item(vec!["krate".to_owned(), "f".to_owned()], tokens)
+        };
+        let array = |element: Vec<Token>, size: &str| {
+            let mut tokens = vec![Token::ArrayOpen];
+            tokens.extend(element);
+            tokens.extend([Token::ArraySemicolon, w(), Token::primitive(size)]);
+            tokens.push(Token::ArrayClose);
+            tokens
+        };
+        let u8_16 = with_param(array(vec![Token::primitive("u8")], "16"));
+        let u8_32 = with_param(array(vec![Token::primitive("u8")], "32"));
+        let u16_32 = with_param(array(vec![Token::primitive("u16")], "32"));
+        let nested_4 = with_param(array(array(vec![Token::primitive("u8")], "4"), "2"));
+        let nested_8 = with_param(array(array(vec![Token::primitive("u8")], "8"), "2"));
+        let slice = with_param(vec![s("["), Token::primitive("u8"), s("]")]);
 
-        // The result we return from this function will be put in these vectors
-        let mut removed: Vec<PublicItem> = vec![];
-        let mut changed: Vec<ChangedPublicItem> = vec![];
-        let mut added: Vec<PublicItem> = vec![];
+        let change_kind = |old: &PublicItem, new: &PublicItem| {
+            ChangedPublicItem {
+                old: old.clone(),
+                new: new.clone(),
+            }
+            .change_kind()
+        };
+        assert_eq!(
+            change_kind(&u8_16, &u8_32),
+            ChangeKind::ArraySizeChanged {
+                old_size: "16".to_owned(),
+                new_size: "32".to_owned(),
+            }
+        );
+        assert_eq!(
+            change_kind(&u8_32, &u16_32),
+            ChangeKind::ArrayElementTypeChanged
+        );
+        assert_eq!(
+            change_kind(&nested_4, &nested_8),
+            ChangeKind::ArraySizeChanged {
+                old_size: "4".to_owned(),
+                new_size: "8".to_owned(),
+            }
+        );
+        assert_eq!(change_kind(&u8_16, &u16_32), ChangeKind::Modified);
+        assert_eq!(change_kind(&u8_32, &nested_8), ChangeKind::Modified);
+        assert_eq!(change_kind(&u8_32, &slice), ChangeKind::Modified);
+    }
 
-        // Figure out all paths of items that are either removed or added. Later
-        // we will match paths that have been both removed and added (i.e.
-        // changed)
-        let mut touched_paths: Vec<PublicItemPath> = vec![];
-        touched_paths.extend::<Vec<_>>(removed_paths.keys().cloned().collect());
-        touched_paths.extend::<Vec<_>>(added_paths.keys().cloned().collect());
+    #[test]
+    fn between_sets_matches_between() {
+        let old = vec![
+            item_with_path("foo"),
+            item_with_path("bar"),
+            item_with_path("bar"),
+        ];
+        let new = vec![item_with_path("baz"), item_with_path("bar")];
 
-        // OK, we are ready to do some actual heavy lifting. Go through all
-        // paths and look for changed items. The remaining items are either
-        // purely removed or purely added.
-        for path in touched_paths {
-            let mut removed_items = removed_paths.remove(&path).unwrap_or_default();
-            let mut added_items = added_paths.remove(&path).unwrap_or_default();
-            loop {
-                match (removed_items.pop(), added_items.pop()) {
-                    (Some(old), Some(new)) => changed.push(ChangedPublicItem { old, new }),
-                    (Some(old), None) => removed.push(old),
-                    (None, Some(new)) => added.push(new),
-                    (None, None) => break,
-                }
-            }
-        }
+        let expected = PublicApiDiff::between(api(old.clone()), api(new.clone()));
+        let actual = PublicApiDiff::between_sets(&old.into(), &new.into());
+        assert_eq!(actual, expected);
+        assert_eq!(
+            actual.removed,
+            [item_with_path("bar"), item_with_path("foo")]
+        );
+    }
 
-        // Make output predictable and stable
-        removed.sort_by(PublicItem::grouping_cmp);
-        changed.sort_by(ChangedPublicItem::grouping_cmp);
-        added.sort_by(PublicItem::grouping_cmp);
+    #[test]
+    fn assert_eq_passes_for_equal_diffs() {
+        let diff = PublicApiDiff::between(api([]), api([item_with_path("foo")]));
+        PublicApiDiff::assert_eq(&diff, &diff.clone());
+    }
 
-        Self {
-            removed,
-            changed,
-            added,
-        }
+    #[test]
+    #[should_panic(expected = "Diffs are not equal (< expected / > actual):\n\
+        added:\n\
+        < bar\n\
+        > baz\n")]
+    fn assert_eq_lists_mismatched_items() {
+        let actual = PublicApiDiff::between(
+            api([item_with_path("foo")]),
+            api([item_with_path("foo"), item_with_path("baz")]),
+        );
+        let expected = PublicApiDiff::between(
+            api([item_with_path("foo")]),
+            api([item_with_path("foo"), item_with_path("bar")]),
+        );
+        PublicApiDiff::assert_eq(&actual, &expected);
     }
 
-    /// Check whether the diff is empty
-    #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.removed.is_empty() && self.changed.is_empty() && self.added.is_empty()
+    #[test]
+    fn grouped_by_change_kind() {
+        let returning = |return_type: Token| {
+            let mut tokens = vec![q("pub"), w(), Token::FnKeyword, w()];
+            tokens.extend([i("krate"), Token::path_separator(), Token::function("f")]);
+            tokens.extend([s("("), s(")"), w(), Token::operator("->"), w(), return_type]);
+            tokens.extend([w(), t("Trait")]);
+            test_item(vec!["krate".to_owned(), "f".to_owned()], tokens)
+        };
+        let modified_1 = ChangedPublicItem {
+            old: fn_with_param_type(&["krate", "a"], "u8"),
+            new: fn_with_param_type(&["krate", "a"], "u16"),
+        };
+        let modified_2 = ChangedPublicItem {
+            old: fn_with_param_type(&["krate", "b"], "u8"),
+            new: fn_with_param_type(&["krate", "b"], "u16"),
+        };
+        let renamed = ChangedPublicItem {
+            old: item_with_path("krate::Old"),
+            new: item_with_path("krate::New"),
+        };
+        let dyn_impl = ChangedPublicItem {
+            old: returning(Token::ImplKeyword),
+            new: returning(Token::DynKeyword),
+        };
+        let diff = PublicApiDiff {
+            removed: vec![],
+            changed: vec![
+                modified_1.clone(),
+                renamed.clone(),
+                dyn_impl.clone(),
+                modified_2.clone(),
+            ],
+            added: vec![],
+        };
+
+        let groups = diff.grouped_by_change_kind();
+
+        let rename = ChangeKind::Renamed {
+            old_path: "krate::Old".to_owned(),
+            new_path: "krate::New".to_owned(),
+        };
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[&ChangeKind::Modified], [&modified_1, &modified_2]);
+        assert_eq!(groups[&rename], [&renamed]);
+        assert_eq!(groups[&ChangeKind::DynImplChanged], [&dyn_impl]);
     }
-}
 
-/// Converts a set (read: bag) of public items into a hash map that maps a given
-/// path to a vec of public items with that path.
-fn bag_to_path_map<'a>(difference: impl Iterator<Item = (&'a PublicItem, usize)>) -> ItemsWithPath {
-    let mut map: ItemsWithPath = HashMap::new();
-    for (item, occurrences) in difference {
-        let items = map.entry(item.sortable_path.clone()).or_default();
-        for _ in 0..occurrences {
-            items.push(item.clone());
-        }
+    /// Creates e.g. `pub struct krate::Foo`
+    fn struct_item(path: &[&str]) -> PublicItem {
+        let mut tokens = vec![q("pub"), w(), k("struct"), w()];
+        tokens.extend(itertools::intersperse(
+            path.iter().map(|component| i(component)),
+            Token::path_separator(),
+        ));
+        test_item(path.iter().copied(), tokens)
     }
-    map
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::tokens::Token;
+    #[test]
+    fn similarity_score() {
+        let a = fn_with_param_type(&["a", "b"], "i32");
+        let b = fn_with_param_type(&["a", "b"], "i64");
+        let c = item_with_path("c");
 
-    use super::*;
+        assert!((a.similarity_score(&a) - 1.0).abs() < f64::EPSILON);
+        assert!(a.similarity_score(&c).abs() < f64::EPSILON);
+        assert!(a.similarity_score(&b) > a.similarity_score(&c));
+        assert!((a.similarity_score(&b) - b.similarity_score(&a)).abs() < f64::EPSILON);
+    }
 
     #[test]
-    fn single_and_only_item_removed() {
+    fn no_diff_means_empty_diff() {
         let old = api([item_with_path("foo")]);
-        let new = api([]);
+        let new = api([item_with_path("foo")]);
 
         let actual = PublicApiDiff::between(old, new);
         let expected = PublicApiDiff {
-            removed: vec![item_with_path("foo")],
+            removed: vec![],
             changed: vec![],
             added: vec![],
         };
         assert_eq!(actual, expected);
-        assert!(!actual.is_empty());
+        assert!(actual.is_empty());
     }
 
     #[test]
-    fn single_and_only_item_added() {
-        let old = api([]);
-        let new = api([item_with_path("foo")]);
+    fn unsafe_impl_to_impl_is_flagged() {
+        let changed = ChangedPublicItem {
+            old: impl_item(&["unsafe", "impl"]),
+            new: impl_item(&["impl"]),
+        };
+        assert!(changed.old.is_unsafe_impl());
+        assert!(!changed.new.is_unsafe_impl());
+        assert!(changed.changes_unsafe_impl());
 
-        let actual = PublicApiDiff::between(old, new);
-        let expected = PublicApiDiff {
-            removed: vec![],
-            changed: vec![],
-            added: vec![item_with_path("foo")],
+        let unchanged = ChangedPublicItem {
+            old: impl_item(&["impl"]),
+            new: impl_item(&["impl"]),
         };
-        assert_eq!(actual, expected);
-        assert!(!actual.is_empty());
+        assert!(!unchanged.changes_unsafe_impl());
     }
 
     #[test]
-    fn middle_item_added() {
-        let old = api([item_with_path("1"), item_with_path("3")]);
-        let new = api([
-            item_with_path("1"),
-            item_with_path("2"),
-            item_with_path("3"),
-        ]);
+    fn semver_compatible_diff_omits_added_items() {
+        let old = api([item_with_path("1"), item_with_path("2")]);
+        let new = api([item_with_path("2"), item_with_path("3")]);
 
-        let actual = PublicApiDiff::between(old, new);
+        let actual = PublicApiDiff::between_semver_compatible(old, new);
         let expected = PublicApiDiff {
-            removed: vec![],
+            removed: vec![item_with_path("1")],
             changed: vec![],
-            added: vec![item_with_path("2")],
+            added: vec![],
         };
         assert_eq!(actual, expected);
-        assert!(!actual.is_empty());
     }
 
     #[test]
-    fn middle_item_removed() {
-        let old = api([
-            item_with_path("1"),
-            item_with_path("2"),
-            item_with_path("3"),
-        ]);
-        let new = api([item_with_path("1"), item_with_path("3")]);
+    fn semver_compatible_diff_keeps_changed_param_type() {
+        let old = api([fn_with_param_type(&["a", "b"], "i32")]);
+        let new = api([fn_with_param_type(&["a", "b"], "i64")]);
 
-        let actual = PublicApiDiff::between(old, new);
-        let expected = PublicApiDiff {
-            removed: vec![item_with_path("2")],
-            changed: vec![],
-            added: vec![],
+        let actual = PublicApiDiff::between_semver_compatible(old, new);
+        assert_eq!(actual.changed.len(), 1);
+    }
+
+    #[test]
+    fn semver_compatible_changes() {
+        let plain_fn = fn_with_param_type(&["a", "b"], "i32");
+        let plain_struct = test_item(
+            vec!["a".to_owned()],
+            vec![q("pub"), w(), k("struct"), w(), t("a")],
+        );
+        for (old, new) in [
+            (non_exhaustive(&plain_struct), plain_struct.clone()),
+            (plain_fn.clone(), with_fn_qualifier(&plain_fn, "const")),
+            (with_fn_qualifier(&plain_fn, "unsafe"), plain_fn.clone()),
+        ] {
+            let changed = ChangedPublicItem { old, new };
+            assert!(changed.is_semver_compatible(), "{changed:?}");
+
+            let reverted = ChangedPublicItem {
+                old: changed.new,
+                new: changed.old,
+            };
+            assert!(!reverted.is_semver_compatible(), "{reverted:?}");
+        }
+    }
+
+    #[test]
+    fn self_type_is_same_as_explicit_type() {
+        let generic_foo = || {
+            vec![
+                i("krate"),
+                Token::path_separator(),
+                t("Foo"),
+                Token::GenericOpen,
+                Token::generic("T"),
+                Token::GenericClose,
+            ]
         };
-        assert_eq!(actual, expected);
-        assert!(!actual.is_empty());
+        let returning = |parent: &str, return_type: Vec<Token>| {
+            let path = vec!["krate".to_owned(), "Foo".to_owned(), "new".to_owned()];
+            let mut tokens = vec![q("pub"), w(), Token::FnKeyword, w()];
+            tokens.extend(generic_foo());
+            tokens.extend([Token::path_separator(), Token::function("new")]);
+            tokens.extend([s("()"), w(), Token::operator("->"), w()]);
+            tokens.extend(return_type);
+            PublicItem {
+                sortable_path: vec![
+                    "004-krate".to_owned(),
+                    parent.to_owned(),
+                    "017-new".to_owned(),
+                ],
+                associated_item_of: Some(path[..2].to_vec()),
+                ..test_item(path, tokens)
+            }
+        };
+        let self_type = returning("010-Foo", vec![Token::SelfType]);
+        let explicit_type = returning("010-Foo", generic_foo());
+        let without_generics = returning(
+            "010-Foo",
+            vec![i("krate"), Token::path_separator(), t("Foo")],
+        );
+        let other_type = returning(
+            "010-Foo",
+            vec![i("krate"), Token::path_separator(), t("Bar")],
+        );
+
+        let changed = ChangedPublicItem {
+            old: self_type.clone(),
+            new: explicit_type.clone(),
+        };
+        assert!(changed.is_semver_compatible());
+        let reverted = ChangedPublicItem {
+            old: explicit_type.clone(),
+            new: self_type.clone(),
+        };
+        assert!(reverted.is_semver_compatible());
+        for new in [without_generics, other_type] {
+            let changed = ChangedPublicItem {
+                old: self_type.clone(),
+                new,
+            };
+            assert!(!changed.is_semver_compatible());
+        }
+
+        // In a trait, `Self` is not the trait
+        let changed = ChangedPublicItem {
+            old: returning("014-Foo", vec![Token::SelfType]),
+            new: returning("014-Foo", generic_foo()),
+        };
+        assert!(!changed.is_semver_compatible());
     }
 
     #[test]
-    fn many_identical_items() {
-        let old = api([
-            item_with_path("1"),
-            item_with_path("2"),
-            item_with_path("2"),
-            item_with_path("3"),
-            item_with_path("3"),
-            item_with_path("3"),
-            fn_with_param_type(&["a", "b"], "i32"),
-            fn_with_param_type(&["a", "b"], "i32"),
-        ]);
-        let new = api([
-            item_with_path("1"),
-            item_with_path("2"),
-            item_with_path("3"),
-            item_with_path("4"),
-            item_with_path("4"),
-            fn_with_param_type(&["a", "b"], "i64"),
-            fn_with_param_type(&["a", "b"], "i64"),
-        ]);
+    fn const_generic_is_not_const_fn() {
+        let plain_fn = fn_with_param_type(&["a", "b"], "i32");
+        let mut tokens = plain_fn.tokens.to_vec();
+        tokens.extend([q("const"), w(), i("N")]);
+        let const_generic_fn = test_item(plain_fn.sortable_path.clone(), tokens);
 
-        let actual = PublicApiDiff::between(old, new);
-        let expected = PublicApiDiff {
-            removed: vec![
-                item_with_path("2"),
-                item_with_path("3"),
-                item_with_path("3"),
-            ],
-            changed: vec![
-                ChangedPublicItem {
-                    old: fn_with_param_type(&["a", "b"], "i32"),
-                    new: fn_with_param_type(&["a", "b"], "i64"),
-                },
-                ChangedPublicItem {
-                    old: fn_with_param_type(&["a", "b"], "i32"),
-                    new: fn_with_param_type(&["a", "b"], "i64"),
-                },
+        let changed = ChangedPublicItem {
+            old: const_generic_fn,
+            new: plain_fn,
+        };
+        assert!(!changed.is_semver_compatible());
+    }
+
+    #[test]
+    fn doc_only_changes_can_be_ignored() {
+        let old = api([with_docs(&item_with_path("a"), "Old docs")]);
+        let new = api([with_docs(&item_with_path("a"), "New docs")]);
+
+        let diff = PublicApiDiff::between_with_options(old, new, DiffOptions::default());
+        assert_eq!(diff.changed.len(), 1);
+        assert!(diff.changed[0].is_doc_only_change());
+
+        let old = api([with_docs(&item_with_path("a"), "Old docs")]);
+        let new = api([with_docs(&item_with_path("a"), "New docs")]);
+        let options = DiffOptions::default().ignore_doc_changes(true);
+        let diff = PublicApiDiff::between_with_options(old, new, options);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn score() {
+        let empty = PublicApiDiff::between(api([]), api([]));
+        assert!((empty.score(0) - 1.0).abs() < f64::EPSILON);
+
+        let unchanged =
+            PublicApiDiff::between(api([item_with_path("a")]), api([item_with_path("a")]));
+        assert!((unchanged.score(1) - 1.0).abs() < f64::EPSILON);
+
+        let all_removed =
+            PublicApiDiff::between(api([item_with_path("a"), item_with_path("b")]), api([]));
+        assert!(all_removed.score(2).abs() < f64::EPSILON);
+
+        let one_of_four_added = PublicApiDiff::between(
+            api([
+                item_with_path("a"),
+                item_with_path("b"),
+                item_with_path("c"),
+            ]),
+            api([
+                item_with_path("a"),
+                item_with_path("b"),
+                item_with_path("c"),
+                item_with_path("d"),
+            ]),
+        );
+        assert!((one_of_four_added.score(3) - 0.75).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn whitespace_only_changes_can_be_ignored() {
+        let old_item = test_item(vec!["a".to_owned()], vec![q("pub"), w(), i("a")]);
+        let new_item = test_item(vec!["a".to_owned()], vec![q("pub"), w(), w(), i("a")]);
+
+        let diff = PublicApiDiff::between(api([old_item.clone()]), api([new_item.clone()]));
+        assert_eq!(diff.changed.len(), 1);
+        assert!(diff.changed[0].is_whitespace_only_change());
+
+        let options = DiffOptions::default().ignore_whitespace(true);
+        let diff = PublicApiDiff::between_with_options(api([old_item]), api([new_item]), options);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn similarity_matching_can_be_disabled() {
+        // Docs sort last, so `old_fn` is sorted after `old_struct`, but
+        // `new_fn` is sorted before `new_struct`
+        let old_fn = with_docs(&fn_with_param_type(&["a", "b"], "i32"), "Docs");
+        let new_fn = fn_with_param_type(&["a", "b"], "i64");
+        let old_struct = test_item(
+            vec!["a".to_owned(), "b".to_owned()],
+            vec![
+                q("pub"),
+                w(),
+                k("struct"),
+                w(),
+                i("a"),
+                Token::path_separator(),
+                t("b"),
             ],
-            added: vec![item_with_path("4"), item_with_path("4")],
+        );
+        let new_struct = non_exhaustive(&old_struct);
+        let pairs = |options| {
+            let old = api([old_fn.clone(), old_struct.clone()]);
+            let new = api([new_fn.clone(), new_struct.clone()]);
+            let diff = PublicApiDiff::between_with_options(old, new, options);
+            diff.changed
+                .into_iter()
+                .map(|changed| (changed.old, changed.new))
+                .collect::<Vec<_>>()
         };
-        assert_eq!(actual, expected);
-        assert!(!actual.is_empty());
+
+        assert_eq!(
+            pairs(DiffOptions::default()),
+            [
+                (old_fn.clone(), new_fn.clone()),
+                (old_struct.clone(), new_struct.clone())
+            ]
+        );
+        assert_eq!(
+            pairs(DiffOptions::default().similarity_matching(false)),
+            [(old_fn, new_struct), (old_struct, new_fn)]
+        );
     }
 
-    /// Regression test for
-    /// <https://github.com/cargo-public-api/cargo-public-api/issues/50>
     #[test]
-    fn no_off_by_one_diff_skewing() {
-        let old = api([
-            fn_with_param_type(&["a", "b"], "i8"),
-            fn_with_param_type(&["a", "b"], "i32"),
-            fn_with_param_type(&["a", "b"], "i64"),
-        ]);
-        // Same as `old` but with a new item with the same path added on top.
-        // The diffing algorithm needs to figure out that only one item has been
-        // added, rather than showing that of three items changed.
-        let new = api([
-            fn_with_param_type(&["a", "b"], "u8"), // The only new item!
-            fn_with_param_type(&["a", "b"], "i8"),
-            fn_with_param_type(&["a", "b"], "i32"),
-            fn_with_param_type(&["a", "b"], "i64"),
-        ]);
-        let expected = PublicApiDiff {
-            removed: vec![],
-            changed: vec![],
-            added: vec![fn_with_param_type(&["a", "b"], "u8")],
+    fn without_similarity_matching_items_pair_up_in_sorted_order() {
+        let f = |type_| fn_with_param_type(&["a", "b"], type_);
+        let old = api([f("u16"), f("u32"), f("u8")]);
+        let new = api([f("i16"), f("i32")]);
+
+        let options = DiffOptions::default().similarity_matching(false);
+        let diff = PublicApiDiff::between_with_options(old, new, options);
+        assert_eq!(
+            diff.changed
+                .into_iter()
+                .map(|changed| (changed.old, changed.new))
+                .collect::<Vec<_>>(),
+            [(f("u16"), f("i16")), (f("u32"), f("i32"))]
+        );
+        assert_eq!(diff.removed, [f("u8")]);
+    }
+
+    #[test]
+    fn signature_change_is_not_doc_only_change() {
+        let changed = ChangedPublicItem {
+            old: with_docs(&fn_with_param_type(&["a"], "i32"), "Docs"),
+            new: with_docs(&fn_with_param_type(&["a"], "i64"), "Docs"),
         };
-        let actual = PublicApiDiff::between(old, new);
-        assert_eq!(actual, expected);
-        assert!(!actual.is_empty());
+        assert!(!changed.is_doc_only_change());
     }
 
     #[test]
-    fn no_diff_means_empty_diff() {
-        let old = api([item_with_path("foo")]);
-        let new = api([item_with_path("foo")]);
+    fn diff_builder_matches_between() {
+        let old = api([item_with_path("a"), fn_with_param_type(&["b"], "i32")]);
+        let new = api([fn_with_param_type(&["b"], "i64"), item_with_path("c")]);
 
-        let actual = PublicApiDiff::between(old, new);
-        let expected = PublicApiDiff {
-            removed: vec![],
-            changed: vec![],
-            added: vec![],
+        let expected = DiffBuilder::new()
+            .added(item_with_path("c"))
+            .changed(
+                fn_with_param_type(&["b"], "i32"),
+                fn_with_param_type(&["b"], "i64"),
+            )
+            .removed(item_with_path("a"))
+            .build()
+            .unwrap();
+        assert_eq!(PublicApiDiff::between(old, new), expected);
+    }
+
+    #[test]
+    fn diff_builder_rejects_item_in_many_categories() {
+        let result = DiffBuilder::new()
+            .removed(item_with_path("a"))
+            .added(item_with_path("a"))
+            .build();
+        assert!(matches!(
+            result,
+            Err(crate::Error::ItemInManyDiffCategories(item)) if item == "a"
+        ));
+    }
+
+    #[test]
+    fn apply_and_apply_inverse_round_trip() {
+        let pool = [
+            item_with_path("a"),
+            item_with_path("b"),
+            item_with_path("b"),
+            fn_with_param_type(&["c"], "i32"),
+            fn_with_param_type(&["c"], "i64"),
+            fn_with_param_type(&["d"], "u8"),
+        ];
+        let subset = |mask: usize| -> Vec<PublicItem> {
+            let mut items: Vec<_> = (pool.iter().enumerate())
+                .filter(|(index, _)| mask & (1 << index) != 0)
+                .map(|(_, item)| item.clone())
+                .collect();
+            items.sort_by(PublicItem::grouping_cmp);
+            items
         };
-        assert_eq!(actual, expected);
-        assert!(actual.is_empty());
+
+        // Every combination of old and new items
+        for old_mask in 0..1 << pool.len() {
+            for new_mask in 0..1 << pool.len() {
+                let (old, new) = (subset(old_mask), subset(new_mask));
+                let diff = PublicApiDiff::between(api(old.clone()), api(new.clone()));
+
+                let applied = diff.apply(old.clone()).unwrap();
+                assert_eq!(applied, new);
+                assert_eq!(diff.apply_inverse(applied).unwrap(), old);
+            }
+        }
+    }
+
+    #[test]
+    fn apply_to_wrong_items_fails() {
+        let diff = PublicApiDiff::between(api([item_with_path("a")]), api([]));
+        assert!(matches!(
+            diff.apply(vec![item_with_path("b")]),
+            Err(crate::Error::ItemMissingWhenApplyingDiff(item)) if item == "a"
+        ));
+        assert_eq!(
+            diff.apply_inverse(vec![]).unwrap(),
+            vec![item_with_path("a")]
+        );
+    }
+
+    fn with_docs(item: &PublicItem, docs: &str) -> PublicItem {
+        let mut tokens = vec![Token::doc(docs)];
+        tokens.extend(item.tokens.iter().cloned());
+        test_item(item.sortable_path.clone(), tokens)
+    }
+
+    fn non_exhaustive(item: &PublicItem) -> PublicItem {
+        let mut tokens = vec![Token::Annotation("#[non_exhaustive]".to_owned()), w()];
+        tokens.extend(item.tokens.iter().cloned());
+        test_item(item.sortable_path.clone(), tokens)
     }
 
     fn item_with_path(path_str: &str) -> PublicItem {
-        new_public_item(
-            path_str
-                .split("::")
-                .map(std::string::ToString::to_string)
-                .collect(),
+        test_item(
+            path_str.split("::"),
             vec![crate::tokens::Token::identifier(path_str)],
         )
     }
@@ -331,21 +2965,39 @@ mod tests {
         // Add path e.g. "a::b"
         tokens.extend(itertools::intersperse(
             path.iter().cloned().map(Token::identifier),
-            Token::symbol("::"),
+            Token::path_separator(),
         ));
 
         // Append function "(x: usize)"
         tokens.extend(vec![q("("), i("x"), s(":"), w(), t(type_), q(")")]);
 
         // End result is e.g. "pub fn a::b(x: usize)"
-        new_public_item(path, tokens)
+        test_item(path, tokens)
+    }
+
+    /// Creates e.g. `unsafe impl Send for Foo` if `keywords` is `["unsafe",
+    /// "impl"]`.
+    /// `pub fn krate::f<T>()` with `bounds` after the `T`, e.g. `: Clone`.
+    fn fn_with_bounds(bounds: Vec<Token>) -> PublicItem {
+        let mut tokens = vec![q("pub"), w(), Token::FnKeyword, w()];
+        tokens.extend([i("krate"), Token::path_separator(), Token::function("f")]);
+        tokens.extend([Token::GenericOpen, Token::generic("T")]);
+        tokens.extend(bounds);
+        tokens.extend([Token::GenericClose, s("()")]);
+        test_item(vec!["krate".to_owned(), "f".to_owned()], tokens)
     }
 
-    fn new_public_item(path: PublicItemPath, tokens: Vec<Token>) -> PublicItem {
-        PublicItem {
-            sortable_path: path,
-            tokens,
+    fn impl_item(keywords: &[&str]) -> PublicItem {
+        let mut tokens = vec![];
+        for keyword in keywords {
+            let keyword = match *keyword {
+                "impl" => Token::ImplKeyword,
+                keyword => Token::keyword(keyword),
+            };
+            tokens.extend([keyword, w()]);
         }
+        tokens.extend([t("Send"), w(), Token::keyword("for"), w(), t("Foo")]);
+        test_item(vec!["Foo".to_owned()], tokens)
     }
 
     fn s(s: &str) -> Token {
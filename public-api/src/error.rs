@@ -6,14 +6,74 @@ use thiserror::Error;
 pub enum Error {
     /// Occurs if the rustdoc JSON you provide can't be parsed. Typically
     /// because the rustdoc JSON format that your version of nightly outputs is
-    /// too old. Consult the "Compatibility matrix" in the README.
-    #[error(transparent)]
+    /// too old. Consult the "Compatibility matrix" in the README. The
+    /// [`std::error::Error::source`] is the [`serde_json::Error`], which tells
+    /// where and why parsing failed.
+    #[error("Failed to parse JSON: {0}")]
     SerdeJsonError(#[from] serde_json::Error),
 
     /// Some kind of IO error occurred. For example, we might not have read
-    /// permissions on the rustdoc JSON input file.
-    #[error(transparent)]
+    /// permissions on the rustdoc JSON input file. The
+    /// [`std::error::Error::source`] is the [`std::io::Error`].
+    #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+
+    /// Occurs if the `format_version` of the rustdoc JSON you provide does not
+    /// match the format version you asserted with
+    /// [`crate::Builder::json_format_version`].
+    #[error("Expected rustdoc JSON format version {expected} but found {actual}")]
+    FormatVersionMismatch {
+        /// The format version that was asserted.
+        expected: u32,
+        /// The format version of the rustdoc JSON.
+        actual: u32,
+    },
+
+    /// Occurs if [`crate::diff::DiffBuilder::build`] is given the same item in
+    /// more than one of the removed, changed and added categories.
+    #[error("The item `{0}` is in more than one category of the diff")]
+    ItemInManyDiffCategories(String),
+
+    /// Occurs if [`crate::diff::PublicApiDiff::apply`] or
+    /// [`crate::diff::PublicApiDiff::apply_inverse`] is given items that lack
+    /// an item that the diff removes or changes. Typically because the diff
+    /// was made from other versions of the public API.
+    #[error("The item `{0}` is not among the items the diff is applied to")]
+    ItemMissingWhenApplyingDiff(String),
+
+    /// Occurs if [`crate::DeltaStore::add_version`] is given a label that
+    /// already belongs to a version in the store.
+    #[error("The store already has a version labeled `{0}`")]
+    DuplicateVersionLabel(String),
+
+    /// Occurs if a [`crate::DeltaStore`] can't be serialized, or if the bytes
+    /// given to [`crate::DeltaStore::from_bytes`] are not a serialized store.
+    #[error("Failed to (de)serialize the store: {0}")]
+    DeltaStoreError(#[from] bincode::Error),
+
+    /// Occurs if [`crate::Builder::expand_macros`] is `false` and the source
+    /// file of an item can't be found or read. The source files are looked for
+    /// relative to the ancestors of the rustdoc JSON file, so the rustdoc JSON
+    /// must be in a subdirectory of the workspace root, as with the default
+    /// target dir.
+    #[error(
+        "Failed to read the source file {0:?}, which is needed to find items generated by macros"
+    )]
+    SourceFileUnreadable(std::path::PathBuf),
+
+    /// Occurs if [`crate::compute_public_api_async`] fails to build rustdoc
+    /// JSON. The [`std::error::Error::source`] is the
+    /// [`rustdoc_json::BuildError`].
+    #[cfg(feature = "async")]
+    #[error("Failed to build rustdoc JSON: {0}")]
+    BuildError(#[from] rustdoc_json::BuildError),
+
+    /// Occurs if [`crate::PublicApiMonitor::watch`] fails to start watching
+    /// the rustdoc JSON file. The [`std::error::Error::source`] is the
+    /// [`notify_debouncer_mini::notify::Error`].
+    #[cfg(feature = "monitor")]
+    #[error("Failed to watch the rustdoc JSON file: {0}")]
+    WatchError(#[from] notify_debouncer_mini::notify::Error),
 }
 
 /// Shorthand for [`std::result::Result<T, public_api::Error>`].
@@ -0,0 +1,207 @@
+use std::collections::{HashMap, VecDeque};
+
+use petgraph::{
+    graph::{DiGraph, NodeIndex},
+    visit::Bfs,
+};
+
+use crate::{tokens::Token, PublicItem};
+
+/// The public API as a directed graph, with a node for each [`PublicItem`]
+/// and an edge for each relationship between two items. Enables graph based
+/// analysis of an API, such as finding the items that are reachable from the
+/// crate root, the items that form cycles, and the diameter of the API.
+///
+/// ```no_run
+/// # let public_api: public_api::PublicApi = todo!();
+/// use public_api::PublicApiGraph;
+///
+/// let graph = PublicApiGraph::from_items(public_api.into_items().collect());
+/// for edge in graph.graph().raw_edges() {
+///     let (from, to) = (&graph.graph()[edge.source()], &graph.graph()[edge.target()]);
+///     println!("{from} --{:?}--> {to}", edge.weight);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PublicApiGraph {
+    graph: DiGraph<PublicItem, EdgeKind>,
+}
+
+/// How the two items of an edge of a [`PublicApiGraph`] are related.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EdgeKind {
+    /// The `to` item is in the `from` item. Either because `from` is the
+    /// module of `to`, or because `to` is a field, variant or associated item
+    /// of `from`.
+    Contains,
+
+    /// The `to` item is an `impl` of the `from` type, such as `impl Clone for
+    /// krate::Struct` of `krate::Struct`.
+    Implements,
+
+    /// The `to` item is mentioned by the `from` item, such as `krate::Struct`
+    /// by `pub fn krate::f(s: krate::Struct)`.
+    References,
+}
+
+impl PublicApiGraph {
+    /// Builds the graph for `items`. Items are related by their paths, so the
+    /// graph is only as complete as `items`. For example, an item that
+    /// references an item of another crate has no edge for that reference.
+    #[must_use]
+    pub fn from_items(items: Vec<PublicItem>) -> Self {
+        let mut graph = DiGraph::with_capacity(items.len(), items.len());
+        for item in items {
+            graph.add_node(item);
+        }
+
+        // Only items with a kind, i.e. not `impl`s, declare their path
+        let mut declarations: HashMap<String, Vec<NodeIndex>> = HashMap::new();
+        for index in graph.node_indices() {
+            let item = &graph[index];
+            if item.kind_text().is_some() {
+                declarations
+                    .entry(item.path_string())
+                    .or_default()
+                    .push(index);
+            }
+        }
+        let declared = |path: &str| declarations.get(path).into_iter().flatten().copied();
+
+        let mut edges = vec![];
+        for index in graph.node_indices() {
+            let item = &graph[index];
+            let mut add_edges = |froms: Vec<NodeIndex>, kind| {
+                edges.extend(
+                    froms
+                        .into_iter()
+                        .filter(|from| *from != index)
+                        .map(|from| (from, index, kind)),
+                );
+            };
+
+            // The path of an `impl` is the path of the type it is for
+            if item.tokens.contains(&Token::ImplKeyword) && item.kind_text().is_none() {
+                add_edges(
+                    declared(&item.path_string()).collect(),
+                    EdgeKind::Implements,
+                );
+            } else if let Some((_, parent)) = item.path.split_last() {
+                add_edges(declared(&parent.join("::")).collect(), EdgeKind::Contains);
+            }
+
+            let own_path = item.path_string();
+            for path in referenced_paths(&item.tokens) {
+                if path != own_path {
+                    edges.extend(declared(&path).map(|to| (index, to, EdgeKind::References)));
+                }
+            }
+        }
+        for (from, to, kind) in edges {
+            graph.add_edge(from, to, kind);
+        }
+
+        Self { graph }
+    }
+
+    /// The underlying graph, for analysis with the algorithms of `petgraph`.
+    #[must_use]
+    pub fn graph(&self) -> &DiGraph<PublicItem, EdgeKind> {
+        &self.graph
+    }
+
+    /// The items that can be reached from the root module of the crate by
+    /// following edges, including the root module itself, in breadth first
+    /// order.
+    #[must_use]
+    pub fn reachable_from_root(&self) -> Vec<&PublicItem> {
+        let Some(root) = self.graph.node_indices().find(|index| {
+            let item = &self.graph[*index];
+            item.path.len() == 1 && item.kind_text() == Some("mod")
+        }) else {
+            return vec![];
+        };
+
+        let mut bfs = Bfs::new(&self.graph, root);
+        let mut reachable = vec![];
+        while let Some(index) = bfs.next(&self.graph) {
+            reachable.push(&self.graph[index]);
+        }
+        reachable
+    }
+
+    /// The groups of items that can reach each other by following edges, such
+    /// as two structs that have fields of each other's type. Items that are
+    /// not part of a cycle are not included.
+    #[must_use]
+    pub fn cycles(&self) -> Vec<Vec<&PublicItem>> {
+        petgraph::algo::kosaraju_scc(&self.graph)
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|component| {
+                component
+                    .into_iter()
+                    .map(|index| &self.graph[index])
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The number of edges of the longest of the shortest paths between any
+    /// two items where one can be reached from the other. Is 0 for a graph
+    /// without edges.
+    #[must_use]
+    pub fn diameter(&self) -> usize {
+        let mut diameter = 0;
+        let mut distances = vec![None; self.graph.node_count()];
+        for start in self.graph.node_indices() {
+            distances.fill(None);
+            distances[start.index()] = Some(0);
+            let mut queue = VecDeque::from([start]);
+            while let Some(index) = queue.pop_front() {
+                let distance = distances[index.index()].unwrap_or_default();
+                diameter = diameter.max(distance);
+                for neighbor in self.graph.neighbors(index) {
+                    if distances[neighbor.index()].is_none() {
+                        distances[neighbor.index()] = Some(distance + 1);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+        diameter
+    }
+}
+
+/// The paths such as `krate::module::Struct` that `tokens` mention.
+fn referenced_paths(tokens: &[Token]) -> Vec<String> {
+    let mut paths = vec![];
+    let mut path = String::new();
+    for token in tokens {
+        match token {
+            Token::Identifier(text)
+            | Token::Type(text)
+            | Token::ImplTrait(text)
+            | Token::Function(text)
+                if path.is_empty() || path.ends_with("::") =>
+            {
+                path.push_str(text);
+            }
+            Token::PathSeparator if !path.is_empty() => path.push_str("::"),
+            _ => {
+                paths.push(std::mem::take(&mut path));
+                if let Token::Identifier(text)
+                | Token::Type(text)
+                | Token::ImplTrait(text)
+                | Token::Function(text) = token
+                {
+                    path.push_str(text);
+                }
+            }
+        }
+    }
+    paths.push(path);
+    paths.retain(|path| !path.is_empty());
+    paths
+}
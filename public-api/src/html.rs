@@ -0,0 +1,119 @@
+//! Renders a [`PublicApiDiff`] as a self-contained HTML report.
+
+use std::fmt::Write;
+
+use crate::{diff::PublicApiDiff, PublicItem};
+
+/// Inline CSS so that the report is a single file that is easy to share.
+const STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; color: #1f2328; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2em; }
+th, td { border: 1px solid #d0d7de; padding: 0.4em 0.6em; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; }
+code { font-family: monospace; white-space: pre-wrap; }
+tr.removed td { background: #ffebe9; }
+tr.changed td { background: #fff8c5; }
+tr.added td { background: #dafbe1; }
+";
+
+impl PublicApiDiff {
+    /// Renders the diff as a single page HTML report with inline CSS. Removed
+    /// items are shown in red, changed items in yellow with the old and new
+    /// item side by side, and added items in green.
+    #[must_use]
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+        html.push_str("<meta charset=\"utf-8\">\n");
+        html.push_str("<title>Public API diff</title>\n");
+        let _ = write!(html, "<style>\n{STYLE}</style>\n");
+        html.push_str("</head>\n<body>\n<main>\n<h1>Public API diff</h1>\n");
+
+        push_section(
+            &mut html,
+            "removed",
+            "Removed items from the public API",
+            &["Removed item"],
+            self.removed.iter().map(|item| vec![item]),
+        );
+        push_section(
+            &mut html,
+            "changed",
+            "Changed items in the public API",
+            &["Old", "New"],
+            self.changed.iter().map(|c| vec![&c.old, &c.new]),
+        );
+        push_section(
+            &mut html,
+            "added",
+            "Added items to the public API",
+            &["Added item"],
+            self.added.iter().map(|item| vec![item]),
+        );
+
+        html.push_str("</main>\n</body>\n</html>\n");
+        html
+    }
+}
+
+/// Pushes a heading and a table with one row per entry in `rows`. If there are
+/// no rows, a "(none)" paragraph is pushed instead of the table.
+fn push_section<'a>(
+    html: &mut String,
+    class: &str,
+    heading: &str,
+    columns: &[&str],
+    rows: impl ExactSizeIterator<Item = Vec<&'a PublicItem>>,
+) {
+    let heading_id = format!("{class}-heading");
+    let _ = writeln!(html, "<h2 id=\"{heading_id}\">{heading}</h2>");
+
+    if rows.len() == 0 {
+        html.push_str("<p>(none)</p>\n");
+        return;
+    }
+
+    let _ = writeln!(html, "<table aria-labelledby=\"{heading_id}\">");
+    html.push_str("<thead>\n<tr>");
+    for column in columns {
+        let _ = write!(html, "<th scope=\"col\">{column}</th>");
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+    for row in rows {
+        let _ = write!(html, "<tr class=\"{class}\">");
+        for item in row {
+            let _ = write!(html, "<td><code>{}</code></td>", escape(&item.to_string()));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+}
+
+/// Escapes the characters that have a special meaning in HTML.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape;
+
+    #[test]
+    fn escapes_generics_and_lifetimes() {
+        assert_eq!(
+            escape("pub fn f<'a, T: Into<&'a str>>(t: T) -> &'a str"),
+            "pub fn f&lt;&#39;a, T: Into&lt;&amp;&#39;a str&gt;&gt;(t: T) -&gt; &amp;&#39;a str"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use rustdoc_types::Item;
+use rustdoc_types::{Item, ItemEnum};
 
 use crate::nameable_item::NameableItem;
 use crate::path_component::PathComponent;
@@ -43,6 +43,80 @@ impl<'c> IntermediatePublicItem<'c> {
             .collect()
     }
 
+    /// The names of the path components, e.g. `["krate", "Struct", "method"]`
+    /// for `krate::Struct::method`. Unnamed components such as `impl`s are
+    /// skipped. Hidden components are included, since for items of `impl`s
+    /// they are what leads up to the type the `impl` is for.
+    ///
+    /// Items shown at their definition path because of
+    /// [`crate::Builder::follow_reexports`] have names such as `dep::Foo`,
+    /// which are split into one component per name.
+    #[must_use]
+    pub fn display_path(&self) -> PublicItemPath {
+        self.path()
+            .iter()
+            .filter_map(|p| p.item.name())
+            .flat_map(|name| {
+                // Recursion breakers such as `<<foo::*>>` are kept as is
+                if name.starts_with("<<") {
+                    vec![name.to_owned()]
+                } else {
+                    name.split("::").map(ToOwned::to_owned).collect()
+                }
+            })
+            .collect()
+    }
+
+    /// For associated items, i.e. items of an `impl` or a `trait`, the
+    /// [`Self::display_path`] of the type or trait they belong to. For
+    /// example `["krate", "Struct"]` for `krate::Struct::method`.
+    #[must_use]
+    pub fn associated_item_of(&self) -> Option<PublicItemPath> {
+        let (_, parents) = self.path().split_last()?;
+        let parent = parents.last()?;
+        let names = |components: &[PathComponent<'_>]| {
+            components
+                .iter()
+                .filter_map(|p| p.item.name())
+                .map(ToOwned::to_owned)
+                .collect()
+        };
+        match parent.item.item.inner {
+            ItemEnum::Impl(_) => Some(names(&parents[..parents.len() - 1])),
+            ItemEnum::Trait(_) => Some(names(parents)),
+            _ => None,
+        }
+    }
+
+    /// Like [`Self::associated_item_of`], but also for enum variants and
+    /// struct and union fields, which belong to their enum, struct or union.
+    #[must_use]
+    pub fn enclosing_type(&self) -> Option<PublicItemPath> {
+        let (_, parents) = self.path().split_last()?;
+        match parents.last()?.item.item.inner {
+            ItemEnum::Enum(_) | ItemEnum::Struct(_) | ItemEnum::Union(_) => Some(
+                parents
+                    .iter()
+                    .filter_map(|p| p.item.name())
+                    .map(ToOwned::to_owned)
+                    .collect(),
+            ),
+            _ => self.associated_item_of(),
+        }
+    }
+
+    /// The conditions of the `#[cfg(...)]` attributes of all components of
+    /// the path, outermost first. See [`crate::PublicItem::cfg_conditions`].
+    #[must_use]
+    pub fn cfg_conditions(&self) -> Vec<String> {
+        self.path()
+            .iter()
+            .flat_map(|p| &p.item.item.attrs)
+            .filter_map(|attr| attr.strip_prefix("#[cfg(")?.strip_suffix(")]"))
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+
     #[must_use]
     pub fn path_contains_renamed_item(&self) -> bool {
         self.path().iter().any(|m| m.item.overridden_name.is_some())
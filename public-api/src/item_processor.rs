@@ -1,8 +1,8 @@
 use super::nameable_item::NameableItem;
 use crate::{
     crate_wrapper::CrateWrapper, intermediate_public_item::IntermediatePublicItem,
-    path_component::PathComponent, public_item::PublicItem, render::RenderingContext,
-    BuilderOptions as Options, PublicApi,
+    macro_expansion::MacroExpansions, path_component::PathComponent, public_item::PublicItem,
+    render::RenderingContext, BuilderOptions as Options, PublicApi, Result,
 };
 use rustdoc_types::{
     Crate, Id, Impl, Item, ItemEnum, Module, Struct, StructKind, Type, Use, VariantKind,
@@ -44,6 +44,13 @@ pub struct ItemProcessor<'c> {
     /// To know if e.g. blanket implementation should be included in the output.
     options: Options,
 
+    /// Finds items generated by macros. `None` if such items should be
+    /// included in the output, see [`crate::Builder::expand_macros`].
+    macro_expansions: Option<MacroExpansions>,
+
+    /// The first error that occurred while processing items, if any.
+    error: Option<crate::Error>,
+
     /// A queue of unprocessed items to process.
     work_queue: VecDeque<UnprocessedItem<'c>>,
 
@@ -57,7 +64,13 @@ impl<'c> ItemProcessor<'c> {
     pub(crate) fn new(crate_: &'c Crate, options: Options) -> Self {
         ItemProcessor {
             crate_: CrateWrapper::new(crate_),
+            macro_expansions: options
+                .rustdoc_json
+                .as_deref()
+                .filter(|_| !options.expand_macros)
+                .map(MacroExpansions::new),
             options,
+            error: None,
             work_queue: VecDeque::new(),
             output: vec![],
         }
@@ -89,6 +102,25 @@ impl<'c> ItemProcessor<'c> {
     /// Process any item. In particular, does the right thing if the item is an
     /// impl or a use.
     fn process_any_item(&mut self, item: &'c Item, unprocessed_item: UnprocessedItem<'c>) {
+        if let Some(macro_expansions) = &mut self.macro_expansions {
+            match macro_expansions.is_macro_expanded(item) {
+                Ok(false) => {}
+                Ok(true) => return,
+                Err(error) => {
+                    self.error.get_or_insert(error);
+                    return;
+                }
+            }
+        }
+
+        // The fields are also left out of the rendered tuple structs and tuple
+        // variants, see `RenderingContext::without_phantom_data_fields()`
+        if let ItemEnum::StructField(type_) = &item.inner {
+            if self.options.normalize_phantom_data && self.crate_.is_phantom_data(type_) {
+                return;
+            }
+        }
+
         match &item.inner {
             ItemEnum::Use(use_) => {
                 if use_.is_glob {
@@ -150,9 +182,10 @@ impl<'c> ItemProcessor<'c> {
         &mut self,
         item: &'c Item,
         use_: &'c Use,
-        unprocessed_item: UnprocessedItem<'c>,
+        mut unprocessed_item: UnprocessedItem<'c>,
     ) {
         let mut actual_item = item;
+        let mut name = use_.name.clone();
 
         if let Some(used_item) = use_
             .id
@@ -160,9 +193,19 @@ impl<'c> ItemProcessor<'c> {
             .and_then(|id| self.get_item_if_not_in_path(&unprocessed_item.parent_path, id))
         {
             actual_item = used_item;
+        } else if let Some(definition_path) = use_
+            .id
+            .as_ref()
+            .and_then(|id| self.reexported_definition_path(id))
+        {
+            // Items of other crates are not always in the rustdoc JSON. Then
+            // the best we can do is to show the use itself at the definition
+            // path
+            unprocessed_item.parent_path.clear();
+            name = definition_path.join("::");
         }
 
-        self.process_item(unprocessed_item, actual_item, Some(use_.name.clone()));
+        self.process_item(unprocessed_item, actual_item, Some(name));
     }
 
     /// Processes impls. Impls are special because we support filtering out e.g.
@@ -173,13 +216,32 @@ impl<'c> ItemProcessor<'c> {
         item: &'c Item,
         impl_: &'c Impl,
     ) {
-        if !ImplKind::from(item, impl_).is_active(self.options) {
+        let kind = ImplKind::from(item, impl_);
+        if !kind.is_active(&self.options)
+            || (kind == ImplKind::AutoDerived && self.is_stripped_derive(impl_))
+        {
             return;
         }
 
         self.process_item_for_type(unprocessed_item, item, None, Some(&impl_.for_));
     }
 
+    /// Returns `true` if `impl_` is an impl of a derived trait that should be
+    /// omitted from the output because of [`crate::Builder::strip_derives`].
+    fn is_stripped_derive(&self, impl_: &Impl) -> bool {
+        impl_.trait_.as_ref().is_some_and(|trait_| {
+            let name = match trait_.name.rsplit("::").next().unwrap_or(&trait_.name) {
+                // `#[derive(PartialEq)]` also derives this marker trait
+                "StructuralPartialEq" => "PartialEq",
+                name => name,
+            };
+            self.options
+                .strip_derives
+                .iter()
+                .any(|derive| derive == name)
+        })
+    }
+
     /// Make sure the item we are about to process is not already part of the
     /// item path. If it is, we have encountered recursion. Stop processing in
     /// that case.
@@ -209,13 +271,29 @@ impl<'c> ItemProcessor<'c> {
     /// it in the output.
     fn process_item(
         &mut self,
-        unprocessed_item: UnprocessedItem<'c>,
+        mut unprocessed_item: UnprocessedItem<'c>,
         item: &'c Item,
-        overridden_name: Option<String>,
+        mut overridden_name: Option<String>,
     ) {
+        // Let the item be its own path if we show items of other crates at
+        // their definition paths, see [`crate::Builder::follow_reexports`]
+        if let Some(definition_path) = self.reexported_definition_path(&item.id) {
+            unprocessed_item.parent_path.clear();
+            overridden_name = Some(definition_path.join("::"));
+        }
+
         self.process_item_for_type(unprocessed_item, item, overridden_name, None);
     }
 
+    /// If [`crate::Builder::follow_reexports`] is enabled, and the item with
+    /// `id` is defined in another crate, the path where the item is defined.
+    fn reexported_definition_path(&self, id: &Id) -> Option<&'c [String]> {
+        if !self.options.follow_reexports {
+            return None;
+        }
+        self.crate_.foreign_definition_path(id)
+    }
+
     /// Process an item. Setup jobs for its children and impls and and then put
     /// it in the output.
     fn process_item_for_type(
@@ -274,7 +352,7 @@ impl<'c> ItemProcessor<'c> {
     //
     // You might think this is rare, but it is actually a common thing in
     // real-world code.
-    fn id_to_items(&self) -> HashMap<&Id, Vec<&IntermediatePublicItem>> {
+    fn id_to_items(&self) -> HashMap<&Id, Vec<&IntermediatePublicItem<'_>>> {
         let mut id_to_items: HashMap<&Id, Vec<&IntermediatePublicItem>> = HashMap::new();
         for finished_item in &self.output {
             id_to_items
@@ -313,6 +391,10 @@ impl<'c> UnprocessedItem<'c> {
     }
 }
 
+/// The [`sorting_prefix`] of traits. Lets [`crate::PublicItem::normalize`] tell
+/// items of traits apart from items of `impl`s.
+pub(crate) const TRAIT_SORTING_PREFIX: u8 = 14;
+
 /// In order for items in the output to be nicely grouped, we add a prefix to
 /// each item in the path to an item. That way, sorting on the name (with this
 /// prefix) will group items. But we don't want this prefix to be be visible to
@@ -339,7 +421,7 @@ pub(crate) fn sorting_prefix(item: &Item) -> u8 {
 
         ItemEnum::Static(_) => 13,
 
-        ItemEnum::Trait(_) => 14,
+        ItemEnum::Trait(_) => TRAIT_SORTING_PREFIX,
 
         ItemEnum::AssocType { .. } => 15,
         ItemEnum::AssocConst { .. } => 16,
@@ -401,7 +483,7 @@ impl ImplKind {
 }
 
 impl ImplKind {
-    fn is_active(&self, options: Options) -> bool {
+    fn is_active(&self, options: &Options) -> bool {
         match self {
             ImplKind::Blanket => !options.omit_blanket_impls,
             ImplKind::AutoTrait => !options.omit_auto_trait_impls,
@@ -443,23 +525,35 @@ pub fn impls_for_item(item: &Item) -> Option<&[Id]> {
     }
 }
 
-pub(crate) fn public_api_in_crate(crate_: &Crate, options: Options) -> super::PublicApi {
-    let mut item_processor = ItemProcessor::new(crate_, options);
+pub(crate) fn public_api_in_crate(crate_: &Crate, options: &Options) -> Result<PublicApi> {
+    let mut item_processor = ItemProcessor::new(crate_, options.clone());
     item_processor.add_to_work_queue(vec![], &crate_.root);
     item_processor.run();
+    if let Some(error) = item_processor.error.take() {
+        return Err(error);
+    }
 
     let context = RenderingContext {
         crate_,
         id_to_items: item_processor.id_to_items(),
-        options,
+        options: options.clone(),
     };
 
-    PublicApi {
+    Ok(PublicApi {
         items: item_processor
             .output
             .iter()
             .map(|item| PublicItem::from_intermediate_public_item(&context, item))
             .collect::<Vec<_>>(),
         missing_item_ids: item_processor.crate_.missing_item_ids(),
-    }
+    })
+}
+
+/// Like [`public_api_in_crate`], but only counts the items instead of rendering
+/// them.
+pub(crate) fn public_api_items_count_in_crate(crate_: &Crate, options: &Options) -> usize {
+    let mut item_processor = ItemProcessor::new(crate_, options.clone());
+    item_processor.add_to_work_queue(vec![], &crate_.root);
+    item_processor.run();
+    item_processor.output.len()
 }
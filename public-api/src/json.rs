@@ -0,0 +1,96 @@
+//! Renders a [`PublicApiDiff`] as JSON with a stable layout, for golden files.
+
+use serde_json::{Map, Value};
+
+use crate::diff::PublicApiDiff;
+
+impl PublicApiDiff {
+    /// Serializes the diff as pretty-printed JSON whose object keys are sorted
+    /// alphabetically at every level and indented with 2 spaces, followed by a
+    /// newline. Serializing the same diff always gives the same text, so the
+    /// output is suitable for golden files that are checked into version
+    /// control.
+    #[must_use]
+    pub fn to_pretty_json(&self) -> String {
+        let value = serde_json::to_value(self).expect("diff is always serializable");
+        let mut json = serde_json::to_string_pretty(&sorted(value))
+            .expect("JSON values are always serializable");
+        json.push('\n');
+        json
+    }
+}
+
+/// Sorts the keys of all objects in `value`, recursively. The order of
+/// [`Map`]s depends on the features of `serde_json`, so it is not relied on.
+fn sorted(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sorted(value)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(sorted).collect()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::sorted;
+    use crate::diff::{ChangedPublicItem, PublicApiDiff};
+    use crate::public_item::test_item;
+    use crate::tokens::Token;
+    use crate::PublicItem;
+
+    #[test]
+    fn keys_are_sorted_and_indented() {
+        let diff = PublicApiDiff {
+            removed: vec![],
+            changed: vec![ChangedPublicItem {
+                old: item("a"),
+                new: item("b"),
+            }],
+            added: vec![item("c")],
+        };
+
+        let json = diff.to_pretty_json();
+        assert_eq!(json, diff.clone().to_pretty_json());
+        assert!(json.starts_with("{\n  \"added\": [\n    {\n"), "{json}");
+        assert!(json.ends_with("}\n"), "{json}");
+        let keys: Vec<&str> = json
+            .lines()
+            .filter(|line| line.starts_with("  \""))
+            .collect();
+        assert_eq!(
+            keys,
+            ["  \"added\": [", "  \"changed\": [", "  \"removed\": []"]
+        );
+        assert!(json.lines().all(|line| !line.contains('\t')));
+        assert!(json
+            .lines()
+            .all(|line| (line.len() - line.trim_start().len()) % 2 == 0));
+    }
+
+    #[test]
+    fn nested_objects_are_sorted() {
+        let value = sorted(json!({ "b": { "d": 1, "c": [{ "f": 1, "e": 2 }] }, "a": null }));
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"a":null,"b":{"c":[{"e":2,"f":1}],"d":1}}"#
+        );
+    }
+
+    fn item(name: &str) -> PublicItem {
+        test_item(
+            [name],
+            vec![Token::FnKeyword, Token::Whitespace, Token::function(name)],
+        )
+    }
+}
@@ -0,0 +1,221 @@
+//! Renders a [`PublicApiDiff`] as a [JUnit XML](https://github.com/testmoapp/junitxml)
+//! report, the format that CI systems such as Jenkins and Bamboo consume.
+
+use std::fmt::Write;
+
+use crate::{diff::PublicApiDiff, html::escape};
+
+/// A test case of the report, i.e. one category of the diff.
+struct TestCase {
+    name: &'static str,
+    /// The lines that describe the items of the category.
+    lines: Vec<String>,
+    /// Why the test case failed, or `None` if it passed.
+    failure: Option<String>,
+}
+
+impl PublicApiDiff {
+    /// Renders the diff as a JUnit XML report with one test suite named
+    /// `test_suite_name`. The test suite has one test case each for removed,
+    /// changed and added items.
+    ///
+    /// The test case for removed items fails if any items were removed, and
+    /// the test case for changed items fails if any change is not [semver
+    /// compatible](crate::diff::ChangedPublicItem::is_semver_compatible). The
+    /// test case for added items only fails if `treat_additions_as_failures`
+    /// is `true`. Items of test cases that pass are listed in `<system-out>`,
+    /// which CI systems typically show as output of the test case.
+    #[must_use]
+    pub fn to_junit_xml(&self, test_suite_name: &str, treat_additions_as_failures: bool) -> String {
+        let removed = TestCase {
+            name: "removed items",
+            lines: self.removed.iter().map(|item| format!("-{item}")).collect(),
+            failure: (!self.removed.is_empty())
+                .then(|| format!("{} items were removed", self.removed.len())),
+        };
+        let breaking = self
+            .changed
+            .iter()
+            .filter(|c| !c.is_semver_compatible())
+            .count();
+        let changed = TestCase {
+            name: "changed items",
+            lines: (self.changed.iter())
+                .map(|c| format!("-{}\n+{}", c.old, c.new))
+                .collect(),
+            failure: (breaking > 0)
+                .then(|| format!("{breaking} items were changed in breaking ways")),
+        };
+        let added = TestCase {
+            name: "added items",
+            lines: self.added.iter().map(|item| format!("+{item}")).collect(),
+            failure: (treat_additions_as_failures && !self.added.is_empty())
+                .then(|| format!("{} items were added", self.added.len())),
+        };
+        let test_cases = [removed, changed, added];
+
+        let suite = escape(test_suite_name);
+        let failures = test_cases.iter().filter(|t| t.failure.is_some()).count();
+        let tests = test_cases.len();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"{suite}\" tests=\"{tests}\" failures=\"{failures}\">",
+        );
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{suite}\" tests=\"{tests}\" failures=\"{failures}\">",
+        );
+        for test_case in &test_cases {
+            push_test_case(&mut xml, &suite, test_case);
+        }
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+}
+
+fn push_test_case(xml: &mut String, suite: &str, test_case: &TestCase) {
+    let name = test_case.name;
+    let details = escape(&test_case.lines.join("\n"));
+    let _ = write!(xml, "    <testcase classname=\"{suite}\" name=\"{name}\"");
+    match &test_case.failure {
+        Some(message) => {
+            let message = escape(message);
+            let _ = writeln!(
+                xml,
+                ">\n      <failure message=\"{message}\" type=\"{name}\">{details}</failure>\n    </testcase>",
+            );
+        }
+        None if test_case.lines.is_empty() => xml.push_str("/>\n"),
+        None => {
+            let _ = writeln!(
+                xml,
+                ">\n      <system-out>{details}</system-out>\n    </testcase>",
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quick_xml::{events::Event, Reader};
+
+    use crate::{
+        diff::{ChangedPublicItem, PublicApiDiff},
+        public_item::{test_fn, with_fn_qualifier},
+    };
+
+    #[test]
+    fn report_is_valid_junit_xml() {
+        let diff = PublicApiDiff {
+            removed: vec![test_fn(&["krate", "a"], &[])],
+            changed: vec![ChangedPublicItem {
+                old: test_fn(&["krate", "b"], &[]),
+                new: test_fn(&["krate", "b"], &[("x", "u8")]),
+            }],
+            added: vec![test_fn(&["krate", "c"], &[("s", "&'static str")])],
+        };
+
+        let report = Report::parse(&diff.to_junit_xml("example_api <1.2.0>", false));
+        assert_eq!(report.suite_name, "example_api <1.2.0>");
+        assert_eq!(
+            report.test_cases,
+            ["removed items", "changed items", "added items"]
+        );
+        assert_eq!(report.failures, ["removed items", "changed items"]);
+        assert!(report.text.contains("+pub fn krate::c(s: &'static str)"));
+
+        let report = Report::parse(&diff.to_junit_xml("example_api", true));
+        assert_eq!(
+            report.failures,
+            ["removed items", "changed items", "added items"]
+        );
+    }
+
+    #[test]
+    fn semver_compatible_changes_pass() {
+        let b = test_fn(&["krate", "b"], &[("x", "u8")]);
+        let diff = PublicApiDiff {
+            changed: vec![ChangedPublicItem {
+                new: with_fn_qualifier(&b, "const"),
+                old: b,
+            }],
+            ..PublicApiDiff::default()
+        };
+
+        let report = Report::parse(&diff.to_junit_xml("example_api", true));
+        assert!(report.failures.is_empty());
+        assert!(report
+            .text
+            .contains("-pub fn krate::b(x: u8)\n+pub const fn krate::b(x: u8)"));
+    }
+
+    #[test]
+    fn empty_diff_passes() {
+        let report = Report::parse(&PublicApiDiff::default().to_junit_xml("example_api", true));
+        assert_eq!(report.test_cases.len(), 3);
+        assert!(report.failures.is_empty());
+    }
+
+    /// The parts of a parsed JUnit XML report that the tests check.
+    #[derive(Default)]
+    struct Report {
+        suite_name: String,
+        test_cases: Vec<String>,
+        /// The names of the test cases that failed.
+        failures: Vec<String>,
+        text: String,
+    }
+
+    impl Report {
+        /// Parses `xml`, and panics if it is not well-formed or if its
+        /// elements are not nested as the JUnit XML schema requires.
+        fn parse(xml: &str) -> Self {
+            let mut report = Report::default();
+            let mut reader = Reader::from_str(xml);
+            let mut stack: Vec<String> = vec![];
+            let mut test_case = String::new();
+            loop {
+                let (event, empty) = match reader.read_event().unwrap() {
+                    Event::Eof => break,
+                    Event::Start(e) => (e, false),
+                    Event::Empty(e) => (e, true),
+                    Event::End(_) => {
+                        stack.pop();
+                        continue;
+                    }
+                    Event::Text(text) => {
+                        report.text.push_str(&text.unescape().unwrap());
+                        continue;
+                    }
+                    _ => continue,
+                };
+                let element = String::from_utf8(event.name().as_ref().to_vec()).unwrap();
+                let attr = |name: &str| {
+                    let attr = event.try_get_attribute(name).unwrap().unwrap();
+                    attr.unescape_value().unwrap().into_owned()
+                };
+                let parent = stack.last().map(String::as_str);
+                match (parent, element.as_str()) {
+                    (None, "testsuites") => {}
+                    (Some("testsuites"), "testsuite") => report.suite_name = attr("name"),
+                    (Some("testsuite"), "testcase") => {
+                        test_case = attr("name");
+                        report.test_cases.push(test_case.clone());
+                    }
+                    (Some("testcase"), "failure") => {
+                        assert!(!attr("message").is_empty());
+                        report.failures.push(test_case.clone());
+                    }
+                    (Some("testcase"), "system-out") => {}
+                    other => panic!("unexpected element {other:?}"),
+                }
+                if !empty {
+                    stack.push(element);
+                }
+            }
+            assert!(stack.is_empty());
+            report
+        }
+    }
+}
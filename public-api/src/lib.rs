@@ -34,14 +34,32 @@
 // deny in CI, only warn here
 #![warn(clippy::all, missing_docs)]
 
+#[cfg(feature = "async")]
+mod asynchronous;
+mod compatibility_matrix;
+mod coverage;
 mod crate_wrapper;
+mod delta_store;
 mod error;
+mod graph;
+mod html;
 mod intermediate_public_item;
 mod item_processor;
+mod json;
+mod junit;
+mod lsp;
+mod macro_expansion;
+mod module_tree;
+#[cfg(feature = "monitor")]
+mod monitor;
 mod nameable_item;
 mod path_component;
+mod public_api_set;
 mod public_item;
+mod release_notes;
 mod render;
+mod slack;
+mod snapshot;
 pub mod tokens;
 
 pub mod diff;
@@ -52,7 +70,21 @@ use std::path::PathBuf;
 pub use error::{Error, Result};
 
 // Documented at the definition site so cargo doc picks it up
-pub use public_item::PublicItem;
+pub use public_item::{Bound, PublicItem, PublicItemsByPath, TraitImpl};
+
+pub use compatibility_matrix::{Compatibility, CompatibilityMatrix};
+pub use coverage::{CoverageDelta, PublicApiCoverage};
+pub use delta_store::DeltaStore;
+pub use graph::{EdgeKind, PublicApiGraph};
+pub use module_tree::ModuleTree;
+pub use public_api_set::PublicApiSet;
+pub use snapshot::PublicApiSnapshot;
+
+#[cfg(feature = "async")]
+pub use asynchronous::compute_public_api_async;
+
+#[cfg(feature = "monitor")]
+pub use monitor::{PublicApiMonitor, PublicApiMonitorHandle};
 
 /// This constant defines the minimum version of nightly that is required in
 /// order for the rustdoc JSON output to be parsable by this library. Note that
@@ -64,14 +96,66 @@ pub use public_item::PublicItem;
 /// nightly or later, you should be fine.
 pub const MINIMUM_NIGHTLY_RUST_VERSION: &str = "nightly-2024-10-18";
 
+/// Commonly derived traits that rarely matter when reviewing changes to a
+/// public API. The default value of [`Builder::strip_derives`].
+pub const BOILERPLATE_DERIVES: &[&str] = &[
+    "Debug",
+    "Clone",
+    "Copy",
+    "PartialEq",
+    "Eq",
+    "Hash",
+    "Default",
+];
+
 /// See [`Builder`] method docs for what each field means.
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 struct BuilderOptions {
     sorted: bool,
     debug_sorting: bool,
     omit_blanket_impls: bool,
     omit_auto_trait_impls: bool,
     omit_auto_derived_impls: bool,
+    json_format_version: Option<u32>,
+    include_docs: bool,
+    redact_doc_comments: bool,
+    max_depth: Option<usize>,
+    strip_derives: Vec<String>,
+    normalize_whitespace: bool,
+    normalize_attribute_order: bool,
+    normalize_phantom_data: bool,
+    follow_reexports: bool,
+    expand_macros: bool,
+
+    /// The rustdoc JSON file, if the public API is built from one. Only used
+    /// to find source files if `expand_macros` is `false`.
+    rustdoc_json: Option<PathBuf>,
+}
+
+impl Default for BuilderOptions {
+    fn default() -> Self {
+        Self {
+            sorted: true,
+            debug_sorting: false,
+            omit_blanket_impls: false,
+            omit_auto_trait_impls: false,
+            omit_auto_derived_impls: false,
+            json_format_version: None,
+            include_docs: false,
+            redact_doc_comments: false,
+            max_depth: None,
+            strip_derives: BOILERPLATE_DERIVES
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            normalize_whitespace: false,
+            normalize_attribute_order: false,
+            normalize_phantom_data: false,
+            follow_reexports: false,
+            expand_macros: true,
+            rustdoc_json: None,
+        }
+    }
 }
 
 /// Builds [`PublicApi`]s. See the [top level][`crate`] module docs for example
@@ -87,16 +171,13 @@ impl Builder {
     /// [top level][`crate`] module docs for example code.
     #[must_use]
     pub fn from_rustdoc_json(path: impl Into<PathBuf>) -> Self {
-        let options = BuilderOptions {
-            sorted: true,
-            debug_sorting: false,
-            omit_blanket_impls: false,
-            omit_auto_trait_impls: false,
-            omit_auto_derived_impls: false,
-        };
+        let rustdoc_json = path.into();
         Self {
-            rustdoc_json: path.into(),
-            options,
+            options: BuilderOptions {
+                rustdoc_json: Some(rustdoc_json.clone()),
+                ..BuilderOptions::default()
+            },
+            rustdoc_json,
         }
     }
 
@@ -166,6 +247,143 @@ impl Builder {
         self
     }
 
+    /// If `Some`, the `format_version` of the rustdoc JSON must be exactly the
+    /// given version, otherwise [`Builder::build`] fails with
+    /// [`Error::FormatVersionMismatch`]. Useful to prevent silently wrong
+    /// results when the rustdoc JSON was built with a different version of
+    /// nightly than expected.
+    ///
+    /// The default value is `None`, which means any format version that can be
+    /// parsed is accepted.
+    #[must_use]
+    pub fn json_format_version(mut self, json_format_version: Option<u32>) -> Self {
+        self.options.json_format_version = json_format_version;
+        self
+    }
+
+    /// If `true`, items with documentation comments begin with a
+    /// [`tokens::Token::Doc`] token that contains the docs. Use
+    /// [`diff::DiffOptions::ignore_doc_changes`] to tell changes to the docs
+    /// apart from changes to the items themselves.
+    ///
+    /// The default value is `false`, since docs are not part of the public API
+    /// in a semver sense.
+    #[must_use]
+    pub fn include_docs(mut self, include_docs: bool) -> Self {
+        self.options.include_docs = include_docs;
+        self
+    }
+
+    /// If `true`, the text of every [`tokens::Token::Doc`] is replaced with
+    /// `[redacted]`, so that reports can be shared without revealing the
+    /// content of doc comments. Items still differ in whether they have docs
+    /// at all, so added and removed docs are still detected. Only relevant if
+    /// [`Self::include_docs`] is enabled.
+    ///
+    /// The default value is `false`.
+    #[must_use]
+    pub fn redact_doc_comments(mut self, redact_doc_comments: bool) -> Self {
+        self.options.redact_doc_comments = redact_doc_comments;
+        self
+    }
+
+    /// If `Some`, items with a [`PublicItem::path_depth`] greater than the
+    /// given depth are omitted from the output. For example, a max depth of 1
+    /// only includes the items at the root of the crate. This makes the output
+    /// less noisy for crates with deeply nested items.
+    ///
+    /// The default value is `None` so that the listed public API is complete
+    /// by default.
+    #[must_use]
+    pub fn max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.options.max_depth = max_depth;
+        self
+    }
+
+    /// Items that belong to automatically derived implementations of the given
+    /// traits are omitted from the output. Traits are given by name, e.g.
+    /// `"Debug"`. Pass an empty list to list all derived impls. To omit all
+    /// derived impls, use [`Self::omit_auto_derived_impls`] instead.
+    ///
+    /// The default value is [`BOILERPLATE_DERIVES`], so that adding or removing
+    /// e.g. `#[derive(Debug)]` does not show up as a change in diffs.
+    #[must_use]
+    pub fn strip_derives(mut self, derives: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.options.strip_derives = derives.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// If `true`, whitespace in items is put on a canonical form, so that
+    /// items that only differ in formatting, such as in the amount of
+    /// whitespace, are considered equal when diffing. Analogous to `git diff
+    /// -w`.
+    ///
+    /// The default value is `false`.
+    #[must_use]
+    pub fn normalize_whitespace(mut self, normalize_whitespace: bool) -> Self {
+        self.options.normalize_whitespace = normalize_whitespace;
+        self
+    }
+
+    /// If `true`, the attributes of items are put in a canonical order, so that
+    /// items that only differ in the order of their attributes, such as
+    /// `#[derive(Debug, Clone)]` and `#[derive(Clone, Debug)]`, are considered
+    /// equal when diffing. See also [`PublicItem::semantically_equal`].
+    ///
+    /// The default value is `false`.
+    #[must_use]
+    pub fn normalize_attribute_order(mut self, normalize_attribute_order: bool) -> Self {
+        self.options.normalize_attribute_order = normalize_attribute_order;
+        self
+    }
+
+    /// If `true`, fields of type `PhantomData` are left out, so that e.g.
+    /// `pub struct Foo<T>(pub PhantomData<T>)` and `pub struct Foo<T> {
+    /// _marker: PhantomData<T> }` are considered equal when diffing. Such
+    /// fields only exist to use generic parameters, and switching between the
+    /// two forms is not a meaningful change. Other zero-sized types are kept.
+    ///
+    /// The default value is `false`.
+    #[must_use]
+    pub fn normalize_phantom_data(mut self, normalize_phantom_data: bool) -> Self {
+        self.options.normalize_phantom_data = normalize_phantom_data;
+        self
+    }
+
+    /// If `true`, items that are re-exported from other crates, e.g. with `pub
+    /// use dep::Foo;`, are shown at the path where they are defined, e.g.
+    /// `dep::Foo`, rather than at the path of the re-export. This makes it easy
+    /// to audit which types of dependencies are exposed in the public API.
+    ///
+    /// The default value is `false`, since the path of the re-export is the
+    /// path that users of the crate use.
+    #[must_use]
+    pub fn follow_reexports(mut self, follow_reexports: bool) -> Self {
+        self.options.follow_reexports = follow_reexports;
+        self
+    }
+
+    /// If `false`, items that were generated by macros, such as by
+    /// `macro_rules!` macros or by derive macros, are omitted from the output.
+    /// Rustdoc JSON does not tell which items that macros generated, so they
+    /// are found by reading the source code of each item. The spans of items
+    /// in the rustdoc JSON are relative to the workspace root of the crate, so
+    /// this only works if the rustdoc JSON is in a subdirectory of the
+    /// workspace root, as with the default target dir. Otherwise building
+    /// fails with [`Error::SourceFileUnreadable`]. Only items of the crate
+    /// itself are looked at, not items inlined from other crates. Impls
+    /// generated by `#[derive(...)]`s of e.g.
+    /// `Clone` are also omitted. Use [`Self::omit_auto_derived_impls`] to only
+    /// omit those.
+    ///
+    /// The default value is `true`, so that the listed public API is complete
+    /// by default.
+    #[must_use]
+    pub fn expand_macros(mut self, expand_macros: bool) -> Self {
+        self.options.expand_macros = expand_macros;
+        self
+    }
+
     /// Builds [`PublicApi`]. See the [top level][`crate`] module docs for
     /// example code.
     ///
@@ -174,7 +392,42 @@ impl Builder {
     /// E.g. if the [JSON](Builder::from_rustdoc_json) is invalid or if the file
     /// can't be read.
     pub fn build(self) -> Result<PublicApi> {
-        from_rustdoc_json_str(std::fs::read_to_string(self.rustdoc_json)?, self.options)
+        from_rustdoc_json_bytes(&std::fs::read(self.rustdoc_json)?, self.options)
+    }
+
+    /// Like [`Self::build`], but bundles the [`PublicApi`] with the name and
+    /// version of the crate, as found in the rustdoc JSON, and with
+    /// `toolchain`, which should be the toolchain that built the rustdoc JSON,
+    /// e.g. `nightly-2024-10-18`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::build`].
+    pub fn build_snapshot(self, toolchain: impl Into<String>) -> Result<PublicApiSnapshot> {
+        let crate_ = deserialize_crate(&std::fs::read(self.rustdoc_json)?, &self.options)?;
+        let crate_name = crate_
+            .index
+            .get(&crate_.root)
+            .and_then(|root| root.name.clone())
+            .unwrap_or_default();
+        Ok(PublicApiSnapshot {
+            crate_name,
+            version: crate_.crate_version.clone().unwrap_or_default(),
+            toolchain: toolchain.into(),
+            items: public_api_from_crate(&crate_, &self.options)?.items,
+            generated_at: std::time::SystemTime::now(),
+        })
+    }
+
+    /// Like [`Self::build`], but groups the items by the module they are in.
+    /// See [`ModuleTree::from_items`]. [`ModuleTree::flatten`] gives the same
+    /// items as [`Self::build`] with the same options.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::build`].
+    pub fn build_module_tree(self) -> Result<ModuleTree> {
+        Ok(ModuleTree::from_items(self.build()?.items))
     }
 }
 
@@ -207,7 +460,8 @@ impl Builder {
 /// let public_api_string = public_api.to_string();
 /// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive] // More fields might be added in the future
 pub struct PublicApi {
     /// The items that constitutes the public API. An "item" is for example a
@@ -231,6 +485,18 @@ impl PublicApi {
         self.items.into_iter()
     }
 
+    /// Renames every name in every item with `rename`, both in the path of the
+    /// item and in its tokens. For example, to diff two versions of an API as
+    /// if `read_v2` and `read_v3` were the same function, rename both to
+    /// `read`. The items are not sorted again.
+    #[must_use]
+    pub fn rename_items(mut self, rename: impl Fn(&str) -> String) -> Self {
+        for item in &mut self.items {
+            *item = item.renamed(&rename);
+        }
+        self
+    }
+
     /// The rustdoc JSON IDs of missing but referenced items. Intended for use
     /// with `--verbose` flags or similar.
     ///
@@ -256,13 +522,124 @@ impl std::fmt::Display for PublicApi {
     }
 }
 
-fn from_rustdoc_json_str(
-    rustdoc_json_str: impl AsRef<str>,
-    options: BuilderOptions,
+/// Parses the given rustdoc JSON into a sorted list of public items, but only
+/// after asserting that the `format_version` of the JSON is `version`.
+///
+/// # Errors
+///
+/// [`Error::FormatVersionMismatch`] if the format version does not match, or
+/// [`Error::SerdeJsonError`] if the JSON is invalid.
+pub fn parse_with_format_version(json: &str, version: u32) -> Result<Vec<PublicItem>> {
+    let options = BuilderOptions {
+        json_format_version: Some(version),
+        ..BuilderOptions::default()
+    };
+    Ok(from_rustdoc_json_bytes(json.as_bytes(), options)?.items)
+}
+
+/// Counts the public items in the given rustdoc JSON. Faster and more memory
+/// efficient than building a [`PublicApi`] and counting its items, since the
+/// items are never rendered.
+///
+/// # Errors
+///
+/// [`Error::SerdeJsonError`] if the JSON is invalid.
+pub fn public_api_items_count(json_str: &str) -> Result<usize> {
+    let crate_ = deserialize_without_recursion_limit::<rustdoc_types::Crate>(json_str.as_bytes())?;
+    Ok(item_processor::public_api_items_count_in_crate(
+        &crate_,
+        &BuilderOptions::default(),
+    ))
+}
+
+/// Builds the public API from the given rustdoc JSON with the default
+/// [`Builder`] options, and groups its items by the module they are in. See
+/// [`ModuleTree::from_items`]. Use [`Builder::build_module_tree`] to build the
+/// tree with other options, such as the ones of a flat listing.
+///
+/// # Errors
+///
+/// [`Error::SerdeJsonError`] if the JSON is invalid.
+pub fn public_api_module_tree(json_str: &str) -> Result<ModuleTree> {
+    let public_api = from_rustdoc_json_bytes(json_str.as_bytes(), BuilderOptions::default())?;
+    Ok(ModuleTree::from_items(public_api.items))
+}
+
+/// Builds the public API from the given rustdoc JSON with the default
+/// [`Builder`] options. Rustdoc JSON is always valid UTF-8, but the bytes do
+/// not need to be validated as UTF-8 before calling this function, since the
+/// JSON parser does that.
+///
+/// # Errors
+///
+/// [`Error::SerdeJsonError`] if the bytes are not valid UTF-8 or not valid
+/// rustdoc JSON.
+pub fn public_api_from_rustdoc_json_bytes(rustdoc_json: &[u8]) -> Result<PublicApi> {
+    from_rustdoc_json_bytes(rustdoc_json, BuilderOptions::default())
+}
+
+/// The entry point that all other ways of building a [`PublicApi`] end up in.
+fn from_rustdoc_json_bytes(rustdoc_json: &[u8], options: BuilderOptions) -> Result<PublicApi> {
+    let crate_ = deserialize_crate(rustdoc_json, &options)?;
+    public_api_from_crate(&crate_, &options)
+}
+
+/// Deserializes the rustdoc JSON, and checks its format version if
+/// [`Builder::json_format_version`] is used.
+fn deserialize_crate(
+    rustdoc_json: &[u8],
+    options: &BuilderOptions,
+) -> Result<rustdoc_types::Crate> {
+    let check_format_version = |actual| match options.json_format_version {
+        Some(expected) if actual != expected => {
+            Err(Error::FormatVersionMismatch { expected, actual })
+        }
+        _ => Ok(()),
+    };
+
+    match deserialize_without_recursion_limit::<rustdoc_types::Crate>(rustdoc_json) {
+        Ok(crate_) => {
+            check_format_version(crate_.format_version)?;
+            Ok(crate_)
+        }
+        // JSON of another format version often fails to deserialize, so only
+        // then do we parse the JSON a second time to tell if that is why
+        Err(e) => {
+            if options.json_format_version.is_some() {
+                if let Ok(FormatVersion { format_version }) =
+                    deserialize_without_recursion_limit(rustdoc_json)
+                {
+                    check_format_version(format_version)?;
+                }
+            }
+            Err(e)
+        }
+    }
+}
+
+fn public_api_from_crate(
+    crate_: &rustdoc_types::Crate,
+    options: &BuilderOptions,
 ) -> Result<PublicApi> {
-    let crate_ = deserialize_without_recursion_limit(rustdoc_json_str.as_ref())?;
+    let mut public_api = item_processor::public_api_in_crate(crate_, options)?;
 
-    let mut public_api = item_processor::public_api_in_crate(&crate_, options);
+    if options.normalize_whitespace {
+        for item in &mut public_api.items {
+            item.tokens = tokens::normalize_whitespace(item.tokens.to_vec()).into();
+        }
+    }
+
+    if options.normalize_attribute_order {
+        for item in &mut public_api.items {
+            item.tokens = tokens::normalize_attribute_order(item.tokens.to_vec()).into();
+        }
+    }
+
+    if let Some(max_depth) = options.max_depth {
+        public_api
+            .items
+            .retain(|item| item.path_depth() <= max_depth);
+    }
 
     if options.sorted {
         public_api.items.sort_by(PublicItem::grouping_cmp);
@@ -274,8 +651,17 @@ fn from_rustdoc_json_str(
 /// Helper to deserialize the JSON with `serde_json`, but with the recursion
 /// limit disabled. Otherwise we hit the recursion limit on crates such as
 /// `diesel`.
-fn deserialize_without_recursion_limit(rustdoc_json_str: &str) -> Result<rustdoc_types::Crate> {
-    let mut deserializer = serde_json::Deserializer::from_str(rustdoc_json_str);
+fn deserialize_without_recursion_limit<T: serde::de::DeserializeOwned>(
+    rustdoc_json: &[u8],
+) -> Result<T> {
+    let mut deserializer = serde_json::Deserializer::from_slice(rustdoc_json);
     deserializer.disable_recursion_limit();
     Ok(serde::de::Deserialize::deserialize(&mut deserializer)?)
 }
+
+/// Only the `format_version` of the rustdoc JSON, so that it can be checked
+/// even if the rest of the JSON fails to deserialize.
+#[derive(serde::Deserialize)]
+struct FormatVersion {
+    format_version: u32,
+}
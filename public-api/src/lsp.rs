@@ -0,0 +1,127 @@
+//! Renders a [`PublicApiDiff`] as Language Server Protocol diagnostics.
+
+use serde_json::{json, Value};
+
+use crate::{diff::PublicApiDiff, PublicItem};
+
+/// The `DiagnosticSeverity` values of the LSP spec.
+const ERROR: u8 = 1;
+const WARNING: u8 = 2;
+const INFORMATION: u8 = 3;
+
+impl PublicApiDiff {
+    /// Renders the diff as LSP `Diagnostic` objects, one per removed, changed
+    /// and added item, for editor integrations to publish for the document
+    /// `uri`, e.g. `file:///path/to/src/lib.rs`. Removed items and breaking
+    /// changes are errors, semver compatible changes are warnings, and added
+    /// items are information.
+    ///
+    /// Public items do not know where in the source code they are defined, so
+    /// the range of each diagnostic is the start of the document. The
+    /// `relatedInformation` of each diagnostic points to the same location.
+    #[must_use]
+    pub fn to_lsp_diagnostics(&self, uri: &str) -> Vec<Value> {
+        let removed = self.removed.iter().map(|item| {
+            let message = format!("Removed from the public API: {item}");
+            diagnostic(uri, ERROR, "removed", message, item)
+        });
+        let changed = self.changed.iter().map(|changed| {
+            let (severity, message) = if changed.is_semver_compatible() {
+                (
+                    WARNING,
+                    "Changed in the public API in a semver compatible way",
+                )
+            } else {
+                (ERROR, "Changed in the public API")
+            };
+            let message = format!("{message}: {} (was: {})", changed.new, changed.old);
+            diagnostic(uri, severity, "changed", message, &changed.new)
+        });
+        let added = self.added.iter().map(|item| {
+            let message = format!("Added to the public API: {item}");
+            diagnostic(uri, INFORMATION, "added", message, item)
+        });
+        removed.chain(changed).chain(added).collect()
+    }
+}
+
+fn diagnostic(uri: &str, severity: u8, code: &str, message: String, item: &PublicItem) -> Value {
+    let range = json!({
+        "start": { "line": 0, "character": 0 },
+        "end": { "line": 0, "character": 0 },
+    });
+    json!({
+        "range": range,
+        "severity": severity,
+        "code": code,
+        "source": "public-api",
+        "message": message,
+        "relatedInformation": [{
+            "location": { "uri": uri, "range": range },
+            "message": item.path().join("::"),
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::{
+        diff::{ChangedPublicItem, PublicApiDiff},
+        public_item::{test_fn, with_fn_qualifier},
+    };
+
+    #[test]
+    fn diagnostics_follow_lsp_spec() {
+        let c = test_fn(&["krate", "c"], &[("x", "u8")]);
+        let diff = PublicApiDiff {
+            removed: vec![test_fn(&["krate", "a"], &[])],
+            changed: vec![
+                ChangedPublicItem {
+                    old: test_fn(&["krate", "b"], &[]),
+                    new: test_fn(&["krate", "b"], &[("x", "u8")]),
+                },
+                ChangedPublicItem {
+                    new: with_fn_qualifier(&c, "const"),
+                    old: c,
+                },
+            ],
+            added: vec![test_fn(&["krate", "d"], &[])],
+        };
+
+        let diagnostics = diff.to_lsp_diagnostics("file:///lib.rs");
+
+        let severities: Vec<_> = diagnostics.iter().map(|d| &d["severity"]).collect();
+        assert_eq!(severities, [1, 1, 2, 3]);
+        assert_eq!(
+            diagnostics[1]["message"],
+            "Changed in the public API: pub fn krate::b(x: u8) (was: pub fn krate::b())"
+        );
+        assert_eq!(
+            diagnostics[2]["message"],
+            "Changed in the public API in a semver compatible way: \
+             pub const fn krate::c(x: u8) (was: pub fn krate::c(x: u8))"
+        );
+        assert_eq!(
+            diagnostics[2]["relatedInformation"][0]["message"],
+            "krate::c"
+        );
+        for diagnostic in &diagnostics {
+            assert_is_range(&diagnostic["range"]);
+            assert!(diagnostic["message"].is_string());
+            assert_eq!(diagnostic["source"], "public-api");
+
+            let location = &diagnostic["relatedInformation"][0]["location"];
+            assert_eq!(location["uri"], "file:///lib.rs");
+            assert_is_range(&location["range"]);
+        }
+    }
+
+    fn assert_is_range(range: &Value) {
+        for position in [&range["start"], &range["end"]] {
+            assert!(position["line"].is_u64(), "{range}");
+            assert!(position["character"].is_u64(), "{range}");
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use rustdoc_types::{Item, ItemEnum, Span};
+
+use crate::tokens::RUST_KEYWORDS;
+use crate::{Error, Result};
+
+/// Finds items that were generated by macros, for
+/// [`crate::Builder::expand_macros`]. Rustdoc JSON has no explicit information
+/// about macro expansions, but the span of an item that a macro generated is
+/// the span of the macro invocation, e.g. `make_fn!(f)` for `macro_rules!`
+/// macros and `MyDerive` in `#[derive(MyDerive)]` for derive macros. So we
+/// look at the source code that the span of each item covers.
+pub struct MacroExpansions {
+    /// The directories that relative paths of spans are resolved against, in
+    /// order of preference.
+    source_roots: Vec<PathBuf>,
+
+    /// The source files that have been read so far, or `None` for files that
+    /// could not be read.
+    files: HashMap<PathBuf, Option<Vec<String>>>,
+}
+
+impl MacroExpansions {
+    /// Paths of spans are relative to the workspace root of the crate, which
+    /// for the default target dir is an ancestor of the `rustdoc_json` file.
+    pub fn new(rustdoc_json: &Path) -> Self {
+        Self {
+            source_roots: rustdoc_json
+                .ancestors()
+                .skip(1)
+                .map(Path::to_path_buf)
+                .collect(),
+            files: HashMap::new(),
+        }
+    }
+
+    /// Returns `true` if `item` was generated by a macro. Only items of the
+    /// crate itself are looked at, since the source code of items inlined from
+    /// other crates is not necessarily available.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::SourceFileUnreadable`] if the source file of `item` can't be
+    /// found or read.
+    pub fn is_macro_expanded(&mut self, item: &Item) -> Result<bool> {
+        // The span of a module is its whole file, and fields and variants are
+        // only generated together with the struct or enum they belong to
+        if item.crate_id != 0
+            || matches!(
+                item.inner,
+                ItemEnum::Module(_) | ItemEnum::StructField(_) | ItemEnum::Variant(_)
+            )
+        {
+            return Ok(false);
+        }
+
+        let Some(span) = &item.span else {
+            return Ok(false);
+        };
+        let text = self
+            .source_text(span)
+            .ok_or_else(|| Error::SourceFileUnreadable(span.filename.clone()))?;
+        Ok(is_macro_invocation(&text))
+    }
+
+    /// The source code that `span` covers, or `None` if the source file can't
+    /// be read or is shorter than the span.
+    fn source_text(&mut self, span: &Span) -> Option<String> {
+        let lines = self.lines(&span.filename)?;
+        let (begin_line, begin_column) = span.begin;
+        let (end_line, end_column) = span.end;
+        let lines = lines.get(begin_line.checked_sub(1)?..end_line)?;
+
+        let mut text = String::new();
+        for (index, line) in lines.iter().enumerate() {
+            let end = if index == lines.len() - 1 {
+                end_column
+            } else {
+                usize::MAX
+            };
+            let begin = if index == 0 { begin_column } else { 0 };
+            text.extend(line.chars().take(end).skip(begin));
+            text.push('\n');
+        }
+        Some(text)
+    }
+
+    fn lines(&mut self, filename: &Path) -> Option<&[String]> {
+        let source_roots = &self.source_roots;
+        self.files
+            .entry(filename.to_owned())
+            .or_insert_with(|| {
+                let path = if filename.is_absolute() {
+                    filename.to_owned()
+                } else {
+                    source_roots
+                        .iter()
+                        .map(|root| root.join(filename))
+                        .find(|path| path.is_file())?
+                };
+                let source = std::fs::read_to_string(path).ok()?;
+                Some(source.lines().map(ToOwned::to_owned).collect())
+            })
+            .as_deref()
+    }
+}
+
+/// Returns `true` if `text` is a macro invocation such as `make_fn!(f)`, or
+/// only the path of a macro, such as `MyDerive` or `krate::MyDerive`. Item
+/// definitions always contain more than that, e.g. `pub fn f() {}`.
+fn is_macro_invocation(text: &str) -> bool {
+    let text = text.trim();
+    let path_len = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+        .unwrap_or(text.len());
+    let (path, rest) = text.split_at(path_len);
+    (rest.is_empty() || rest.starts_with('!'))
+        && RUST_KEYWORDS.binary_search(&path).is_err()
+        && path
+            .split("::")
+            .all(|segment| !segment.is_empty() && !segment.contains(':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_macro_invocation;
+
+    #[test]
+    fn macro_invocations() {
+        assert!(is_macro_invocation("make_fn!()"));
+        assert!(is_macro_invocation("krate::make_fn! { f }"));
+        assert!(is_macro_invocation("MyDerive"));
+        assert!(is_macro_invocation("krate::MyDerive\n"));
+
+        assert!(!is_macro_invocation("pub fn f() {}"));
+        assert!(!is_macro_invocation("pub struct S;"));
+        assert!(!is_macro_invocation("impl Trait for S {}"));
+        assert!(!is_macro_invocation("impl !Send for S {}"));
+        assert!(!is_macro_invocation("const A: u8;"));
+        assert!(!is_macro_invocation(""));
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::{public_item::PublicItemPath, PublicItem};
+
+/// The items of each module, and the position of the first item of the
+/// module, so that modules can be put in the order they were given.
+type Groups = BTreeMap<PublicItemPath, (usize, Vec<PublicItem>)>;
+
+/// The modules in each module, or in no module for `None`, together with the
+/// position of their first items.
+type Children = BTreeMap<Option<PublicItemPath>, Vec<(usize, PublicItemPath)>>;
+
+/// The items of a public API grouped by the module they are in, as a tree
+/// that mirrors the module hierarchy of the crate. Enables tree based
+/// rendering of an API. Create one with [`ModuleTree::from_items`],
+/// [`crate::Builder::build_module_tree`] or [`crate::public_api_module_tree`].
+///
+/// ```no_run
+/// # let public_api: public_api::PublicApi = todo!();
+/// use public_api::ModuleTree;
+///
+/// fn print(tree: &ModuleTree, indent: usize) {
+///     println!("{:indent$}mod {}", "", tree.name);
+///     for item in &tree.items {
+///         println!("{:indent$}  {item}", "");
+///     }
+///     for child in &tree.children {
+///         print(child, indent + 2);
+///     }
+/// }
+///
+/// print(&ModuleTree::from_items(public_api.into_items().collect()), 0);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModuleTree {
+    /// The name of the module, e.g. `inner` for `krate::outer::inner`. For
+    /// the root of the tree, this is the name of the crate.
+    pub name: String,
+
+    /// The items that are in the module, including the `pub mod` item of the
+    /// module itself, in the order they were given.
+    pub items: Vec<PublicItem>,
+
+    /// The modules that are in the module, in the order they were given.
+    pub children: Vec<ModuleTree>,
+}
+
+impl ModuleTree {
+    /// Groups `items` by the module they are in. An item is in the module
+    /// whose path is the longest prefix of the path of the item, so e.g. both
+    /// `krate::m::Struct` and `krate::m::Struct::method` are in `krate::m`.
+    /// Items that are in no module of `items` are put at the root of the tree.
+    #[must_use]
+    pub fn from_items(items: Vec<PublicItem>) -> Self {
+        let modules: HashSet<PublicItemPath> = items
+            .iter()
+            .filter(|item| item.kind_text() == Some("mod"))
+            .map(|item| item.path.clone())
+            .collect();
+
+        let mut groups = Groups::new();
+        for (position, item) in items.into_iter().enumerate() {
+            let own_path_len = if modules.contains(&item.path) {
+                item.path.len()
+            } else {
+                item.path.len().saturating_sub(1)
+            };
+            let module = (0..=own_path_len)
+                .rev()
+                .map(|len| &item.path[..len])
+                .find(|path| modules.contains(*path))
+                .unwrap_or_default();
+            groups
+                .entry(module.to_vec())
+                .or_insert_with(|| (position, vec![]))
+                .1
+                .push(item);
+        }
+
+        // The parent of a module is the module with the longest prefix of its
+        // path
+        let mut children = Children::new();
+        for (path, (position, _)) in &groups {
+            let parent = (0..path.len())
+                .rev()
+                .map(|len| &path[..len])
+                .find(|parent| groups.contains_key(*parent));
+            children
+                .entry(parent.map(<[String]>::to_vec))
+                .or_default()
+                .push((*position, path.clone()));
+        }
+        for siblings in children.values_mut() {
+            siblings.sort();
+        }
+
+        let mut roots: Vec<ModuleTree> = children
+            .get(&None)
+            .into_iter()
+            .flatten()
+            .map(|(_, path)| build(path, &mut groups, &children))
+            .collect();
+        if roots.len() == 1 {
+            roots.pop().unwrap()
+        } else {
+            ModuleTree {
+                children: roots,
+                ..ModuleTree::default()
+            }
+        }
+    }
+
+    /// All items of the tree as a flat list, sorted like
+    /// [`crate::Builder::sorted`] sorts them. For a tree of sorted items, this
+    /// reproduces the items that the tree was created from.
+    #[must_use]
+    pub fn flatten(&self) -> Vec<PublicItem> {
+        let mut items = vec![];
+        self.collect_items(&mut items);
+        items.sort_by(PublicItem::grouping_cmp);
+        items
+    }
+
+    fn collect_items(&self, items: &mut Vec<PublicItem>) {
+        items.extend(self.items.iter().cloned());
+        for child in &self.children {
+            child.collect_items(items);
+        }
+    }
+}
+
+/// Builds the [`ModuleTree`] of the module at `path`, taking its items from
+/// `groups`.
+fn build(path: &[String], groups: &mut Groups, children: &Children) -> ModuleTree {
+    let items = groups
+        .get_mut(path)
+        .map(std::mem::take)
+        .unwrap_or_default()
+        .1;
+    ModuleTree {
+        name: path.last().cloned().unwrap_or_default(),
+        items,
+        children: children
+            .get(&Some(path.to_vec()))
+            .into_iter()
+            .flatten()
+            .map(|(_, child)| build(child, groups, children))
+            .collect(),
+    }
+}
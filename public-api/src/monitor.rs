@@ -0,0 +1,143 @@
+use std::path::Path;
+use std::sync::mpsc::channel;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use notify_debouncer_mini::{
+    new_debouncer, notify::RecommendedWatcher, notify::RecursiveMode, DebounceEventResult,
+    Debouncer,
+};
+
+use crate::{diff::PublicApiDiff, Builder};
+
+/// Watches a rustdoc JSON file and diffs its public API against the public API
+/// it had when watching started, every time the file is modified. Intended for
+/// editor integrations that want to give live feedback about API changes.
+/// Requires the `monitor` feature.
+///
+/// ```no_run
+/// use public_api::PublicApiMonitor;
+///
+/// let handle = PublicApiMonitor::new("target/doc/example_api.json".as_ref()).watch(|diff| {
+///     println!("{diff:?}");
+/// })?;
+///
+/// // Later, when no more diffs are wanted
+/// handle.stop();
+/// # Ok::<(), public_api::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct PublicApiMonitor {
+    builder: Builder,
+    debounce: Duration,
+}
+
+impl PublicApiMonitor {
+    /// Create a new monitor for the given rustdoc JSON file. The public API is
+    /// built with the default [`Builder`] options. Use [`Self::from_builder`]
+    /// for other options.
+    #[must_use]
+    pub fn new(rustdoc_json_path: &Path) -> Self {
+        Self::from_builder(Builder::from_rustdoc_json(rustdoc_json_path))
+    }
+
+    /// Create a new monitor that builds the public API with `builder`.
+    #[must_use]
+    pub fn from_builder(builder: Builder) -> Self {
+        Self {
+            builder,
+            debounce: Duration::from_millis(500),
+        }
+    }
+
+    /// How long the file must remain unmodified before it is diffed. Makes sure
+    /// that a series of rapid modifications, such as when the file is being
+    /// written by `rustdoc`, only results in one diff.
+    ///
+    /// The default value is 500 ms.
+    #[must_use]
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Builds the initial public API and then starts watching the file for
+    /// file system notifications. `callback` is called on a background thread
+    /// with the diff every time the file has been modified and its public API
+    /// could be built. Modifications that result in e.g. invalid JSON are
+    /// ignored.
+    ///
+    /// Modifications made after this function returns are always noticed, also
+    /// if the file is replaced rather than written to.
+    ///
+    /// # Errors
+    ///
+    /// If the initial public API can't be built, or if the file can't be
+    /// watched.
+    pub fn watch(
+        self,
+        callback: impl Fn(PublicApiDiff) + Send + 'static,
+    ) -> crate::Result<PublicApiMonitorHandle> {
+        let baseline = self.builder.clone().build()?;
+
+        // Watch the directory rather than the file, so that we keep getting
+        // notifications if the file is deleted and re-created
+        let path = self.builder.rustdoc_json.canonicalize()?;
+        let dir = path.parent().unwrap_or(&path);
+        let (sender, receiver) = channel::<DebounceEventResult>();
+        let mut debouncer = new_debouncer(self.debounce, sender)?;
+        debouncer
+            .watcher()
+            .watch(dir, RecursiveMode::NonRecursive)?;
+
+        // Ends when the debouncer is dropped, since that disconnects the channel
+        let thread = std::thread::spawn(move || {
+            for events in receiver {
+                let modified = events.is_ok_and(|events| {
+                    events
+                        .iter()
+                        .any(|event| event.path.canonicalize().is_ok_and(|p| p == path))
+                });
+                if modified {
+                    if let Ok(new) = self.builder.clone().build() {
+                        callback(PublicApiDiff::between(baseline.clone(), new));
+                    }
+                }
+            }
+        });
+
+        Ok(PublicApiMonitorHandle {
+            debouncer: Some(debouncer),
+            thread: Some(thread),
+        })
+    }
+}
+
+/// Returned by [`PublicApiMonitor::watch`]. Watching stops when the handle is
+/// dropped or when [`Self::stop`] is called.
+#[derive(Debug)]
+pub struct PublicApiMonitorHandle {
+    debouncer: Option<Debouncer<RecommendedWatcher>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PublicApiMonitorHandle {
+    /// Stops watching, and waits for the background thread to finish.
+    pub fn stop(mut self) {
+        self.stop_and_join();
+    }
+
+    fn stop_and_join(&mut self) {
+        drop(self.debouncer.take());
+        if let Some(thread) = self.thread.take() {
+            // A panic in the callback has already been reported on stderr
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for PublicApiMonitorHandle {
+    fn drop(&mut self) {
+        self.stop_and_join();
+    }
+}
@@ -40,10 +40,13 @@ impl NameableItem<'_> {
             // In order for items of impls to be grouped together with its impl,
             // add the "name" of the impl to the sorting prefix. Ignore `!` when
             // sorting however, because that just messes the expected order up.
+            // Also ignore `unsafe`, so that going from `unsafe impl` to `impl`
+            // (or vice versa) shows up as a changed item in diffs rather than
+            // as one removed and one added item.
             sortable_name.push_str(&crate::tokens::tokens_to_string(&context.render_impl(
                 impl_,
                 &[],
-                true, /* disregard_negativity */
+                true, /* disregard_negativity_and_unsafety */
             )));
 
             // If this is an inherent impl, additionally add the concatenated
@@ -0,0 +1,110 @@
+use hashbag::HashBag;
+
+use crate::{PublicApi, PublicItem};
+
+/// The items of a public API as a multiset. Unlike a `Vec<PublicItem>` the
+/// order of the items does not matter, but unlike a `HashSet<PublicItem>`
+/// duplicates do: two items can render the same, e.g. due to limitations of
+/// rustdoc JSON, and they are still two items. Diff two sets with
+/// [`crate::diff::PublicApiDiff::between_sets`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PublicApiSet(HashBag<PublicItem>);
+
+impl PublicApiSet {
+    /// Creates an empty set.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a copy of `item`. Returns how many copies of the item the set had
+    /// before.
+    pub fn insert(&mut self, item: PublicItem) -> usize {
+        self.0.insert(item)
+    }
+
+    /// How many copies of `item` the set has. Zero if it has none.
+    #[must_use]
+    pub fn contains(&self, item: &PublicItem) -> usize {
+        self.0.contains(item)
+    }
+
+    /// Removes one copy of `item`. Returns how many copies of the item the set
+    /// had before.
+    pub fn remove(&mut self, item: &PublicItem) -> usize {
+        self.0.remove(item)
+    }
+
+    /// The number of items in the set, counting each copy.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the set has no items.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over the items of the set in arbitrary order, once for each
+    /// copy.
+    pub fn iter(&self) -> impl Iterator<Item = &PublicItem> {
+        self.0.iter()
+    }
+
+    /// The items of the set, sorted like [`crate::Builder::sorted`] sorts
+    /// them.
+    #[must_use]
+    #[allow(clippy::manual_repeat_n)] // `repeat_n()` needs Rust 1.82
+    pub fn into_vec(self) -> Vec<PublicItem> {
+        let mut items: Vec<PublicItem> = self
+            .0
+            .into_iter()
+            .flat_map(|(item, count)| std::iter::repeat(item).take(count))
+            .collect();
+        items.sort_by(PublicItem::grouping_cmp);
+        items
+    }
+}
+
+impl From<Vec<PublicItem>> for PublicApiSet {
+    fn from(items: Vec<PublicItem>) -> Self {
+        items.into_iter().collect()
+    }
+}
+
+impl From<PublicApi> for PublicApiSet {
+    fn from(public_api: PublicApi) -> Self {
+        public_api.items.into()
+    }
+}
+
+impl FromIterator<PublicItem> for PublicApiSet {
+    fn from_iter<I: IntoIterator<Item = PublicItem>>(items: I) -> Self {
+        Self(items.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PublicApiSet;
+    use crate::{public_item::test_item, tokens::Token, PublicItem};
+
+    #[test]
+    fn duplicates_are_counted() {
+        let mut set = PublicApiSet::from(vec![item("b"), item("a"), item("b")]);
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.contains(&item("b")), 2);
+        assert_eq!(set.contains(&item("c")), 0);
+
+        assert_eq!(set.remove(&item("b")), 2);
+        assert_eq!(set.insert(item("c")), 0);
+        assert_eq!(set.iter().count(), 3);
+        assert_eq!(set.into_vec(), [item("a"), item("b"), item("c")]);
+    }
+
+    fn item(name: &str) -> PublicItem {
+        test_item([name], vec![Token::identifier(name)])
+    }
+}
@@ -1,11 +1,14 @@
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::fmt::Display;
 use std::hash::Hash;
+use std::sync::Arc;
 
 use crate::intermediate_public_item::IntermediatePublicItem;
+use crate::item_processor::TRAIT_SORTING_PREFIX;
 use crate::render::RenderingContext;
 use crate::tokens::tokens_to_string;
-use crate::tokens::Token;
+use crate::tokens::{normalize_attribute_order, normalize_whitespace, Token};
 
 /// Each public item (except `impl`s) have a path that is displayed like
 /// `first::second::third`. Internally we represent that with a `vec!["first",
@@ -17,13 +20,30 @@ pub(crate) type PublicItemPath = Vec<String>;
 /// of the public API of a crate. Implements [`Display`] so it can be printed. It
 /// also implements [`Ord`], but how items are ordered are not stable yet, and
 /// will change in later versions.
-#[derive(Clone)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct PublicItem {
     /// Read [`crate::item_processor::sorting_prefix()`] docs for more info
     pub(crate) sortable_path: PublicItemPath,
 
-    /// The rendered item as a stream of [`Token`]s
-    pub(crate) tokens: Vec<Token>,
+    /// The path of the item as shown to users, including the name of the
+    /// crate, but without any `impl`s.
+    pub(crate) path: PublicItemPath,
+
+    /// See [`Self::associated_item_of`]
+    pub(crate) associated_item_of: Option<PublicItemPath>,
+
+    /// See [`Self::enclosing_type`]
+    #[serde(default)]
+    pub(crate) enclosing_type: Option<PublicItemPath>,
+
+    /// See [`Self::cfg_conditions`]
+    #[serde(default)]
+    pub(crate) cfg_conditions: Vec<String>,
+
+    /// The rendered item as a stream of [`Token`]s. Reference counted so that
+    /// cloning an item, and thus a [`crate::diff::PublicApiDiff`], is cheap.
+    pub(crate) tokens: Arc<[Token]>,
 }
 
 impl PublicItem {
@@ -33,7 +53,11 @@ impl PublicItem {
     ) -> PublicItem {
         PublicItem {
             sortable_path: public_item.sortable_path(context),
-            tokens: public_item.render_token_stream(context),
+            path: public_item.display_path(),
+            associated_item_of: public_item.associated_item_of(),
+            enclosing_type: public_item.enclosing_type(),
+            cfg_conditions: public_item.cfg_conditions(),
+            tokens: public_item.render_token_stream(context).into(),
         }
     }
 
@@ -42,6 +66,525 @@ impl PublicItem {
         self.tokens.iter()
     }
 
+    /// The path of the item, for example `["krate", "Struct", "method"]` for
+    /// `krate::Struct::method`.
+    #[must_use]
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// How deeply nested the item is, not counting the crate itself. For
+    /// example, the depth of `krate::Struct` is 1 and the depth of
+    /// `krate::Struct::method` is 2. The crate root module has depth 0.
+    #[must_use]
+    pub fn path_depth(&self) -> usize {
+        self.path.len().saturating_sub(1)
+    }
+
+    /// The path of the type or trait that the item is an associated item of.
+    /// For example, `Some(["krate", "Struct"])` for `krate::Struct::method`
+    /// when `method` is defined in an `impl Struct` block. `None` for items
+    /// that are not associated items, such as free functions.
+    #[must_use]
+    pub fn associated_item_of(&self) -> Option<&[String]> {
+        self.associated_item_of.as_deref()
+    }
+
+    /// The path of the type or trait that the item belongs to, so that items
+    /// can be grouped by their parent. For example `Some(["krate", "Enum"])`
+    /// for the variant `krate::Enum::Variant`, and `Some(["krate", "Trait"])`
+    /// for the associated type `krate::Trait::Associated`. Unlike
+    /// [`Self::associated_item_of`], this includes enum variants and struct
+    /// and union fields. `None` for items that do not belong to a type, such
+    /// as modules and free functions.
+    #[must_use]
+    pub fn enclosing_type(&self) -> Option<&[String]> {
+        self.enclosing_type.as_deref()
+    }
+
+    /// Returns `true` if the item has any lifetime parameters. See
+    /// [`Self::lifetime_param_count`].
+    #[must_use]
+    pub fn has_lifetime_params(&self) -> bool {
+        self.lifetime_param_count() > 0
+    }
+
+    /// The number of distinct named lifetimes of the item, e.g. 2 for `pub fn
+    /// f<'a, 'b>(x: &'a u8, y: &'b u8)`. The `'static` and `'_` lifetimes are
+    /// not counted, since they are not parameters. Lifetimes are found by
+    /// scanning the [`Token::Lifetime`]s of the item, so for e.g. a method of
+    /// `impl<'a> Foo<'a>` that mentions `'a`, the `'a` of the `impl` is
+    /// counted.
+    #[must_use]
+    pub fn lifetime_param_count(&self) -> usize {
+        self.tokens
+            .iter()
+            .filter_map(|token| match token {
+                Token::Lifetime(name) if name != "'static" && name != "'_" => Some(name),
+                _ => None,
+            })
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// The length in bytes of the text of the item, i.e. of its [`Display`]
+    /// form, as a rough measure of how complex the item is. E.g. 17 for `pub fn
+    /// krate::f()`. Useful to find the most complex items of a public API, or
+    /// to track how complex the items of a public API get over time.
+    #[must_use]
+    pub fn size_hint(&self) -> usize {
+        self.tokens.iter().map(|token| token.text().len()).sum()
+    }
+
+    /// Returns `true` if the item is an `unsafe impl`, such as `unsafe impl
+    /// Send for Foo`. An `unsafe impl` upholds guarantees that safe code may
+    /// rely on, so it is worth auditing changes to such items separately from
+    /// changes to regular items.
+    #[must_use]
+    pub fn is_unsafe_impl(&self) -> bool {
+        self.tokens.windows(3).any(|window| {
+            window
+                == [
+                    Token::keyword("unsafe"),
+                    Token::Whitespace,
+                    Token::ImplKeyword,
+                ]
+        })
+    }
+
+    /// How similar this item is to `other`, from `0.0` (nothing in common) to
+    /// `1.0` (identical). Computed as the Jaccard similarity of the sets of
+    /// tokens of the two items. Used to figure out which removed item an added
+    /// item replaced, if many items have the same path.
+    #[must_use]
+    pub fn similarity_score(&self, other: &Self) -> f64 {
+        jaccard_similarity(
+            &self.tokens.iter().collect(),
+            &other.tokens.iter().collect(),
+        )
+    }
+
+    /// Returns `true` if this item and `other` are equal when the order of
+    /// their attributes is disregarded, e.g. if they only differ in
+    /// `#[derive(Debug, Clone)]` versus `#[derive(Clone, Debug)]`. Unlike `==`,
+    /// which compares tokens as they are. See also
+    /// [`crate::Builder::normalize_attribute_order`].
+    #[must_use]
+    pub fn semantically_equal(&self, other: &Self) -> bool {
+        self == other
+            || normalize_attribute_order(self.tokens.to_vec())
+                == normalize_attribute_order(other.tokens.to_vec())
+    }
+
+    /// Puts the item on a canonical form, so that items that different
+    /// compiler versions render slightly differently become equal. Useful
+    /// before diffing public APIs that were built with different toolchains.
+    /// The rules are applied in this order:
+    ///
+    /// 1. Whitespace is normalized like [`crate::Builder::normalize_whitespace`]
+    ///    normalizes it.
+    /// 2. Attributes are sorted like [`crate::Builder::normalize_attribute_order`]
+    ///    sorts them.
+    /// 3. The `Self` type is expanded to the type of the `impl` the item
+    ///    belongs to, e.g. `pub fn krate::Foo<T>::new() -> Self` becomes `pub
+    ///    fn krate::Foo<T>::new() -> krate::Foo<T>`. Items of traits keep
+    ///    `Self`, since it is not known what type it is.
+    ///
+    /// Normalizing an item that already is on canonical form does not change
+    /// it.
+    pub fn normalize(&mut self) {
+        let tokens = normalize_whitespace(self.tokens.to_vec());
+        let tokens = normalize_attribute_order(tokens);
+        let tokens = self.expand_self_type(tokens);
+        self.tokens = tokens.into();
+    }
+
+    /// Replaces each [`Token::SelfType`] in `tokens` with the tokens of the
+    /// type of the `impl` that the item belongs to. The type is taken from the
+    /// path of the item itself, e.g. `krate::Foo<T>` in `pub fn
+    /// krate::Foo<T>::new() -> Self`, so that generic arguments are kept.
+    pub(crate) fn expand_self_type(&self, tokens: Vec<Token>) -> Vec<Token> {
+        if self.associated_item_of.is_none() || self.is_trait_item() {
+            return tokens;
+        }
+        let Some(self_type) = self_type_range(&tokens, &self.path) else {
+            return tokens;
+        };
+
+        let self_type = tokens[self_type].to_vec();
+        let mut expanded = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            if token == Token::SelfType {
+                expanded.extend(self_type.iter().cloned());
+            } else {
+                expanded.push(token);
+            }
+        }
+        expanded
+    }
+
+    /// Returns `true` if the item is an associated item of a trait, such as
+    /// `pub fn krate::Trait::method(&self)`, rather than of an `impl`.
+    pub(crate) fn is_trait_item(&self) -> bool {
+        self.associated_item_of.is_some()
+            && self
+                .sortable_path
+                .iter()
+                .rev()
+                .nth(1)
+                .is_some_and(|parent| parent.starts_with(&format!("{TRAIT_SORTING_PREFIX:0>3}-")))
+    }
+
+    /// Like [`Self::similarity_score`], but the tokens that make up the path
+    /// of the respective item, such as the name of the item itself, are
+    /// disregarded, and the order of the tokens matters. Computed from the
+    /// longest common subsequence of the remaining tokens. Used to detect
+    /// renamed and moved items.
+    pub(crate) fn similarity_score_ignoring_path(&self, other: &Self) -> f64 {
+        sequence_similarity(&self.tokens_outside_path(), &other.tokens_outside_path())
+    }
+
+    /// The tokens of the item without the tokens of its path, e.g. `pub fn
+    /// (x: u8)` for `pub fn krate::f(x: u8)`. Only the occurrence of the path
+    /// that matches most of it is removed, so other tokens with the same text
+    /// as a path component, like a parameter type, are kept.
+    fn tokens_outside_path(&self) -> Vec<&Token> {
+        let mut path_positions = vec![];
+        for start in 0..self.tokens.len() {
+            let positions = path_positions_at(&self.tokens, start, &self.path);
+            if positions.len() > path_positions.len() {
+                path_positions = positions;
+            }
+        }
+
+        self.tokens
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !path_positions.contains(index))
+            .map(|(_, token)| token)
+            .collect()
+    }
+
+    /// Returns `true` if the item is a function, such as `pub fn krate::f()`.
+    /// Methods and associated functions are also functions.
+    #[inline]
+    #[must_use]
+    pub fn is_function(&self) -> bool {
+        self.kind_text() == Some("fn")
+    }
+
+    /// Returns `true` if the item is a struct, such as `pub struct
+    /// krate::Struct`.
+    #[inline]
+    #[must_use]
+    pub fn is_struct(&self) -> bool {
+        self.kind_text() == Some("struct")
+    }
+
+    /// Returns `true` if the item is an enum, such as `pub enum krate::Enum`.
+    #[inline]
+    #[must_use]
+    pub fn is_enum(&self) -> bool {
+        self.kind_text() == Some("enum")
+    }
+
+    /// Returns `true` if the item is a trait, such as `pub trait
+    /// krate::Trait`. Trait aliases are also traits.
+    #[inline]
+    #[must_use]
+    pub fn is_trait(&self) -> bool {
+        self.kind_text() == Some("trait")
+    }
+
+    /// Returns `true` if the item is a `type` item, such as `pub type
+    /// krate::Alias = u8`. Associated types are also type aliases.
+    #[inline]
+    #[must_use]
+    pub fn is_type_alias(&self) -> bool {
+        self.kind_text() == Some("type")
+    }
+
+    /// The number of parameters of a function, or `None` if the item is not a
+    /// function. Receivers such as `&self` count as parameters, so both `pub
+    /// fn krate::f(x: u8)` and `pub fn krate::Struct::m(&self)` have one
+    /// parameter. A function without parameters has `Some(0)`.
+    #[must_use]
+    pub fn param_count(&self) -> Option<usize> {
+        let (open, close) = self.param_list_bounds()?;
+        let params = &self.tokens[open + 1..close];
+
+        // Count top level commas
+        let mut depth = 0_usize;
+        let mut commas = 0;
+        for token in params {
+            match (token.text(), depth) {
+                ("(" | "[" | "<", _) => depth += 1,
+                (")" | "]" | ">", _) => depth = depth.saturating_sub(1),
+                (",", 0) => commas += 1,
+                _ => {}
+            }
+        }
+        Some(if params.is_empty() { 0 } else { commas + 1 })
+    }
+
+    /// The tokens of the return type of a function, e.g. the tokens of
+    /// `Result<T, E>` for `pub fn krate::f() -> Result<T, E>`. `None` if the
+    /// item is not a function, or if the function implicitly returns `()`.
+    /// The `->` and any trailing `where` clause are not included.
+    #[must_use]
+    pub fn return_type_tokens(&self) -> Option<&[Token]> {
+        let (_, close) = self.param_list_bounds()?;
+        let after_params = &self.tokens[close + 1..];
+        let arrow = after_params
+            .iter()
+            .position(|token| *token == Token::operator("->"))?;
+        // The `->` must directly follow the parameter list, so that e.g. the
+        // `->` of a `where F: Fn() -> u8` clause is not mistaken for it
+        if after_params[..arrow]
+            .iter()
+            .any(|token| *token != Token::Whitespace)
+        {
+            return None;
+        }
+
+        let return_type = &after_params[arrow + 1..];
+        let end = return_type
+            .iter()
+            .position(|token| matches!(token, Token::Keyword(k) if k == "where"))
+            .unwrap_or(return_type.len());
+        let return_type = &return_type[..end];
+        let leading = return_type
+            .iter()
+            .take_while(|token| **token == Token::Whitespace)
+            .count();
+        let trailing = return_type[leading..]
+            .iter()
+            .rev()
+            .take_while(|token| **token == Token::Whitespace)
+            .count();
+        Some(&return_type[leading..return_type.len() - trailing])
+    }
+
+    /// The indices of the `(` and `)` that enclose the parameters of a
+    /// function, or `None` if the item is not a function.
+    fn param_list_bounds(&self) -> Option<(usize, usize)> {
+        if !self.is_function() {
+            return None;
+        }
+
+        // Skip past the name of the function, so that the parentheses of
+        // e.g. `#[derive(...)]` or `impl Fn(u8)` types are not mistaken for
+        // the parameter list
+        let name = self.path.last()?;
+        let mut tokens = self
+            .tokens
+            .iter()
+            .enumerate()
+            .skip_while(|(_, token)| !matches!(token, Token::Function(text) if text == name))
+            .skip(1);
+
+        // Skip generic parameters, e.g. `<F: Fn(u8)>`
+        let mut depth = 0_usize;
+        let open = loop {
+            let (index, token) = tokens.next()?;
+            match token.text() {
+                "<" => depth += 1,
+                ">" => depth = depth.saturating_sub(1),
+                "(" if depth == 0 => break index,
+                _ => {}
+            }
+        };
+
+        // Find the matching closing parenthesis
+        for (index, token) in tokens {
+            match (token.text(), depth) {
+                (")", 0) => return Some((open, index)),
+                ("(" | "[" | "<", _) => depth += 1,
+                (")" | "]" | ">", _) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// The first line of the documentation comment of the item, without the
+    /// leading `///`. For example `Some("Does a thing.")` for an item
+    /// documented with `/// Does a thing.`. `None` if the item has no docs,
+    /// which is always the case unless [`crate::Builder::include_docs`] is
+    /// enabled.
+    #[must_use]
+    pub fn doc_comment(&self) -> Option<String> {
+        let Some(Token::Doc(docs)) = self.tokens.iter().find(|t| matches!(t, Token::Doc(_))) else {
+            return None;
+        };
+        let first_line = docs.lines().next()?.trim_start_matches("///");
+        Some(
+            first_line
+                .strip_prefix(' ')
+                .unwrap_or(first_line)
+                .to_owned(),
+        )
+    }
+
+    /// The conditions of the `#[cfg(...)]` attributes of the item and of the
+    /// items it is in, such as its module, outermost first. For example
+    /// `feature = "a"` and `unix` for an item with `#[cfg(unix)]` in a module
+    /// with `#[cfg(feature = "a")]`. Since items whose conditions are false
+    /// are not part of the public API, all conditions are true for the build
+    /// that produced the item.
+    #[must_use]
+    pub fn cfg_conditions(&self) -> &[String] {
+        &self.cfg_conditions
+    }
+
+    /// The features that must be enabled for the item to exist, as given by
+    /// [`Self::cfg_conditions`] of the form `feature = "name"`, also when
+    /// combined with `all(...)`. Conditions such as `any(...)` and `not(...)`
+    /// do not require any specific feature and are left out. For example
+    /// `["a", "b"]` for an item with `#[cfg(all(feature = "a", feature =
+    /// "b"))]`.
+    #[must_use]
+    pub fn feature_gates(&self) -> Vec<String> {
+        let mut features = vec![];
+        for condition in &self.cfg_conditions {
+            for feature in required_features(condition) {
+                if !features.contains(&feature) {
+                    features.push(feature);
+                }
+            }
+        }
+        features
+    }
+
+    /// The [`Self::cfg_conditions`] as a single `#[cfg(...)]` attribute, e.g.
+    /// `#[cfg(feature = "a")]` or `#[cfg(all(feature = "a", unix))]`. `None`
+    /// if the item has no conditions.
+    #[must_use]
+    pub fn cfg_attribute(&self) -> Option<String> {
+        match self.cfg_conditions.as_slice() {
+            [] => None,
+            [condition] => Some(format!("#[cfg({condition})]")),
+            conditions => Some(format!("#[cfg(all({}))]", conditions.join(", "))),
+        }
+    }
+
+    /// The bounds on the generic parameters of the item, both inline, as in
+    /// `pub fn f<T: Clone + Debug>(t: T)`, and in `where` clauses, as in `pub
+    /// fn f<T>(t: T) where T: Clone`. Parameters without bounds are left out,
+    /// and so are const generics. Lifetime bounds such as `'a: 'static` are
+    /// included. The traits are rendered like the rest of the item, i.e. with
+    /// their full paths, e.g. `core::clone::Clone`.
+    #[must_use]
+    pub fn bounds(&self) -> Vec<Bound> {
+        let mut bounds = vec![];
+
+        // The generic parameters follow the name of the item, or the `impl`
+        // keyword for `impl`s
+        let name = self.path.last().map(String::as_str);
+        let generics_start = self.tokens.windows(2).position(|window| {
+            (Some(window[0].text()) == name || window[0] == Token::ImplKeyword)
+                && window[1].text() == "<"
+        });
+        if let Some(start) = generics_start {
+            let params = split_top_level(&self.tokens[start + 2..], ",", ">");
+            bounds.extend(
+                params
+                    .into_iter()
+                    .filter(|param| !param.contains(&Token::qualifier("const")))
+                    .filter_map(bound),
+            );
+        }
+
+        let where_start = self
+            .tokens
+            .iter()
+            .position(|t| *t == Token::keyword("where"));
+        if let Some(start) = where_start {
+            let predicates = split_top_level(&self.tokens[start + 1..], ",", "=");
+            bounds.extend(predicates.into_iter().filter_map(bound));
+        }
+
+        bounds
+    }
+
+    /// If the item is a trait implementation such as `impl<T> Trait<T> for
+    /// &Type`, the trait and the type it is implemented for. `None` for all
+    /// other items, including inherent `impl`s such as `impl Type`. The
+    /// `trait_path` does not include any generic arguments of the trait.
+    #[must_use]
+    pub fn as_trait_impl(&self) -> Option<TraitImpl> {
+        if self.kind_text().is_some() {
+            return None;
+        }
+
+        let impl_keyword = self.tokens.iter().position(|t| *t == Token::ImplKeyword)?;
+        let mut rest = &self.tokens[impl_keyword + 1..];
+        if rest.first() == Some(&Token::GenericOpen) {
+            // Skip past the generic parameters of the `impl`
+            let generics_end = position_outside_generics(rest, |t| *t == Token::GenericClose)?;
+            rest = &rest[generics_end + 1..];
+        }
+        let for_keyword = position_outside_generics(rest, |t| *t == Token::keyword("for"))?;
+
+        let trait_path = rest[..for_keyword]
+            .iter()
+            .take_while(|t| **t != Token::GenericOpen)
+            .filter_map(|t| match t {
+                Token::Identifier(name) | Token::Type(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let for_type = &rest[for_keyword + 1..];
+        let where_keyword = for_type
+            .iter()
+            .position(|t| *t == Token::keyword("where"))
+            .unwrap_or(for_type.len());
+        Some(TraitImpl {
+            trait_path,
+            for_type: tokens_to_string(&for_type[..where_keyword])
+                .trim()
+                .to_owned(),
+        })
+    }
+
+    /// See [`crate::PublicApi::rename_items`]
+    pub(crate) fn renamed(&self, rename: &impl Fn(&str) -> String) -> Self {
+        let rename_all = |path: &PublicItemPath| path.iter().map(|name| rename(name)).collect();
+        PublicItem {
+            sortable_path: rename_all(&self.sortable_path),
+            path: rename_all(&self.path),
+            associated_item_of: self.associated_item_of.as_ref().map(rename_all),
+            enclosing_type: self.enclosing_type.as_ref().map(rename_all),
+            cfg_conditions: self.cfg_conditions.clone(),
+            tokens: self
+                .tokens
+                .iter()
+                .map(|token| match token {
+                    Token::Identifier(name) => Token::Identifier(rename(name)),
+                    Token::Type(name) => Token::Type(rename(name)),
+                    Token::ImplTrait(name) => Token::ImplTrait(rename(name)),
+                    Token::Function(name) => Token::Function(rename(name)),
+                    token => token.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// The text of the first [`Token::Kind`] of the item, e.g. `"struct"` for
+    /// `pub struct Foo`. Items such as `impl`s have no kind.
+    pub(crate) fn kind_text(&self) -> Option<&str> {
+        self.tokens
+            .iter()
+            .find(|token| token.is_kind())
+            .map(Token::text)
+    }
+
+    /// The path of the item as shown to users, e.g. `krate::Struct::method`.
+    pub(crate) fn path_string(&self) -> String {
+        self.path.join("::")
+    }
+
     /// Special version of [`cmp`](Ord::cmp) that is used to sort public items in a way that
     /// makes them grouped logically. For example, struct fields will be put
     /// right after the struct they are part of.
@@ -55,11 +598,81 @@ impl PublicItem {
         // Fall back to lexical sorting if the above is not sufficient
         self.to_string().cmp(&other.to_string())
     }
+
+    /// Compares the paths of the items, e.g. `krate::Struct::method`, and
+    /// nothing else. Unlike [`PublicItem::grouping_cmp`], items whose
+    /// signatures differ but that have the same path compare equal, so the
+    /// ordering is stable across signature changes.
+    #[must_use]
+    pub fn path_cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.path.cmp(&other.path)
+    }
+}
+
+/// A bound on a generic parameter of a [`PublicItem`], as returned by
+/// [`PublicItem::bounds`]. For example `T: Clone + Debug` has the `param` `T`
+/// and the `traits` `Clone` and `Debug`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Bound {
+    /// The bounded parameter or type, e.g. `T` or `&'a T`.
+    pub param: String,
+
+    /// The bounds of the parameter, in the order they are written.
+    pub traits: Vec<String>,
+}
+
+/// A trait implementation, as returned by [`PublicItem::as_trait_impl`]. For
+/// example `impl<T> core::clone::Clone for &krate::Type<T>` has the
+/// `trait_path` `["core", "clone", "Clone"]` and the `for_type`
+/// `&krate::Type<T>`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TraitImpl {
+    /// The path of the implemented trait, without generic arguments.
+    pub trait_path: Vec<String>,
+
+    /// The type the trait is implemented for, rendered like the rest of the
+    /// item.
+    pub for_type: String,
+}
+
+/// Wraps a [`PublicItem`] so that it is compared, ordered and hashed by
+/// [`PublicItem::path_cmp`] only. Useful to deduplicate or index items by
+/// path, e.g. in a `BTreeSet<PublicItemsByPath>`.
+#[derive(Clone, Debug)]
+pub struct PublicItemsByPath(pub PublicItem);
+
+impl PartialEq for PublicItemsByPath {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.path == other.0.path
+    }
+}
+
+impl Eq for PublicItemsByPath {}
+
+impl PartialOrd for PublicItemsByPath {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PublicItemsByPath {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.path_cmp(&other.0)
+    }
+}
+
+impl Hash for PublicItemsByPath {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.path.hash(state);
+    }
 }
 
+/// Items are equal if they look the same and are gated behind the same
+/// [`PublicItem::cfg_conditions`], so that e.g. an item that moved from one
+/// feature to another shows up as changed in a diff.
 impl PartialEq for PublicItem {
     fn eq(&self, other: &Self) -> bool {
-        self.tokens == other.tokens
+        self.tokens == other.tokens && self.cfg_conditions == other.cfg_conditions
     }
 }
 
@@ -68,14 +681,33 @@ impl Eq for PublicItem {}
 impl Hash for PublicItem {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.tokens.hash(state);
+        self.cfg_conditions.hash(state);
     }
 }
 
-/// We want pretty-printing (`"{:#?}"`) of [`crate::diff::PublicApiDiff`] to print
-/// each public item as `Display`, so implement `Debug` with `Display`.
+/// The tokens of an item are many and verbose, so `"{:?}"` only shows the path
+/// and the number of tokens, e.g. `PublicItem { path: "krate::Struct", tokens:
+/// [5 tokens] }`, to keep test failure messages readable. Pretty-printing
+/// (`"{:#?}"`) shows all fields, including every token.
 impl std::fmt::Debug for PublicItem {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        std::fmt::Display::fmt(self, f)
+        let alternate = f.alternate();
+        let mut debug = f.debug_struct("PublicItem");
+        debug.field("path", &self.path_string());
+        if alternate {
+            debug
+                .field("sortable_path", &self.sortable_path)
+                .field("associated_item_of", &self.associated_item_of)
+                .field("enclosing_type", &self.enclosing_type)
+                .field("cfg_conditions", &self.cfg_conditions)
+                .field("tokens", &self.tokens)
+                .finish()
+        } else {
+            let tokens = self.tokens.len();
+            debug
+                .field("tokens", &format_args!("[{tokens} tokens]"))
+                .finish()
+        }
     }
 }
 
@@ -87,6 +719,213 @@ impl Display for PublicItem {
     }
 }
 
+/// The number of tokens the sets have in common, divided by the number of
+/// distinct tokens in total. Two empty sets are considered identical.
+fn jaccard_similarity(a: &HashSet<&Token>, b: &HashSet<&Token>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+
+    #[allow(clippy::cast_precision_loss)] // Token counts are small
+    let score = a.intersection(b).count() as f64 / union as f64;
+    score
+}
+
+/// Twice the length of the longest common subsequence of `a` and `b`,
+/// divided by their total length. Two empty sequences are considered
+/// identical.
+fn sequence_similarity(a: &[&Token], b: &[&Token]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    // Only the previous row of the length matrix is needed
+    let mut previous = vec![0_usize; b.len() + 1];
+    for a_token in a {
+        let mut current = vec![0];
+        for (j, b_token) in b.iter().enumerate() {
+            current.push(if a_token == b_token {
+                previous[j] + 1
+            } else {
+                previous[j + 1].max(current[j])
+            });
+        }
+        previous = current;
+    }
+
+    #[allow(clippy::cast_precision_loss)] // Token counts are small
+    let score = (2 * previous[b.len()]) as f64 / (a.len() + b.len()) as f64;
+    score
+}
+
+/// The positions of the tokens of `path`, e.g. `krate`, `::` and `f` in `pub
+/// fn krate::f()`, if the tokens from `start` on are the path, or as many of
+/// its first components as match. Generic arguments, like `<T>` in
+/// `krate::Foo<T>::new`, are not part of the path.
+fn path_positions_at(tokens: &[Token], start: usize, path: &[String]) -> Vec<usize> {
+    let mut positions = vec![];
+    let mut index = start;
+    for (n, component) in path.iter().enumerate() {
+        let mut separator = None;
+        if n > 0 {
+            if tokens.get(index) == Some(&Token::GenericOpen) {
+                let Some(close) = position_outside_generics(&tokens[index..], |token| {
+                    *token == Token::GenericClose
+                }) else {
+                    break;
+                };
+                index += close + 1;
+            }
+            if tokens.get(index) != Some(&Token::PathSeparator) {
+                break;
+            }
+            separator = Some(index);
+            index += 1;
+        }
+        if tokens.get(index).map(Token::text) != Some(component.as_str()) {
+            break;
+        }
+        positions.extend(separator);
+        positions.push(index);
+        index += 1;
+    }
+    positions
+}
+
+/// The features that `condition`, the inside of a `#[cfg(...)]`, requires to
+/// be enabled. See [`PublicItem::feature_gates`].
+fn required_features(condition: &str) -> Vec<String> {
+    let condition = condition.trim();
+    if let Some(value) = condition.strip_prefix("feature") {
+        let value = value.trim_start();
+        return value
+            .strip_prefix('=')
+            .map(str::trim)
+            .and_then(|value| value.strip_prefix('"')?.strip_suffix('"'))
+            .map(|name| vec![name.to_owned()])
+            .unwrap_or_default();
+    }
+
+    let Some(inner) = condition
+        .strip_prefix("all(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    else {
+        return vec![];
+    };
+
+    // Split at the commas that are not nested within parentheses or strings
+    let mut features = vec![];
+    let mut depth = 0_usize;
+    let mut in_string = false;
+    let mut start = 0;
+    for (index, c) in inner.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '(' if !in_string => depth += 1,
+            ')' if !in_string => depth = depth.saturating_sub(1),
+            ',' if !in_string && depth == 0 => {
+                features.extend(required_features(&inner[start..index]));
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    features.extend(required_features(&inner[start..]));
+    features
+}
+
+/// Parses a bound such as `T: Clone + Debug`. Returns `None` for unbounded
+/// parameters such as `T`. A default such as `T: Clone = u8` is ignored.
+fn bound(tokens: &[Token]) -> Option<Bound> {
+    let colon = tokens.iter().position(|t| t.text() == ":")?;
+    let traits = split_top_level(&tokens[colon + 1..], "+", "=")
+        .into_iter()
+        .map(|tokens| tokens_to_string(tokens).trim().to_owned())
+        .filter(|text| !text.is_empty())
+        .collect();
+    Some(Bound {
+        param: tokens_to_string(&tokens[..colon]).trim().to_owned(),
+        traits,
+    })
+}
+
+/// Splits `tokens` at each `separator` that is not nested within brackets,
+/// until an unnested `terminator` or closing bracket is found.
+fn split_top_level<'a>(tokens: &'a [Token], separator: &str, terminator: &str) -> Vec<&'a [Token]> {
+    let mut parts = vec![];
+    let mut depth = 0_usize;
+    let mut start = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        match token.text() {
+            text if depth == 0 && text == terminator => {
+                parts.push(&tokens[start..index]);
+                return parts;
+            }
+            text if depth == 0 && text == separator => {
+                parts.push(&tokens[start..index]);
+                start = index + 1;
+            }
+            "<" | "(" | "[" => depth += 1,
+            ">" | ")" | "]" if depth == 0 => {
+                parts.push(&tokens[start..index]);
+                return parts;
+            }
+            ">" | ")" | "]" => depth -= 1,
+            _ => {}
+        }
+    }
+    parts.push(&tokens[start..]);
+    parts
+}
+
+/// The position of the first token that matches `predicate` and is not within
+/// `<` and `>`. A `>` matches when it closes the outermost `<`.
+fn position_outside_generics(
+    tokens: &[Token],
+    predicate: impl Fn(&Token) -> bool,
+) -> Option<usize> {
+    let mut depth = 0_usize;
+    tokens.iter().position(|token| {
+        match token {
+            Token::GenericOpen => depth += 1,
+            Token::GenericClose => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        depth == 0 && predicate(token)
+    })
+}
+
+/// The range of `tokens` that make up the type that the item with `path`
+/// belongs to, e.g. the tokens of `krate::Foo<T>` in `pub fn
+/// krate::Foo<T>::new() -> Self`. That is, everything from the first component
+/// of `path` to the `::` before the name of the item. Any `::` within generic
+/// arguments is not a separator of `path`.
+fn self_type_range(tokens: &[Token], path: &[String]) -> Option<std::ops::Range<usize>> {
+    let (_, parents) = path.split_last()?;
+    let first = parents.first()?;
+    let start = tokens
+        .iter()
+        .position(|token| matches!(token, Token::Identifier(text) if text == first))?;
+
+    let mut depth = 0_usize;
+    let mut separators = 0;
+    for (index, token) in tokens.iter().enumerate().skip(start) {
+        match token.text() {
+            "<" => depth += 1,
+            ">" => depth = depth.saturating_sub(1),
+            "::" if depth == 0 => {
+                separators += 1;
+                if separators == parents.len() {
+                    return Some(start..index);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 /// Returns `None` if two items are equal. Otherwise their ordering is returned.
 fn different_or_none<T: Ord>(a: &T, b: &T) -> Option<Ordering> {
     match a.cmp(b) {
@@ -94,3 +933,318 @@ fn different_or_none<T: Ord>(a: &T, b: &T) -> Option<Ordering> {
         c => Some(c),
     }
 }
+
+/// An item with the given `path` and `tokens` for tests, e.g. `test_item(["a",
+/// "f"], vec![..])`. The sortable path is the same as the path.
+#[cfg(test)]
+pub(crate) fn test_item(
+    path: impl IntoIterator<Item = impl Into<String>>,
+    tokens: Vec<Token>,
+) -> PublicItem {
+    let path: PublicItemPath = path.into_iter().map(Into::into).collect();
+    PublicItem {
+        sortable_path: path.clone(),
+        path,
+        associated_item_of: None,
+        enclosing_type: None,
+        cfg_conditions: vec![],
+        tokens: tokens.into(),
+    }
+}
+
+/// A function for tests with the tokens that a real function is rendered
+/// with, e.g. `pub fn krate::f(x: u8)` for `test_fn(&["krate", "f"], &[("x",
+/// "u8")])`. Parameters are given as pairs of names and primitive types.
+#[cfg(test)]
+pub(crate) fn test_fn(path: &[&str], params: &[(&str, &str)]) -> PublicItem {
+    let (name, parents) = path.split_last().unwrap();
+    let mut tokens = vec![
+        Token::qualifier("pub"),
+        Token::Whitespace,
+        Token::FnKeyword,
+        Token::Whitespace,
+    ];
+    for parent in parents {
+        tokens.extend([Token::identifier(*parent), Token::path_separator()]);
+    }
+    tokens.extend([Token::function(*name), Token::symbol("(")]);
+    for (index, (param, type_)) in params.iter().enumerate() {
+        if index > 0 {
+            tokens.extend([Token::symbol(","), Token::Whitespace]);
+        }
+        tokens.extend([
+            Token::identifier(*param),
+            Token::symbol(":"),
+            Token::Whitespace,
+            Token::primitive(*type_),
+        ]);
+    }
+    tokens.push(Token::symbol(")"));
+    test_item(path.iter().copied(), tokens)
+}
+
+/// Turns e.g. `pub fn a::b()` into `pub const fn a::b()` for tests.
+#[cfg(test)]
+pub(crate) fn with_fn_qualifier(item: &PublicItem, qualifier: &str) -> PublicItem {
+    let mut tokens = item.tokens.to_vec();
+    tokens.splice(2..2, [Token::qualifier(qualifier), Token::Whitespace]);
+    PublicItem {
+        tokens: tokens.into(),
+        ..item.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{test_item, PublicItem};
+    use crate::tokens::Token;
+
+    #[test]
+    fn feature_gates_are_extracted_from_cfg_conditions() {
+        let item = |conditions: &[&str]| PublicItem {
+            cfg_conditions: conditions.iter().map(|c| (*c).to_owned()).collect(),
+            ..test_item(
+                ["krate", "f"],
+                vec![Token::FnKeyword, Token::Whitespace, Token::function("f")],
+            )
+        };
+
+        assert_eq!(item(&[]).feature_gates(), Vec::<String>::new());
+        assert_eq!(item(&["feature = \"a\""]).feature_gates(), ["a"]);
+        assert_eq!(
+            item(&["all(feature = \"a\", all(unix, feature = \"b,c\"))"]).feature_gates(),
+            ["a", "b,c"]
+        );
+        assert_eq!(
+            item(&[
+                "feature = \"a\"",
+                "unix",
+                "feature = \"a\"",
+                "feature=\"b\""
+            ])
+            .feature_gates(),
+            ["a", "b"]
+        );
+        assert_eq!(
+            item(&[
+                "any(feature = \"a\", feature = \"b\")",
+                "not(feature = \"c\")"
+            ])
+            .feature_gates(),
+            Vec::<String>::new()
+        );
+
+        assert_eq!(item(&[]).cfg_attribute(), None);
+        assert_eq!(
+            item(&["feature = \"a\""]).cfg_attribute().as_deref(),
+            Some("#[cfg(feature = \"a\")]")
+        );
+        assert_eq!(
+            item(&["feature = \"a\"", "unix"])
+                .cfg_attribute()
+                .as_deref(),
+            Some("#[cfg(all(feature = \"a\", unix))]")
+        );
+
+        assert_ne!(item(&["feature = \"a\""]), item(&["feature = \"b\""]));
+        assert_eq!(item(&["feature = \"a\""]), item(&["feature = \"a\""]));
+    }
+
+    #[test]
+    fn debug_shows_path_and_token_count() {
+        let item = test_item(
+            ["krate", "f"],
+            vec![Token::FnKeyword, Token::Whitespace, Token::function("f")],
+        );
+
+        assert_eq!(
+            format!("{item:?}"),
+            r#"PublicItem { path: "krate::f", tokens: [3 tokens] }"#
+        );
+        let pretty = format!("{item:#?}");
+        assert!(pretty.contains("FnKeyword"), "{pretty}");
+        assert!(pretty.contains("sortable_path"), "{pretty}");
+    }
+
+    #[test]
+    fn size_hint_is_length_of_text() {
+        let item = method(&["krate", "S", "f"], vec![Token::symbol("()")]);
+        assert_eq!(item.size_hint(), "pub fn krate::S::f()".len());
+        assert_eq!(item.size_hint(), item.to_string().len());
+
+        let longer = method(
+            &["krate", "S", "f"],
+            vec![
+                Token::symbol("("),
+                Token::identifier("x"),
+                Token::symbol(": u8)"),
+            ],
+        );
+        assert!(longer.size_hint() > item.size_hint());
+    }
+
+    #[test]
+    fn param_count_with_unbalanced_tokens() {
+        // Arbitrary or deserialized items can have e.g. a stray `>`
+        let item = method(
+            &["krate", "S", "f"],
+            vec![
+                Token::symbol("("),
+                Token::identifier("a"),
+                Token::symbol(":"),
+                Token::Whitespace,
+                Token::symbol(">"),
+                Token::symbol(","),
+                Token::Whitespace,
+                Token::identifier("b"),
+                Token::symbol(":"),
+                Token::Whitespace,
+                Token::primitive("u8"),
+                Token::symbol(")"),
+            ],
+        );
+        assert_eq!(item.param_count(), Some(2));
+    }
+
+    #[test]
+    fn semantically_equal_ignores_attribute_order() {
+        let item = |attrs: &[&str]| {
+            let mut tokens = vec![];
+            for attr in attrs {
+                tokens.push(Token::Annotation((*attr).to_owned()));
+                tokens.push(Token::Whitespace);
+            }
+            tokens.extend([Token::kind("struct"), Token::Whitespace, Token::type_("S")]);
+            test_item(["krate", "S"], tokens)
+        };
+
+        let a = item(&["#[derive(Debug, Clone)]", "#[repr(C)]"]);
+        let b = item(&["#[repr(C)]", "#[derive(Clone, Debug)]"]);
+        assert_ne!(a, b);
+        assert!(a.semantically_equal(&b));
+        assert!(!a.semantically_equal(&item(&["#[repr(C)]"])));
+    }
+
+    #[test]
+    fn normalize_collapses_whitespace() {
+        let mut item = method(
+            &["krate", "S", "f"],
+            vec![
+                Token::symbol("() "),
+                Token::Whitespace,
+                Token::operator("->"),
+                Token::Whitespace,
+                Token::primitive("u8"),
+            ],
+        );
+        item.normalize();
+        assert_eq!(item.to_string(), "pub fn krate::S::f() -> u8");
+    }
+
+    #[test]
+    fn normalize_sorts_attributes() {
+        let mut item = method(&["krate", "S", "f"], vec![Token::symbol("()")]);
+        let mut tokens = vec![
+            Token::Annotation("#[must_use]".to_owned()),
+            Token::Whitespace,
+            Token::Annotation("#[doc(hidden)]".to_owned()),
+            Token::Whitespace,
+        ];
+        tokens.extend(item.tokens().cloned());
+        item.tokens = tokens.into();
+
+        item.normalize();
+        assert_eq!(
+            item.to_string(),
+            "#[doc(hidden)] #[must_use] pub fn krate::S::f()"
+        );
+    }
+
+    #[test]
+    fn normalize_expands_self_type() {
+        let returning_self = vec![
+            Token::symbol("()"),
+            Token::Whitespace,
+            Token::operator("->"),
+            Token::Whitespace,
+            Token::SelfType,
+        ];
+
+        let mut item = method(&["krate", "S", "new"], returning_self.clone());
+        item.normalize();
+        assert_eq!(item.to_string(), "pub fn krate::S::new() -> krate::S");
+
+        let mut generic = method(&["krate", "m", "G", "new"], returning_self.clone());
+        let mut tokens = generic.tokens.to_vec();
+        let name = tokens.iter().position(|t| t.text() == "new").unwrap();
+        tokens.splice(
+            name - 1..name - 1,
+            [
+                Token::GenericOpen,
+                Token::type_("alloc"),
+                Token::path_separator(),
+                Token::type_("String"),
+                Token::GenericClose,
+            ],
+        );
+        generic.tokens = tokens.into();
+        generic.normalize();
+        assert_eq!(
+            generic.to_string(),
+            "pub fn krate::m::G<alloc::String>::new() -> krate::m::G<alloc::String>"
+        );
+
+        let mut in_trait = method(&["krate", "Trait", "new"], returning_self);
+        in_trait.sortable_path = vec![
+            "004-krate".to_owned(),
+            "014-Trait".to_owned(),
+            "017-new".to_owned(),
+        ];
+        in_trait.normalize();
+        assert_eq!(in_trait.to_string(), "pub fn krate::Trait::new() -> Self");
+    }
+
+    #[test]
+    fn normalize_is_idempotent() {
+        let mut item = method(
+            &["krate", "S", "new"],
+            vec![
+                Token::symbol("() "),
+                Token::operator("->"),
+                Token::Whitespace,
+                Token::SelfType,
+            ],
+        );
+        item.normalize();
+        let normalized = item.clone();
+        item.normalize();
+        assert_eq!(item, normalized);
+    }
+
+    /// An item like `pub fn krate::S::f` with `rest` after the name, that is
+    /// an associated item of an inherent `impl`.
+    fn method(path: &[&str], rest: Vec<Token>) -> PublicItem {
+        let (name, parents) = path.split_last().unwrap();
+        let mut tokens = vec![
+            Token::qualifier("pub"),
+            Token::Whitespace,
+            Token::FnKeyword,
+            Token::Whitespace,
+        ];
+        for parent in parents {
+            tokens.extend([Token::identifier(*parent), Token::path_separator()]);
+        }
+        tokens.push(Token::function(*name));
+        tokens.extend(rest);
+
+        let item = test_item(path.iter().copied(), tokens);
+        let mut sortable_path = item.path.clone();
+        sortable_path.insert(path.len() - 1, "020-impl".to_owned());
+        PublicItem {
+            sortable_path,
+            associated_item_of: Some(item.path[..path.len() - 1].to_vec()),
+            enclosing_type: Some(item.path[..path.len() - 1].to_vec()),
+            ..item
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Renders a [`PublicApiDiff`] as release notes in the [Keep a
+//! Changelog](https://keepachangelog.com/) format.
+
+use std::fmt::Write;
+
+use crate::diff::PublicApiDiff;
+
+impl PublicApiDiff {
+    /// Renders the diff as a release notes section that can be inserted
+    /// directly into a `CHANGELOG.md` that follows the [Keep a
+    /// Changelog](https://keepachangelog.com/) format. For example, with
+    /// `new_version` `"1.2.0"` and `date` `"2024-01-01"` the section starts
+    /// with `## [1.2.0] - 2024-01-01`.
+    ///
+    /// Removed items and changes that are not
+    /// [semver compatible](crate::diff::ChangedPublicItem::is_semver_compatible)
+    /// are listed under `### Breaking Changes`. Added items are listed under
+    /// `### Added` and the remaining changes under `### Changed`. Sections
+    /// without items are left out.
+    #[must_use]
+    pub fn to_release_notes_md(&self, new_version: &str, date: &str) -> String {
+        let (compatible, breaking): (Vec<_>, Vec<_>) =
+            self.changed.iter().partition(|c| c.is_semver_compatible());
+
+        let mut md = format!("## [{new_version}] - {date}\n");
+
+        let breaking_changes: Vec<String> = (self.removed.iter())
+            .map(|item| format!("Removed `{item}`"))
+            .chain(
+                breaking
+                    .iter()
+                    .map(|c| format!("Changed `{}` to `{}`", c.old, c.new)),
+            )
+            .collect();
+        push_section(&mut md, "Breaking Changes", &breaking_changes);
+
+        let added: Vec<String> = self.added.iter().map(|item| format!("`{item}`")).collect();
+        push_section(&mut md, "Added", &added);
+
+        let changed: Vec<String> = compatible
+            .iter()
+            .map(|c| format!("`{}` to `{}`", c.old, c.new))
+            .collect();
+        push_section(&mut md, "Changed", &changed);
+
+        md
+    }
+}
+
+/// Pushes a `###` heading followed by one bullet point per entry, unless there
+/// are no entries.
+fn push_section(md: &mut String, heading: &str, entries: &[String]) {
+    if entries.is_empty() {
+        return;
+    }
+
+    let _ = write!(md, "\n### {heading}\n\n");
+    for entry in entries {
+        let _ = writeln!(md, "- {entry}");
+    }
+}
@@ -11,7 +11,7 @@ use rustdoc_types::{
     Abi, AssocItemConstraint, AssocItemConstraintKind, Constant, Crate, FunctionHeader,
     FunctionPointer, FunctionSignature, GenericArg, GenericArgs, GenericBound, GenericParamDef,
     GenericParamDefKind, Generics, Id, Impl, Item, ItemEnum, MacroKind, Path, PolyTrait,
-    StructKind, Term, Trait, Type, VariantKind, WherePredicate,
+    StructKind, Term, Trait, TraitBoundModifier, Type, VariantKind, WherePredicate,
 };
 
 /// A simple macro to write `Token::Whitespace` in less characters.
@@ -41,6 +41,14 @@ impl<'c> RenderingContext<'c> {
 
         let mut tokens = vec![];
 
+        if let Some(docs) = item.docs.as_deref().filter(|_| self.options.include_docs) {
+            if self.options.redact_doc_comments {
+                tokens.push(Token::doc("[redacted]"));
+            } else {
+                tokens.push(Token::doc(docs));
+            }
+        }
+
         for attr in &item.attrs {
             if attr_relevant_for_public_apis(attr) {
                 tokens.push(Token::Annotation(attr.clone()));
@@ -57,9 +65,10 @@ impl<'c> RenderingContext<'c> {
                 let mut output = self.render_simple(&["struct"], item_path);
                 output.extend(self.render_generics(&s.generics));
                 if let StructKind::Tuple(fields) = &s.kind {
-                    output.extend(
-                        self.render_option_tuple(&self.resolve_tuple_fields(fields), Some(&pub_())),
-                    );
+                    let fields = self.without_phantom_data_fields(fields);
+                    if !fields.is_empty() {
+                        output.extend(self.render_tuple_fields(&fields, Some(&pub_())));
+                    }
                 }
                 output
             }
@@ -85,9 +94,10 @@ impl<'c> RenderingContext<'c> {
                         }
                     }
                     VariantKind::Tuple(fields) => {
-                        output.extend(
-                            self.render_option_tuple(&self.resolve_tuple_fields(fields), None),
-                        );
+                        let fields = self.without_phantom_data_fields(fields);
+                        if !fields.is_empty() {
+                            output.extend(self.render_tuple_fields(&fields, None));
+                        }
                     }
                 }
                 output
@@ -101,7 +111,9 @@ impl<'c> RenderingContext<'c> {
             ItemEnum::Trait(trait_) => self.render_trait(trait_, item_path),
             ItemEnum::TraitAlias(_) => self.render_simple(&["trait", "alias"], item_path),
             ItemEnum::Impl(impl_) => {
-                self.render_impl(impl_, item_path, false /* disregard_negativity */)
+                self.render_impl(
+                    impl_, item_path, false, /* disregard_negativity_and_unsafety */
+                )
             }
             ItemEnum::TypeAlias(inner) => {
                 let mut output = self.render_simple(&["type"], item_path);
@@ -177,11 +189,7 @@ impl<'c> RenderingContext<'c> {
                 //   rustup component add rust-docs-json --toolchain nightly
                 //   cargo run -- --rustdoc-json ~/.rustup/toolchains/nightly-x86_64-unknown-linux-gnu/share/doc/rust/json/core.json
                 let mut output = pub_();
-                output.extend([
-                    Token::kind("type"),
-                    ws!(),
-                    Token::primitive(&primitive.name),
-                ]);
+                output.extend([Token::TypeKeyword, ws!(), Token::primitive(&primitive.name)]);
                 output
             }
         };
@@ -214,6 +222,25 @@ impl<'c> RenderingContext<'c> {
         resolved_fields
     }
 
+    /// The `fields` of a tuple struct or tuple variant, without the fields of
+    /// type `PhantomData` if [`crate::Builder::normalize_phantom_data`] is
+    /// enabled. Without any remaining fields, the struct or variant is
+    /// rendered without parentheses.
+    fn without_phantom_data_fields(&self, fields: &[Option<Id>]) -> Vec<Option<Id>> {
+        let mut fields = fields.to_vec();
+        if self.options.normalize_phantom_data {
+            let types = self.resolve_tuple_fields(&fields);
+            let mut types = types.into_iter();
+            fields.retain(|_| {
+                !types
+                    .next()
+                    .flatten()
+                    .is_some_and(|type_| is_phantom_data(self.crate_, type_))
+            });
+        }
+        fields
+    }
+
     fn render_simple(&self, tags: &[&str], path: &[PathComponent]) -> Vec<Token> {
         let mut output = pub_();
         output.extend(
@@ -240,7 +267,7 @@ impl<'c> RenderingContext<'c> {
             output.extend(tokens);
 
             if push_a_separator {
-                output.push(Token::symbol("::"));
+                output.push(Token::path_separator());
             }
         }
         if !path.is_empty() {
@@ -341,6 +368,7 @@ impl<'c> RenderingContext<'c> {
         match ty {
             Type::ResolvedPath(path) => self.render_resolved_path(path),
             Type::DynTrait(dyn_trait) => self.render_dyn_trait(dyn_trait),
+            Type::Generic(name) if name == "Self" => vec![Token::SelfType],
             Type::Generic(name) => vec![Token::generic(name)],
             Type::Primitive(name) => vec![Token::primitive(name)],
             Type::FunctionPointer(ptr) => self.render_function_pointer(ptr),
@@ -372,7 +400,7 @@ impl<'c> RenderingContext<'c> {
         if trait_.is_unsafe {
             output.extend(vec![Token::qualifier("unsafe"), ws!()]);
         };
-        output.extend([Token::kind("trait"), ws!()]);
+        output.extend([Token::TraitKeyword, ws!()]);
         output.extend(self.render_path(path));
         output.extend(self.render_generics(&trait_.generics));
         output.extend(self.render_generic_bounds_with_colon(&trait_.bounds));
@@ -388,7 +416,7 @@ impl<'c> RenderingContext<'c> {
         }
 
         output.extend(self.render_sequence_if_not_empty(
-            vec![Token::keyword("dyn"), ws!()],
+            vec![Token::dyn_keyword(), ws!()],
             vec![],
             plus(),
             &dyn_trait.traits,
@@ -440,7 +468,7 @@ impl<'c> RenderingContext<'c> {
             output.push(ws!());
         }
 
-        output.extend(vec![Token::kind("fn"), ws!()]);
+        output.extend(vec![Token::FnKeyword, ws!()]);
         output.extend(name);
 
         // Generic parameters
@@ -492,12 +520,12 @@ impl<'c> RenderingContext<'c> {
                     type_,
                 } => match type_.as_ref() {
                     Type::Generic(name) if name == "Self" => {
-                        let mut output = vec![Token::symbol("&")];
+                        let mut output = vec![reference(*is_mutable)];
                         if let Some(lt) = lifetime {
                             output.extend(vec![Token::lifetime(lt), ws!()]);
                         }
                         if *is_mutable {
-                            output.extend(vec![Token::keyword("mut"), ws!()]);
+                            output.extend(vec![Token::mut_keyword(), ws!()]);
                         }
                         output.push(Token::self_("self"));
                         Some(output)
@@ -553,7 +581,7 @@ impl<'c> RenderingContext<'c> {
             } else {
                 output.push(Token::identifier(part.as_ref()));
             }
-            output.push(Token::symbol("::"));
+            output.push(Token::path_separator());
         }
         if len > 0 {
             output.pop();
@@ -563,7 +591,7 @@ impl<'c> RenderingContext<'c> {
 
     fn render_function_pointer(&self, ptr: &FunctionPointer) -> Vec<Token> {
         let mut output = self.render_higher_rank_trait_bounds(&ptr.generic_params);
-        output.push(Token::kind("fn"));
+        output.push(Token::FnKeyword);
         output.extend(self.render_fn_decl(&ptr.sig));
         output
     }
@@ -577,22 +605,39 @@ impl<'c> RenderingContext<'c> {
     /// tuple structs. The former marks public fields as `pub ` whereas all fields
     /// of enum tuple structs are always implicitly `pub`.
     fn render_option_tuple(&self, types: &[Option<&Type>], prefix: Option<&[Token]>) -> Vec<Token> {
-        self.render_sequence(
-            vec![Token::symbol("(")],
-            vec![Token::symbol(")")],
-            comma(),
-            types,
-            |type_| {
-                let mut output: Vec<Token> = vec![];
-                if let (Some(prefix), Some(_)) = (prefix, type_) {
-                    output.extend(prefix.to_owned());
-                }
-                output.extend(self.render_option_type(type_));
-                output
-            },
+        self.render_delimited_tuple(Token::symbol("("), Token::symbol(")"), types, prefix)
+    }
+
+    /// Like [`Self::render_option_tuple`], but for the positional fields of a
+    /// tuple struct or tuple variant, which are delimited by
+    /// [`Token::TupleOpen`] and [`Token::TupleClose`] to distinguish them from
+    /// tuple types.
+    fn render_tuple_fields(&self, fields: &[Option<Id>], prefix: Option<&[Token]>) -> Vec<Token> {
+        self.render_delimited_tuple(
+            Token::TupleOpen,
+            Token::TupleClose,
+            &self.resolve_tuple_fields(fields),
+            prefix,
         )
     }
 
+    fn render_delimited_tuple(
+        &self,
+        open: Token,
+        close: Token,
+        types: &[Option<&Type>],
+        prefix: Option<&[Token]>,
+    ) -> Vec<Token> {
+        self.render_sequence(vec![open], vec![close], comma(), types, |type_| {
+            let mut output: Vec<Token> = vec![];
+            if let (Some(prefix), Some(_)) = (prefix, type_) {
+                output.extend(prefix.to_owned());
+            }
+            output.extend(self.render_option_type(type_));
+            output
+        })
+    }
+
     fn render_slice(&self, ty: &Type) -> Vec<Token> {
         let mut output = vec![Token::symbol("[")];
         output.extend(self.render_type(ty));
@@ -601,13 +646,13 @@ impl<'c> RenderingContext<'c> {
     }
 
     fn render_array(&self, type_: &Type, len: &str) -> Vec<Token> {
-        let mut output = vec![Token::symbol("[")];
+        let mut output = vec![Token::ArrayOpen];
         output.extend(self.render_type(type_));
         output.extend(vec![
-            Token::symbol(";"),
+            Token::ArraySemicolon,
             ws!(),
             Token::primitive(len),
-            Token::symbol("]"),
+            Token::ArrayClose,
         ]);
         output
     }
@@ -616,7 +661,7 @@ impl<'c> RenderingContext<'c> {
         &self,
         impl_: &Impl,
         path: &[PathComponent],
-        disregard_negativity: bool,
+        disregard_negativity_and_unsafety: bool,
     ) -> Vec<Token> {
         let mut output = vec![];
 
@@ -625,19 +670,19 @@ impl<'c> RenderingContext<'c> {
             output.push(ws!());
         }
 
-        if impl_.is_unsafe {
+        if !disregard_negativity_and_unsafety && impl_.is_unsafe {
             output.extend(vec![Token::keyword("unsafe"), ws!()]);
         }
 
-        output.push(Token::keyword("impl"));
+        output.push(Token::ImplKeyword);
 
         output.extend(self.render_generic_param_defs(&impl_.generics.params));
 
         output.push(ws!());
 
         if let Some(trait_) = &impl_.trait_ {
-            if !disregard_negativity && impl_.is_negative {
-                output.push(Token::symbol("!"));
+            if !disregard_negativity_and_unsafety && impl_.is_negative {
+                output.push(Token::NegativeImpl);
             }
             output.extend(self.render_resolved_path(trait_));
             output.extend(vec![ws!(), Token::keyword("for"), ws!()]);
@@ -651,10 +696,48 @@ impl<'c> RenderingContext<'c> {
         output
     }
 
+    /// Like [`Self::render_generic_bounds`], but the name of each trait is a
+    /// [`Token::ImplTrait`], so that `impl Trait` is distinguishable from the
+    /// concrete type `Trait`.
     fn render_impl_trait(&self, bounds: &[GenericBound]) -> Vec<Token> {
-        let mut output = vec![Token::keyword("impl")];
+        let mut output = vec![Token::ImplKeyword];
         output.push(ws!());
-        output.extend(self.render_generic_bounds(bounds));
+        output.extend(
+            self.render_sequence_if_not_empty(vec![], vec![], plus(), bounds, |bound| {
+                self.render_impl_trait_bound(bound)
+            }),
+        );
+        output
+    }
+
+    fn render_impl_trait_bound(&self, bound: &GenericBound) -> Vec<Token> {
+        let GenericBound::TraitBound {
+            trait_,
+            generic_params,
+            modifier,
+        } = bound
+        else {
+            return self.render_generic_bounds(std::slice::from_ref(bound));
+        };
+
+        let mut output = self.render_higher_rank_trait_bounds(generic_params);
+        if *modifier == TraitBoundModifier::Maybe {
+            output.push(Token::OptionalBound);
+        }
+        let mut path = self.render_resolved_path(&Path {
+            args: None,
+            ..trait_.clone()
+        });
+        if let Some(last) = path.pop() {
+            path.push(match last {
+                Token::Type(name) => Token::impl_trait(name),
+                token => token,
+            });
+        }
+        output.extend(path);
+        if let Some(args) = &trait_.args {
+            output.extend(self.render_generic_args(args));
+        }
         output
     }
 
@@ -672,12 +755,12 @@ impl<'c> RenderingContext<'c> {
         is_mutable: bool,
         type_: &Type,
     ) -> Vec<Token> {
-        let mut output = vec![Token::symbol("&")];
+        let mut output = vec![reference(is_mutable)];
         if let Some(lt) = lifetime {
             output.extend(vec![Token::lifetime(lt), ws!()]);
         }
         if is_mutable {
-            output.extend(vec![Token::keyword("mut"), ws!()]);
+            output.extend(vec![Token::mut_keyword(), ws!()]);
         }
         output.extend(self.render_type(type_));
         output
@@ -687,21 +770,21 @@ impl<'c> RenderingContext<'c> {
         let mut output = vec![];
         match (type_, trait_) {
             (Type::Generic(name), Some(trait_)) if name == "Self" && trait_.name.is_empty() => {
-                output.push(Token::keyword("Self"));
+                output.push(Token::SelfType);
             }
             (_, trait_) => {
                 if trait_.is_some() {
-                    output.push(Token::symbol("<"));
+                    output.push(Token::angle_bracket_open());
                 }
                 output.extend(self.render_type(type_));
                 if let Some(trait_) = trait_ {
                     output.extend(vec![ws!(), Token::keyword("as"), ws!()]);
                     output.extend(self.render_resolved_path(trait_));
-                    output.push(Token::symbol(">"));
+                    output.push(Token::angle_bracket_close());
                 }
             }
         }
-        output.push(Token::symbol("::"));
+        output.push(Token::path_separator());
         output.push(Token::identifier(name));
         output
     }
@@ -742,8 +825,8 @@ impl<'c> RenderingContext<'c> {
             AssocItemConstraint(&'c AssocItemConstraint),
         }
         self.render_sequence_if_not_empty(
-            vec![Token::symbol("<")],
-            vec![Token::symbol(">")],
+            vec![Token::angle_bracket_open()],
+            vec![Token::angle_bracket_close()],
             comma(),
             &args
                 .iter()
@@ -841,8 +924,8 @@ impl<'c> RenderingContext<'c> {
             .collect();
 
         self.render_sequence_if_not_empty(
-            vec![Token::symbol("<")],
-            vec![Token::symbol(">")],
+            vec![Token::angle_bracket_open()],
+            vec![Token::angle_bracket_close()],
             comma(),
             &params_without_synthetics,
             |param| self.render_generic_param_def(param),
@@ -855,10 +938,14 @@ impl<'c> RenderingContext<'c> {
             GenericParamDefKind::Lifetime { outlives } => {
                 output.push(Token::lifetime(&generic_param_def.name));
                 if !outlives.is_empty() {
-                    output.extend(colon());
-                    output.extend(self.render_sequence(vec![], vec![], plus(), outlives, |s| {
-                        vec![Token::lifetime(s)]
-                    }));
+                    output.extend(bound_colon());
+                    output.extend(self.render_sequence(
+                        vec![],
+                        vec![],
+                        bound_plus(),
+                        outlives,
+                        |s| vec![Token::lifetime(s)],
+                    ));
                 }
             }
             GenericParamDefKind::Type { bounds, .. } => {
@@ -907,9 +994,9 @@ impl<'c> RenderingContext<'c> {
                 dbg!(lifetime, outlives);
                 output.push(Token::Lifetime(lifetime.clone()));
                 output.extend(self.render_sequence_if_not_empty(
-                    colon(),
+                    bound_colon(),
                     vec![],
-                    plus(),
+                    bound_plus(),
                     outlives,
                     |s| vec![Token::Lifetime(s.clone())],
                 ));
@@ -926,21 +1013,24 @@ impl<'c> RenderingContext<'c> {
     fn render_generic_bounds_with_colon(&self, bounds: &[GenericBound]) -> Vec<Token> {
         let mut output = vec![];
         if !bounds.is_empty() {
-            output.extend(colon());
+            output.extend(bound_colon());
             output.extend(self.render_generic_bounds(bounds));
         }
         output
     }
 
     fn render_generic_bounds(&self, bounds: &[GenericBound]) -> Vec<Token> {
-        self.render_sequence_if_not_empty(vec![], vec![], plus(), bounds, |bound| match bound {
+        self.render_sequence_if_not_empty(vec![], vec![], bound_plus(), bounds, |bound| match bound {
             GenericBound::TraitBound {
                 trait_,
                 generic_params,
-                ..
+                modifier,
             } => {
                 let mut output = vec![];
                 output.extend(self.render_higher_rank_trait_bounds(generic_params));
+                if *modifier == TraitBoundModifier::Maybe {
+                    output.push(Token::OptionalBound);
+                }
                 output.extend(self.render_resolved_path(trait_));
                 output
             }
@@ -992,10 +1082,23 @@ impl<'c> RenderingContext<'c> {
     }
 }
 
+/// Returns `true` if `type_` is `core::marker::PhantomData<...>`, also when
+/// it is written e.g. `std::marker::PhantomData<...>`.
+pub(crate) fn is_phantom_data(crate_: &Crate, type_: &Type) -> bool {
+    let Type::ResolvedPath(path) = type_ else {
+        return false;
+    };
+    match crate_.paths.get(&path.id) {
+        Some(summary) => summary.path == ["core", "marker", "PhantomData"],
+        None => path.name == "PhantomData" || path.name.ends_with("::PhantomData"),
+    }
+}
+
 /// Our list of allowed attributes comes from
 /// <https://github.com/rust-lang/rust/blob/68d0b29098/src/librustdoc/html/render/mod.rs#L941-L942>
 fn attr_relevant_for_public_apis<S: AsRef<str>>(attr: S) -> bool {
     let prefixes = [
+        "#[deprecated",
         "#[export_name",
         "#[link_section",
         "#[no_mangle",
@@ -1017,13 +1120,24 @@ fn pub_() -> Vec<Token> {
 }
 
 fn plus() -> Vec<Token> {
-    vec![ws!(), Token::symbol("+"), ws!()]
+    vec![ws!(), Token::operator("+"), ws!()]
 }
 
 fn colon() -> Vec<Token> {
     vec![Token::symbol(":"), ws!()]
 }
 
+/// The `:` between a generic parameter and its bounds, like in `T: Clone`
+fn bound_colon() -> Vec<Token> {
+    vec![Token::BoundSeparator, ws!()]
+}
+
+/// The `+` between the bounds of a generic parameter, like in `T: Clone +
+/// Debug`
+fn bound_plus() -> Vec<Token> {
+    vec![ws!(), Token::BoundCombiner, ws!()]
+}
+
 fn comma() -> Vec<Token> {
     vec![Token::symbol(","), ws!()]
 }
@@ -1033,7 +1147,15 @@ fn equals() -> Vec<Token> {
 }
 
 fn arrow() -> Vec<Token> {
-    vec![ws!(), Token::symbol("->"), ws!()]
+    vec![ws!(), Token::operator("->"), ws!()]
+}
+
+fn reference(is_mutable: bool) -> Token {
+    if is_mutable {
+        Token::mutable_reference()
+    } else {
+        Token::reference()
+    }
 }
 
 #[cfg(test)]
@@ -1102,9 +1224,9 @@ mod test {
             },
             vec![
                 Token::identifier("name"),
-                Token::symbol("::"),
+                Token::path_separator(),
                 Token::identifier("with"),
-                Token::symbol("::"),
+                Token::path_separator(),
                 Token::type_("parts"),
             ],
             "name::with::parts",
@@ -1123,7 +1245,7 @@ mod test {
             },
             vec![
                 Token::identifier("$crate"),
-                Token::symbol("::"),
+                Token::path_separator(),
                 Token::type_("name"),
             ],
             "$crate::name",
@@ -1142,7 +1264,7 @@ mod test {
             },
             vec![
                 Token::identifier("name"),
-                Token::symbol("::"),
+                Token::path_separator(),
                 Token::type_("$crate"),
             ],
             "name::$crate",
@@ -1195,12 +1317,12 @@ mod test {
                 })
             },
             vec![
-                Token::symbol("["),
+                Token::ArrayOpen,
                 Token::symbol("_"),
-                Token::symbol(";"),
+                Token::ArraySemicolon,
                 ws!(),
                 Token::primitive("20"),
-                Token::symbol("]"),
+                Token::ArrayClose,
             ],
             "[_; 20]",
         );
@@ -1236,7 +1358,7 @@ mod test {
             },
             vec![
                 Token::symbol("*"),
-                Token::keyword("mut"),
+                Token::mut_keyword(),
                 ws!(),
                 Token::symbol("_"),
             ],
@@ -1254,7 +1376,7 @@ mod test {
                     type_: Box::new(Type::Infer),
                 })
             },
-            vec![Token::symbol("&"), Token::symbol("_")],
+            vec![Token::reference(), Token::symbol("_")],
             "&_",
         );
     }
@@ -1270,8 +1392,8 @@ mod test {
                 })
             },
             vec![
-                Token::symbol("&"),
-                Token::keyword("mut"),
+                Token::mutable_reference(),
+                Token::mut_keyword(),
                 ws!(),
                 Token::symbol("_"),
             ],
@@ -1290,7 +1412,7 @@ mod test {
                 })
             },
             vec![
-                Token::symbol("&"),
+                Token::reference(),
                 Token::lifetime("'a"),
                 ws!(),
                 Token::symbol("_"),
@@ -1310,10 +1432,10 @@ mod test {
                 })
             },
             vec![
-                Token::symbol("&"),
+                Token::mutable_reference(),
                 Token::lifetime("'a"),
                 ws!(),
-                Token::keyword("mut"),
+                Token::mut_keyword(),
                 ws!(),
                 Token::symbol("_"),
             ],
@@ -1340,20 +1462,42 @@ mod test {
                 })
             },
             vec![
-                Token::symbol("<"),
+                Token::angle_bracket_open(),
                 Token::generic("type"),
                 ws!(),
                 Token::keyword("as"),
                 ws!(),
                 Token::type_("trait"),
-                Token::symbol(">"),
-                Token::symbol("::"),
+                Token::angle_bracket_close(),
+                Token::path_separator(),
                 Token::identifier("name"),
             ],
             "<type as trait>::name",
         );
     }
 
+    #[test]
+    fn test_type_impl_trait_bounds() {
+        assert_render(
+            |context| {
+                context.render_type(&Type::ImplTrait(vec![
+                    GenericBound::Outlives(s!("'a")),
+                    GenericBound::Outlives(s!("'b")),
+                ]))
+            },
+            vec![
+                Token::ImplKeyword,
+                ws!(),
+                Token::lifetime("'a"),
+                ws!(),
+                Token::operator("+"),
+                ws!(),
+                Token::lifetime("'b"),
+            ],
+            "impl 'a + 'b",
+        );
+    }
+
     fn assert_render(
         render_fn: impl Fn(RenderingContext) -> Vec<Token>,
         expected: Vec<Token>,
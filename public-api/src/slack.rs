@@ -0,0 +1,183 @@
+//! Renders a [`PublicApiDiff`] as a Slack message in the [Block
+//! Kit](https://api.slack.com/block-kit) format.
+
+use std::fmt::Write;
+
+use serde_json::{json, Value};
+
+use crate::diff::PublicApiDiff;
+
+/// Slack rejects `header` blocks with longer texts.
+const MAX_HEADER_LEN: usize = 150;
+
+/// Slack rejects `section` blocks with longer texts.
+const MAX_SECTION_LEN: usize = 3000;
+
+impl PublicApiDiff {
+    /// Renders the diff as a Slack Block Kit message payload, e.g. for
+    /// deployment notifications. The message starts with a header such as
+    /// `Public API diff of example_api 1.2.0`, followed by one section each
+    /// for removed, changed and added items, with the items in `mrkdwn` code
+    /// blocks. Sections without items are left out.
+    ///
+    /// Slack limits the length of the text of a section, so if there are too
+    /// many items to fit, the last ones are replaced by a line such as `... and
+    /// 12 more`.
+    #[must_use]
+    pub fn to_slack_message(&self, crate_name: &str, version: &str) -> Value {
+        let mut header = format!("Public API diff of {crate_name} {version}");
+        truncate(&mut header, MAX_HEADER_LEN);
+        let mut blocks = vec![json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": header },
+        })];
+
+        let removed: Vec<String> = self.removed.iter().map(|item| format!("-{item}")).collect();
+        let changed: Vec<String> = (self.changed.iter())
+            .map(|c| format!("-{}\n+{}", c.old, c.new))
+            .collect();
+        let added: Vec<String> = self.added.iter().map(|item| format!("+{item}")).collect();
+        for (title, lines) in [
+            ("Removed items", removed),
+            ("Changed items", changed),
+            ("Added items", added),
+        ] {
+            if !lines.is_empty() {
+                blocks.push(section(&format!("*{title}*"), &lines));
+            }
+        }
+
+        if self.is_empty() {
+            blocks.push(json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": "No changes to the public API" },
+            }));
+        }
+
+        json!({ "blocks": blocks })
+    }
+}
+
+/// A `section` block with `title` followed by as many of `lines` in a code
+/// block as fit within [`MAX_SECTION_LEN`].
+fn section(title: &str, lines: &[String]) -> Value {
+    const FENCE: &str = "```";
+    // Room for a line such as "... and 123 more" after the code block
+    const MORE_LEN: usize = 32;
+
+    let mut text = format!("{title}\n{FENCE}\n");
+    let mut included = 0;
+    for line in lines {
+        if text.len() + line.len() + 1 + FENCE.len() + MORE_LEN > MAX_SECTION_LEN {
+            break;
+        }
+        text.push_str(line);
+        text.push('\n');
+        included += 1;
+    }
+    text.push_str(FENCE);
+    if included < lines.len() {
+        let _ = write!(text, "\n... and {} more", lines.len() - included);
+    }
+
+    json!({
+        "type": "section",
+        "text": { "type": "mrkdwn", "text": text },
+    })
+}
+
+/// Truncates `text` to at most `max_len` bytes, at a `char` boundary.
+fn truncate(text: &mut String, max_len: usize) {
+    if text.len() > max_len {
+        let mut end = max_len;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::{
+        diff::{ChangedPublicItem, PublicApiDiff},
+        public_item::test_fn,
+    };
+
+    use super::{MAX_HEADER_LEN, MAX_SECTION_LEN};
+
+    #[test]
+    fn message_follows_block_kit_schema() {
+        let diff = PublicApiDiff {
+            removed: vec![test_fn(&["krate", "a"], &[])],
+            changed: vec![ChangedPublicItem {
+                old: test_fn(&["krate", "b"], &[]),
+                new: test_fn(&["krate", "b"], &[("x", "u8")]),
+            }],
+            added: vec![test_fn(&["krate", "c"], &[])],
+        };
+
+        let message = diff.to_slack_message("example_api", "1.2.0");
+
+        let blocks = assert_is_message(&message);
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(
+            blocks[0]["text"]["text"],
+            "Public API diff of example_api 1.2.0"
+        );
+        assert_eq!(
+            blocks[2]["text"]["text"],
+            "*Changed items*\n```\n-pub fn krate::b()\n+pub fn krate::b(x: u8)\n```"
+        );
+    }
+
+    #[test]
+    fn empty_diff() {
+        let message = PublicApiDiff::default().to_slack_message("example_api", "1.2.0");
+
+        let blocks = assert_is_message(&message);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1]["text"]["text"], "No changes to the public API");
+    }
+
+    #[test]
+    fn long_sections_are_truncated() {
+        let diff = PublicApiDiff {
+            added: (0..1000)
+                .map(|i| test_fn(&["krate", &format!("f{i}")], &[]))
+                .collect(),
+            ..PublicApiDiff::default()
+        };
+
+        let message = diff.to_slack_message(&"krate".repeat(100), "1.2.0");
+
+        let blocks = assert_is_message(&message);
+        let text = blocks[1]["text"]["text"].as_str().unwrap();
+        assert!(text.ends_with(" more"), "{text}");
+    }
+
+    /// Checks the parts of the Block Kit schema that are used, and returns the
+    /// blocks.
+    fn assert_is_message(message: &Value) -> &[Value] {
+        let blocks = message["blocks"].as_array().unwrap();
+        assert!(blocks.len() <= 50, "Slack allows at most 50 blocks");
+        for block in blocks {
+            let text = &block["text"];
+            let len = text["text"].as_str().unwrap().len();
+            match block["type"].as_str().unwrap() {
+                "header" => {
+                    assert_eq!(text["type"], "plain_text");
+                    assert!(len <= MAX_HEADER_LEN);
+                }
+                "section" => {
+                    assert_eq!(text["type"], "mrkdwn");
+                    assert!(len <= MAX_SECTION_LEN);
+                }
+                other => panic!("unexpected block type {other}"),
+            }
+        }
+        blocks
+    }
+}
@@ -0,0 +1,46 @@
+use std::time::SystemTime;
+
+use crate::{PublicApi, PublicItem};
+
+/// A [`PublicApi`] together with information about where it came from. Create
+/// an instance with [`crate::Builder::build_snapshot`], and diff two
+/// instances with [`crate::diff::PublicApiDiff::between_snapshots`].
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PublicApiSnapshot {
+    /// The name of the crate, e.g. `example_api`.
+    pub crate_name: String,
+
+    /// The version of the crate, e.g. `1.2.3`. Empty if unknown.
+    pub version: String,
+
+    /// The toolchain that built the rustdoc JSON, e.g. `nightly-2024-10-18`.
+    pub toolchain: String,
+
+    /// The items of the public API, see [`PublicApi::items`].
+    pub items: Vec<PublicItem>,
+
+    /// When the snapshot was created.
+    pub generated_at: SystemTime,
+}
+
+impl PublicApiSnapshot {
+    /// The crate name and version, e.g. `example_api 1.2.3`, or only the crate
+    /// name if the version is unknown.
+    #[must_use]
+    pub fn name_and_version(&self) -> String {
+        if self.version.is_empty() {
+            self.crate_name.clone()
+        } else {
+            format!("{} {}", self.crate_name, self.version)
+        }
+    }
+
+    /// The items of the snapshot as a [`PublicApi`].
+    #[must_use]
+    pub fn to_public_api(&self) -> PublicApi {
+        PublicApi {
+            items: self.items.clone(),
+            missing_item_ids: vec![],
+        }
+    }
+}
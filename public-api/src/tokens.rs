@@ -2,15 +2,142 @@
 #[cfg(doc)]
 use crate::public_item::PublicItem;
 
+/// All strict and reserved keywords of Rust, as well as the weak keywords that
+/// can appear in public items. Sorted, so that it can be binary searched.
+pub const RUST_KEYWORDS: &[&str] = &[
+    "Self",
+    "abstract",
+    "as",
+    "async",
+    "await",
+    "become",
+    "box",
+    "break",
+    "const",
+    "continue",
+    "crate",
+    "do",
+    "dyn",
+    "else",
+    "enum",
+    "extern",
+    "false",
+    "final",
+    "fn",
+    "for",
+    "gen",
+    "if",
+    "impl",
+    "in",
+    "let",
+    "loop",
+    "macro",
+    "macro_rules",
+    "match",
+    "mod",
+    "move",
+    "mut",
+    "override",
+    "priv",
+    "pub",
+    "ref",
+    "return",
+    "safe",
+    "self",
+    "static",
+    "struct",
+    "super",
+    "trait",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "union",
+    "unsafe",
+    "unsized",
+    "use",
+    "virtual",
+    "where",
+    "while",
+    "yield",
+];
+
 /// A token in a rendered [`PublicItem`], used to apply syntax coloring in downstream applications.
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[non_exhaustive]
 pub enum Token {
     /// A symbol, like `=` or `::<`
     Symbol(String),
+    /// An operator in a signature, like `->` before a return type or `+`
+    /// between the traits of `impl Trait` or `dyn Trait`
+    Operator(String),
     /// A qualifier, like `pub` or `const`
     Qualifier(String),
-    /// The kind of an item, like `function` or `trait`
+    /// The kind of an item that has no dedicated variant, like `union` or
+    /// `macro`
     Kind(String),
+    /// The `fn` keyword, e.g. the kind of a function
+    FnKeyword,
+    /// The `struct` keyword, the kind of a struct
+    StructKeyword,
+    /// The `enum` keyword, the kind of an enum
+    EnumKeyword,
+    /// The `trait` keyword, the kind of a trait
+    TraitKeyword,
+    /// The `impl` keyword, e.g. of an `impl` block or of `impl Trait`
+    ImplKeyword,
+    /// The `dyn` keyword of a trait object, like `dyn Trait`
+    DynKeyword,
+    /// The `type` keyword, e.g. the kind of a type alias
+    TypeKeyword,
+    /// The `const` keyword, the kind of a constant
+    ConstKeyword,
+    /// The `static` keyword, the kind of a static
+    StaticKeyword,
+    /// The `mod` keyword, the kind of a module
+    ModKeyword,
+    /// The `!` of a negative impl, like in `impl !Send for Foo`
+    NegativeImpl,
+    /// The `&` of a shared reference, like in `&T`
+    SharedRef,
+    /// The `&` of a mutable reference, like in `&mut T`. The `mut` is a
+    /// separate [`Token::Keyword`], since a lifetime can come in between, like
+    /// in `&'a mut T`
+    MutRef,
+    /// The `<` that opens generic parameters or arguments, like in `Vec<T>`,
+    /// or a qualified path, like in `<T as Trait>::Assoc`. Never a comparison
+    GenericOpen,
+    /// The `>` that closes what a [`Token::GenericOpen`] opened
+    GenericClose,
+    /// The `::` between the components of a path, like in `krate::Struct`
+    /// or `<T as Trait>::Assoc`
+    PathSeparator,
+    /// The `:` between a generic parameter and its bounds, like in `T: Clone`
+    /// or `where T: Clone`, or between a trait and its supertraits
+    BoundSeparator,
+    /// The `+` between two bounds, like in `T: Clone + Debug`
+    BoundCombiner,
+    /// The `?` of a bound that relaxes a default bound, like in `T: ?Sized`
+    OptionalBound,
+    /// The `[` that opens an array type, like in `[u8; 32]`. Slices such as
+    /// `[u8]` use [`Token::Symbol`]
+    ArrayOpen,
+    /// The `;` between the element type and the length of an array type,
+    /// like in `[u8; 32]`
+    ArraySemicolon,
+    /// The `]` that closes what a [`Token::ArrayOpen`] opened
+    ArrayClose,
+    /// The `(` that opens the positional fields of a tuple struct or tuple
+    /// variant, like in `pub struct Foo(pub usize, pub bool)`. Tuple types
+    /// such as `(usize, bool)` use [`Token::Symbol`]. The fields are separated
+    /// by `,` symbols, so the position of a field is the number of `,` before
+    /// it outside of nested types
+    TupleOpen,
+    /// The `)` that closes what a [`Token::TupleOpen`] opened
+    TupleClose,
     /// Whitespace, a single space
     Whitespace,
     /// An identifier, like variable names or parts of the path of an item
@@ -19,6 +146,8 @@ pub enum Token {
     Annotation(String),
     /// The identifier self, the text can be `self` or `Self`
     Self_(String),
+    /// The `Self` type, like in `fn new() -> Self`
+    SelfType,
     /// The identifier for a function, like `fn_arg` in `comprehensive_api::functions::fn_arg`
     Function(String),
     /// A lifetime including the apostrophe `'`, like `'a`
@@ -31,20 +160,49 @@ pub enum Token {
     Primitive(String),
     /// A non-primitive type, like the name of a struct or a trait
     Type(String),
+    /// The name of a trait in `impl Trait` position, like `Display` in `fn
+    /// f(x: impl Display)` or in `fn f() -> impl Display`. Distinguishes an
+    /// opaque `impl Trait` from a concrete type with the same name
+    ImplTrait(String),
+    /// A documentation comment, like `/// Does a thing.` followed by a newline.
+    /// Only present if [`crate::Builder::include_docs`] is enabled.
+    Doc(String),
 }
 
 impl Token {
-    /// A symbol, like `=` or `::<`
+    /// A symbol, like `=` or `::<`. Operators, like `->`, `=>`, `+` and `|`,
+    /// become [`Token::Operator`]. Prefer to use [`Token::operator`] for them.
     pub(crate) fn symbol(text: impl Into<String>) -> Self {
-        Self::Symbol(text.into())
+        let text = text.into();
+        match text.as_str() {
+            "->" | "=>" | "+" | "|" => Self::Operator(text),
+            _ => Self::Symbol(text),
+        }
+    }
+    /// An operator, like `->` or `+`
+    pub(crate) fn operator(text: impl Into<String>) -> Self {
+        Self::Operator(text.into())
     }
     /// A qualifier, like `pub` or `const`
     pub(crate) fn qualifier(text: impl Into<String>) -> Self {
         Self::Qualifier(text.into())
     }
-    /// The kind of an item, like `function` or `trait`
+    /// The kind of an item, like `fn` or `union`. Kinds that have a dedicated
+    /// variant, like [`Token::FnKeyword`], become that variant. Prefer to use
+    /// such variants directly.
     pub(crate) fn kind(text: impl Into<String>) -> Self {
-        Self::Kind(text.into())
+        let text = text.into();
+        match text.as_str() {
+            "fn" => Self::FnKeyword,
+            "struct" => Self::StructKeyword,
+            "enum" => Self::EnumKeyword,
+            "trait" => Self::TraitKeyword,
+            "type" => Self::TypeKeyword,
+            "const" => Self::const_keyword(),
+            "static" => Self::static_keyword(),
+            "mod" => Self::ModKeyword,
+            _ => Self::Kind(text),
+        }
     }
     /// An identifier, like variable names or parts of the path of an item
     pub(crate) fn identifier(text: impl Into<String>) -> Self {
@@ -62,9 +220,57 @@ impl Token {
     pub(crate) fn lifetime(text: impl Into<String>) -> Self {
         Self::Lifetime(text.into())
     }
-    /// A keyword, like `impl`
+    /// A keyword, like `where`. Keywords that have a dedicated constructor,
+    /// like [`Token::mut_keyword`], should be created with that constructor
+    /// instead, so that typos are caught by the compiler.
     pub(crate) fn keyword(text: impl Into<String>) -> Self {
-        Self::Keyword(text.into())
+        let text = text.into();
+        match text.as_str() {
+            "impl" => Self::impl_keyword(),
+            "dyn" => Self::dyn_keyword(),
+            _ => Self::Keyword(text),
+        }
+    }
+    /// The `const` keyword, the kind of a constant. Note that the `const` of
+    /// e.g. a `const fn` is a [`Token::Qualifier`].
+    pub(crate) fn const_keyword() -> Self {
+        Self::ConstKeyword
+    }
+    /// The `static` keyword, the kind of a static
+    pub(crate) fn static_keyword() -> Self {
+        Self::StaticKeyword
+    }
+    /// The `impl` keyword, e.g. of an `impl` block or of `impl Trait`
+    pub(crate) fn impl_keyword() -> Self {
+        Self::ImplKeyword
+    }
+    /// The `&` of a shared reference, like in `&T`
+    pub(crate) fn reference() -> Self {
+        Self::SharedRef
+    }
+    /// The `&` of a mutable reference, like in `&mut T`
+    pub(crate) fn mutable_reference() -> Self {
+        Self::MutRef
+    }
+    /// The `<` of generics, like in `Vec<T>`
+    pub(crate) fn angle_bracket_open() -> Self {
+        Self::GenericOpen
+    }
+    /// The `>` of generics, like in `Vec<T>`
+    pub(crate) fn angle_bracket_close() -> Self {
+        Self::GenericClose
+    }
+    /// The `::` of paths, like in `krate::Struct`
+    pub(crate) fn path_separator() -> Self {
+        Self::PathSeparator
+    }
+    /// The `mut` keyword, e.g. of `&mut T`
+    pub(crate) fn mut_keyword() -> Self {
+        Self::Keyword("mut".to_owned())
+    }
+    /// The `dyn` keyword of a trait object, like `dyn Trait`
+    pub(crate) fn dyn_keyword() -> Self {
+        Self::DynKeyword
     }
     /// A generic, like `T`
     pub(crate) fn generic(text: impl Into<String>) -> Self {
@@ -78,6 +284,68 @@ impl Token {
     pub(crate) fn type_(text: impl Into<String>) -> Self {
         Self::Type(text.into())
     }
+    /// The name of a trait in `impl Trait` position, like `Display`
+    pub(crate) fn impl_trait(text: impl Into<String>) -> Self {
+        Self::ImplTrait(text.into())
+    }
+    /// A documentation comment. Every line of `docs` is rendered as a `///`
+    /// comment that ends with a newline.
+    pub(crate) fn doc(docs: &str) -> Self {
+        Self::Doc(
+            docs.lines()
+                .map(|line| {
+                    if line.is_empty() {
+                        "///\n".to_owned()
+                    } else {
+                        format!("/// {line}\n")
+                    }
+                })
+                .collect(),
+        )
+    }
+    /// Returns `true` if the token is a Rust keyword, such as `fn`, `pub`,
+    /// `unsafe` or `where`. Besides [`Token::Keyword`]s, this includes
+    /// [`Token::Qualifier`]s, [`Token::Kind`]s and [`Token::Self_`]s whose text
+    /// is in [`RUST_KEYWORDS`].
+    #[must_use]
+    pub fn is_keyword(&self) -> bool {
+        match self {
+            Self::Keyword(_)
+            | Self::FnKeyword
+            | Self::StructKeyword
+            | Self::EnumKeyword
+            | Self::TraitKeyword
+            | Self::ImplKeyword
+            | Self::DynKeyword
+            | Self::TypeKeyword
+            | Self::ConstKeyword
+            | Self::StaticKeyword
+            | Self::ModKeyword
+            | Self::SelfType => true,
+            Self::Qualifier(text) | Self::Kind(text) | Self::Self_(text) => {
+                RUST_KEYWORDS.binary_search(&text.as_str()).is_ok()
+            }
+            _ => false,
+        }
+    }
+    /// Returns `true` if the token is the kind of an item, i.e. a
+    /// [`Token::Kind`] or one of the keyword variants such as
+    /// [`Token::FnKeyword`]. A [`Token::ImplKeyword`] is not a kind, since
+    /// `impl`s are not considered to have a kind.
+    pub(crate) fn is_kind(&self) -> bool {
+        matches!(
+            self,
+            Self::Kind(_)
+                | Self::FnKeyword
+                | Self::StructKeyword
+                | Self::EnumKeyword
+                | Self::TraitKeyword
+                | Self::TypeKeyword
+                | Self::ConstKeyword
+                | Self::StaticKeyword
+                | Self::ModKeyword
+        )
+    }
     /// Give the length of the inner text of this token
     #[allow(clippy::len_without_is_empty)]
     #[must_use]
@@ -89,6 +357,7 @@ impl Token {
     pub fn text(&self) -> &str {
         match self {
             Self::Symbol(l)
+            | Self::Operator(l)
             | Self::Qualifier(l)
             | Self::Kind(l)
             | Self::Identifier(l)
@@ -99,12 +368,345 @@ impl Token {
             | Self::Keyword(l)
             | Self::Generic(l)
             | Self::Primitive(l)
-            | Self::Type(l) => l,
+            | Self::Type(l)
+            | Self::ImplTrait(l)
+            | Self::Doc(l) => l,
+            Self::FnKeyword => "fn",
+            Self::StructKeyword => "struct",
+            Self::EnumKeyword => "enum",
+            Self::TraitKeyword => "trait",
+            Self::ImplKeyword => "impl",
+            Self::DynKeyword => "dyn",
+            Self::TypeKeyword => "type",
+            Self::ConstKeyword => "const",
+            Self::StaticKeyword => "static",
+            Self::ModKeyword => "mod",
+            Self::SelfType => "Self",
+            Self::NegativeImpl => "!",
+            Self::SharedRef | Self::MutRef => "&",
+            Self::GenericOpen => "<",
+            Self::GenericClose => ">",
+            Self::PathSeparator => "::",
+            Self::BoundSeparator => ":",
+            Self::BoundCombiner => "+",
+            Self::OptionalBound => "?",
+            Self::ArrayOpen => "[",
+            Self::ArraySemicolon => ";",
+            Self::ArrayClose => "]",
+            Self::TupleOpen => "(",
+            Self::TupleClose => ")",
             Self::Whitespace => " ",
         }
     }
 }
 
+impl Token {
+    /// One instance of each variant, with dummy texts where needed, for tests
+    /// and fuzz harnesses that want to cover all kinds of tokens. Only
+    /// available with the `testing` feature.
+    #[cfg(any(test, feature = "testing"))]
+    #[must_use]
+    pub fn all_variants() -> &'static [Token] {
+        static ALL_VARIANTS: std::sync::OnceLock<Vec<Token>> = std::sync::OnceLock::new();
+        ALL_VARIANTS.get_or_init(|| {
+            let all_variants = vec![
+                Token::symbol("("),
+                Token::operator("->"),
+                Token::qualifier("pub"),
+                Token::Kind("union".to_owned()),
+                Token::FnKeyword,
+                Token::StructKeyword,
+                Token::EnumKeyword,
+                Token::TraitKeyword,
+                Token::ImplKeyword,
+                Token::DynKeyword,
+                Token::TypeKeyword,
+                Token::ConstKeyword,
+                Token::StaticKeyword,
+                Token::ModKeyword,
+                Token::NegativeImpl,
+                Token::SharedRef,
+                Token::MutRef,
+                Token::GenericOpen,
+                Token::GenericClose,
+                Token::PathSeparator,
+                Token::BoundSeparator,
+                Token::BoundCombiner,
+                Token::OptionalBound,
+                Token::ArrayOpen,
+                Token::ArraySemicolon,
+                Token::ArrayClose,
+                Token::TupleOpen,
+                Token::TupleClose,
+                Token::Whitespace,
+                Token::identifier("krate"),
+                Token::Annotation("#[non_exhaustive]".to_owned()),
+                Token::self_("self"),
+                Token::SelfType,
+                Token::function("f"),
+                Token::lifetime("'a"),
+                Token::Keyword("where".to_owned()),
+                Token::generic("T"),
+                Token::primitive("usize"),
+                Token::type_("Struct"),
+                Token::impl_trait("Trait"),
+                Token::doc("Docs."),
+            ];
+            for token in &all_variants {
+                // If this fails to compile, a variant is missing here. Add it
+                // to the list above too.
+                match token {
+                    Token::Symbol(_)
+                    | Token::Operator(_)
+                    | Token::Qualifier(_)
+                    | Token::Kind(_)
+                    | Token::FnKeyword
+                    | Token::StructKeyword
+                    | Token::EnumKeyword
+                    | Token::TraitKeyword
+                    | Token::ImplKeyword
+                    | Token::DynKeyword
+                    | Token::TypeKeyword
+                    | Token::ConstKeyword
+                    | Token::StaticKeyword
+                    | Token::ModKeyword
+                    | Token::NegativeImpl
+                    | Token::SharedRef
+                    | Token::MutRef
+                    | Token::GenericOpen
+                    | Token::GenericClose
+                    | Token::PathSeparator
+                    | Token::BoundSeparator
+                    | Token::BoundCombiner
+                    | Token::OptionalBound
+                    | Token::ArrayOpen
+                    | Token::ArraySemicolon
+                    | Token::ArrayClose
+                    | Token::TupleOpen
+                    | Token::TupleClose
+                    | Token::Whitespace
+                    | Token::Identifier(_)
+                    | Token::Annotation(_)
+                    | Token::Self_(_)
+                    | Token::SelfType
+                    | Token::Function(_)
+                    | Token::Lifetime(_)
+                    | Token::Keyword(_)
+                    | Token::Generic(_)
+                    | Token::Primitive(_)
+                    | Token::Type(_)
+                    | Token::ImplTrait(_)
+                    | Token::Doc(_) => {}
+                }
+            }
+            all_variants
+        })
+    }
+}
+
+/// The text of `token` as it is rendered, e.g. `"fn"` for
+/// [`Token::FnKeyword`], `" "` for [`Token::Whitespace`] and `"u8"` for
+/// `Token::Primitive("u8")`. The same as [`Token::text`], as a free function,
+/// for e.g. `tokens.map(token_text)`. Concatenating the texts of the tokens
+/// of a [`PublicItem`] gives its [`std::fmt::Display`] form.
+#[must_use]
+pub fn token_text(token: &Token) -> &str {
+    token.text()
+}
+
 pub(crate) fn tokens_to_string(tokens: &[Token]) -> String {
     tokens.iter().map(Token::text).collect()
 }
+
+/// Puts `tokens` on a canonical form with regards to whitespace, so that items
+/// that only differ in formatting become equal. Leading and trailing whitespace
+/// of [`Token::Symbol`]s is turned into [`Token::Whitespace`], and consecutive
+/// [`Token::Whitespace`]s are collapsed into one. See
+/// [`crate::Builder::normalize_whitespace`].
+pub(crate) fn normalize_whitespace(tokens: Vec<Token>) -> Vec<Token> {
+    let mut normalized: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut push = |token: Token| {
+        if token != Token::Whitespace || normalized.last() != Some(&Token::Whitespace) {
+            normalized.push(token);
+        }
+    };
+
+    for token in tokens {
+        match token {
+            Token::Symbol(text) if text.trim() != text => {
+                if text.starts_with(char::is_whitespace) {
+                    push(Token::Whitespace);
+                }
+                if !text.trim().is_empty() {
+                    push(Token::symbol(text.trim()));
+                }
+                if text.ends_with(char::is_whitespace) {
+                    push(Token::Whitespace);
+                }
+            }
+            token => push(token),
+        }
+    }
+
+    normalized
+}
+
+/// Puts the attributes of `tokens` on a canonical form, so that items that only
+/// differ in the order of their attributes, or in the order of the traits in a
+/// `#[derive(...)]`, become equal. Each run of consecutive
+/// [`Token::Annotation`]s is sorted. See
+/// [`crate::Builder::normalize_attribute_order`].
+pub(crate) fn normalize_attribute_order(tokens: Vec<Token>) -> Vec<Token> {
+    let mut normalized = Vec::with_capacity(tokens.len());
+    let mut annotations = vec![];
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        if let Token::Annotation(text) = token {
+            annotations.push(sorted_derive(&text).unwrap_or(text));
+            // Annotations are separated by whitespace
+            if tokens.peek() == Some(&Token::Whitespace) {
+                tokens.next();
+            }
+            continue;
+        }
+        push_sorted(&mut normalized, &mut annotations);
+        normalized.push(token);
+    }
+    push_sorted(&mut normalized, &mut annotations);
+    normalized
+}
+
+/// Drains `annotations` into `tokens` in sorted order, each followed by
+/// whitespace.
+fn push_sorted(tokens: &mut Vec<Token>, annotations: &mut Vec<String>) {
+    annotations.sort();
+    for annotation in annotations.drain(..) {
+        tokens.push(Token::Annotation(annotation));
+        tokens.push(Token::Whitespace);
+    }
+}
+
+/// If `attr` is e.g. `#[derive(Debug, Clone)]`, returns
+/// `#[derive(Clone, Debug)]`.
+fn sorted_derive(attr: &str) -> Option<String> {
+    let traits = attr.strip_prefix("#[derive(")?.strip_suffix(")]")?;
+    let mut traits: Vec<&str> = traits.split(',').map(str::trim).collect();
+    traits.sort_unstable();
+    Some(format!("#[derive({})]", traits.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        normalize_attribute_order, normalize_whitespace, token_text, Token, RUST_KEYWORDS,
+    };
+
+    #[test]
+    fn all_variants_are_distinct() {
+        let all_variants = Token::all_variants();
+        for (index, token) in all_variants.iter().enumerate() {
+            let same_variant =
+                |other: &Token| std::mem::discriminant(token) == std::mem::discriminant(other);
+            assert!(
+                !all_variants[index + 1..].iter().any(same_variant),
+                "{token:?} is listed twice"
+            );
+        }
+    }
+
+    #[test]
+    fn token_text_of_each_variant() {
+        for token in Token::all_variants() {
+            assert_eq!(token_text(token), token.text());
+            assert!(!token_text(token).is_empty(), "{token:?} has no text");
+        }
+        assert_eq!(token_text(&Token::Whitespace), " ");
+        assert_eq!(token_text(&Token::FnKeyword), "fn");
+        assert_eq!(token_text(&Token::primitive("u8")), "u8");
+    }
+
+    #[test]
+    fn rust_keywords_are_sorted() {
+        assert!(RUST_KEYWORDS.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn whitespace_is_normalized() {
+        let tokens = vec![
+            Token::generic("T"),
+            Token::Whitespace,
+            Token::symbol(" = "),
+            Token::Whitespace,
+            Token::Whitespace,
+            Token::primitive("u8"),
+            Token::symbol(" "),
+        ];
+        let expected = vec![
+            Token::generic("T"),
+            Token::Whitespace,
+            Token::symbol("="),
+            Token::Whitespace,
+            Token::primitive("u8"),
+            Token::Whitespace,
+        ];
+        assert_eq!(normalize_whitespace(tokens), expected);
+    }
+
+    #[test]
+    fn attribute_order_is_normalized() {
+        let tokens = vec![
+            Token::Annotation("#[repr(C)]".to_owned()),
+            Token::Whitespace,
+            Token::Annotation("#[derive(Debug, Clone)]".to_owned()),
+            Token::Whitespace,
+            Token::qualifier("pub"),
+        ];
+        let expected = vec![
+            Token::Annotation("#[derive(Clone, Debug)]".to_owned()),
+            Token::Whitespace,
+            Token::Annotation("#[repr(C)]".to_owned()),
+            Token::Whitespace,
+            Token::qualifier("pub"),
+        ];
+        assert_eq!(normalize_attribute_order(tokens), expected);
+    }
+
+    #[test]
+    fn is_keyword() {
+        assert!(Token::keyword("where").is_keyword());
+        assert!(Token::qualifier("pub").is_keyword());
+        assert!(Token::StructKeyword.is_keyword());
+        assert!(Token::ImplKeyword.is_keyword());
+        assert!(Token::DynKeyword.is_keyword());
+        assert!(Token::self_("Self").is_keyword());
+        assert!(Token::SelfType.is_keyword());
+
+        // The qualifier of e.g. `extern "C" fn` is not a keyword
+        assert!(!Token::qualifier("c").is_keyword());
+        assert!(!Token::kind("proc_macro").is_keyword());
+        assert!(!Token::identifier("fn").is_keyword());
+        assert!(!Token::type_("String").is_keyword());
+    }
+
+    #[test]
+    fn string_constructors_delegate_to_named_constructors() {
+        assert_eq!(Token::kind("const"), Token::const_keyword());
+        assert_eq!(Token::kind("static"), Token::static_keyword());
+        assert_eq!(Token::keyword("impl"), Token::impl_keyword());
+        assert_eq!(Token::keyword("mut"), Token::mut_keyword());
+        assert_eq!(Token::mut_keyword().text(), "mut");
+        assert_eq!(Token::keyword("dyn"), Token::dyn_keyword());
+        assert_eq!(Token::dyn_keyword().text(), "dyn");
+        assert_eq!(Token::symbol("->"), Token::operator("->"));
+        assert_eq!(Token::symbol("=>"), Token::operator("=>"));
+        assert_eq!(Token::symbol("|"), Token::operator("|"));
+        assert_eq!(Token::symbol(":"), Token::Symbol(":".to_owned()));
+    }
+
+    #[test]
+    fn padded_operators_are_normalized_to_operators() {
+        let tokens = vec![Token::symbol(" + ")];
+        let expected = vec![Token::Whitespace, Token::operator("+"), Token::Whitespace];
+        assert_eq!(normalize_whitespace(tokens), expected);
+    }
+}
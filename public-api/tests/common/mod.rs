@@ -7,7 +7,7 @@ pub fn builder_for_crate(
     target_dir: impl AsRef<Path>,
 ) -> public_api::Builder {
     let json = rustdoc_json_path_for_crate(test_crate, target_dir);
-    public_api::Builder::from_rustdoc_json(json)
+    public_api::Builder::from_rustdoc_json(json).strip_derives(Vec::<String>::new())
 }
 
 /// Returns a builder for a so called "simplified" API, which is an API without
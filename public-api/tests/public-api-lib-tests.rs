@@ -8,7 +8,7 @@ use std::{
 };
 
 use expect_test::expect_file;
-use public_api::Error;
+use public_api::{tokens::Token, Error};
 
 use pretty_assertions::assert_eq;
 
@@ -26,7 +26,9 @@ fn public_api() -> Result<(), Box<dyn std::error::Error>> {
         .toolchain("nightly")
         .build()?;
 
-    let public_api = public_api::Builder::from_rustdoc_json(rustdoc_json).build()?;
+    let public_api = public_api::Builder::from_rustdoc_json(rustdoc_json)
+        .strip_derives(Vec::<String>::new())
+        .build()?;
 
     expect_test::expect_file!["public-api.txt"].assert_eq(&public_api.to_string());
 
@@ -94,6 +96,99 @@ fn diff_with_added_items() {
     );
 }
 
+/// Uses the `tokio` dependency of the `async` feature, so that the tests don't
+/// need a dev-dependency on `tokio`.
+#[cfg(feature = "async")]
+#[test]
+fn diff_with_added_items_async() {
+    fn assert_send<T: Send>(t: T) -> T {
+        t
+    }
+
+    let build_dir = tempdir().unwrap();
+    let build_dir2 = tempdir().unwrap();
+    let builder = |manifest_path, target_dir: &TempDir| {
+        rustdoc_json::Builder::default()
+            .toolchain("nightly")
+            .manifest_path(manifest_path)
+            .target_dir(target_dir.path())
+    };
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let diff = runtime.block_on(async {
+        let old = assert_send(public_api::compute_public_api_async(builder(
+            "../test-apis/example_api-v0.1.0/Cargo.toml",
+            &build_dir,
+        )))
+        .await
+        .unwrap();
+        let new = assert_send(public_api::compute_public_api_async(builder(
+            "../test-apis/example_api-v0.2.0/Cargo.toml",
+            &build_dir2,
+        )))
+        .await
+        .unwrap();
+        assert_send(public_api::diff::PublicApiDiff::between_async(old, new)).await
+    });
+
+    expect_file!["./expected-output/diff_with_added_items.txt"]
+        .assert_debug_eq(&DiffSnapshot(&diff));
+}
+
+#[test]
+fn diff_to_html() {
+    // Create independent build dirs so all tests can run in parallel
+    let build_dir = tempdir().unwrap();
+    let build_dir2 = tempdir().unwrap();
+
+    let old = public_api::Builder::from_rustdoc_json(rustdoc_json_path_for_crate(
+        "../test-apis/example_api-v0.1.0",
+        &build_dir,
+    ))
+    .build()
+    .unwrap();
+    let new = public_api::Builder::from_rustdoc_json(rustdoc_json_path_for_crate(
+        "../test-apis/example_api-v0.2.0",
+        &build_dir2,
+    ))
+    .build()
+    .unwrap();
+
+    let diff = public_api::diff::PublicApiDiff::between(old, new);
+    expect_file!["./expected-output/diff_with_added_items.html"].assert_eq(&diff.to_html());
+}
+
+#[test]
+fn diff_to_release_notes_md() {
+    // Create independent build dirs so all tests can run in parallel
+    let build_dir = tempdir().unwrap();
+    let build_dir2 = tempdir().unwrap();
+
+    let old = public_api::Builder::from_rustdoc_json(rustdoc_json_path_for_crate(
+        "../test-apis/example_api-v0.1.0",
+        &build_dir,
+    ))
+    .omit_blanket_impls(true)
+    .omit_auto_trait_impls(true)
+    .build()
+    .unwrap();
+    let new = public_api::Builder::from_rustdoc_json(rustdoc_json_path_for_crate(
+        "../test-apis/example_api-v0.3.0",
+        &build_dir2,
+    ))
+    .omit_blanket_impls(true)
+    .omit_auto_trait_impls(true)
+    .build()
+    .unwrap();
+
+    let diff = public_api::diff::PublicApiDiff::between(old, new);
+    expect_file!["./expected-output/release_notes_v0.1.0_to_v0.3.0.md"]
+        .assert_eq(&diff.to_release_notes_md("0.3.0", "2024-01-01"));
+}
+
 #[test]
 fn empty_diff() {
     // Create independent build dirs so all tests can run in parallel
@@ -179,6 +274,145 @@ pub enum Foo {
     assert_no_textual_public_api_diff(v1.json_path, v2.json_path);
 }
 
+#[test]
+fn diff_empty_when_adding_boilerplate_derive() {
+    let v1 = rustdoc_json_for_lib("pub struct Foo;");
+    let v2 = rustdoc_json_for_lib("#[derive(Debug, PartialEq, Eq, Hash)] pub struct Foo;");
+
+    let build = |json_path| {
+        public_api::Builder::from_rustdoc_json(json_path)
+            .build()
+            .unwrap()
+    };
+
+    let diff = public_api::diff::PublicApiDiff::between(build(&v1.json_path), build(&v2.json_path));
+    assert!(diff.is_empty(), "{diff:#?}");
+}
+
+#[test]
+fn losing_auto_trait_is_breaking() {
+    let v1 = rustdoc_json_for_lib("pub struct Foo;");
+    let v2 = rustdoc_json_for_lib("pub struct Foo(*const u8);");
+
+    let build = |json_path| {
+        public_api::Builder::from_rustdoc_json(json_path)
+            .omit_blanket_impls(true)
+            .build()
+            .unwrap()
+    };
+
+    let diff = public_api::diff::PublicApiDiff::between(build(&v1.json_path), build(&v2.json_path));
+    let send = diff
+        .changed
+        .iter()
+        .find(|c| c.old.to_string() == "impl core::marker::Send for lib::Foo")
+        .unwrap();
+    assert_eq!(
+        send.new.to_string(),
+        "impl !core::marker::Send for lib::Foo"
+    );
+    assert!(send
+        .new
+        .tokens()
+        .any(|token| *token == public_api::tokens::Token::NegativeImpl));
+    assert_eq!(
+        public_api::Compatibility::of(&diff),
+        public_api::Compatibility::Breaking
+    );
+}
+
+#[test]
+fn path_cmp_ignores_tokens() {
+    let v1 = rustdoc_json_for_lib("pub fn f(_: u8) {}\npub fn g() {}");
+    let v2 = rustdoc_json_for_lib("pub fn f(_: u16) {}\npub fn g() {}");
+
+    let item = |json_path, path: &str| {
+        public_api::Builder::from_rustdoc_json(json_path)
+            .build()
+            .unwrap()
+            .into_items()
+            .find(|item| item.path().join("::") == path)
+            .unwrap()
+    };
+    let f1 = item(&v1.json_path, "lib::f");
+    let f2 = item(&v2.json_path, "lib::f");
+    let g = item(&v1.json_path, "lib::g");
+
+    assert_ne!(f1, f2);
+    assert_eq!(f1.path_cmp(&f2), std::cmp::Ordering::Equal);
+    assert_eq!(f1.path_cmp(&g), std::cmp::Ordering::Less);
+
+    let by_path: std::collections::BTreeSet<_> = [f1, f2, g]
+        .into_iter()
+        .map(public_api::PublicItemsByPath)
+        .collect();
+    assert_eq!(by_path.len(), 2);
+}
+
+#[test]
+fn follow_reexports() {
+    let root = tempdir().unwrap();
+    let example_api = fs::canonicalize("../test-apis/example_api-v0.1.0").unwrap();
+    fs::write(
+        root.path().join("Cargo.toml"),
+        format!(
+            "[package]\n\
+            name = \"lib\"\n\
+            version = \"0.1.0\"\n\
+            edition = \"2021\"\n\
+            [lib]\n\
+            path = \"lib.rs\"\n\
+            [dependencies]\n\
+            example_api = {{ path = {example_api:?} }}\n\
+            "
+        ),
+    )
+    .unwrap();
+    fs::write(root.path().join("lib.rs"), "pub use example_api::Struct;").unwrap();
+    let json_path = rustdoc_json_path_for_temp_crate(&root);
+
+    let build = |follow_reexports| {
+        public_api::Builder::from_rustdoc_json(&json_path)
+            .omit_blanket_impls(true)
+            .omit_auto_trait_impls(true)
+            .follow_reexports(follow_reexports)
+            .build()
+            .unwrap()
+    };
+    // Items of other crates are not included in the rustdoc JSON, so the use
+    // itself is shown
+    let reexported = build(false).to_string();
+    let followed = build(true).to_string();
+    assert_eq!(reexported, "pub mod lib\npub use lib::Struct\n");
+    assert_eq!(followed, "pub use example_api::Struct\npub mod lib\n");
+
+    let diff = public_api::diff::PublicApiDiff::between(build(false), build(true));
+    assert!(
+        !diff.removed.is_empty() && !diff.added.is_empty(),
+        "{diff:#?}"
+    );
+}
+
+#[test]
+fn strip_derives_keeps_other_derives() {
+    let lib = rustdoc_json_for_lib("#[derive(Debug, PartialOrd, PartialEq)] pub struct Foo;");
+    let api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .strip_derives(["Debug", "PartialEq"])
+        .build()
+        .unwrap()
+        .to_string();
+
+    assert!(
+        api.contains("impl core::cmp::PartialOrd for lib::Foo"),
+        "{api}"
+    );
+    assert!(!api.contains("impl core::fmt::Debug for lib::Foo"), "{api}");
+    assert!(
+        !api.contains("impl core::cmp::PartialEq for lib::Foo"),
+        "{api}"
+    );
+}
+
 #[test]
 fn diff_with_removed_items() {
     // Create independent build dirs so all tests can run in parallel
@@ -247,6 +481,1176 @@ fn invalid_json() {
     write!(invalid_json.as_file(), "}}}}}}}}}}").unwrap();
     let result = public_api::Builder::from_rustdoc_json(invalid_json.path()).build();
     assert!(matches!(result, Err(Error::SerdeJsonError(_))));
+
+    // The JSON parse error is available as the source of the error
+    let error = result.unwrap_err();
+    let source = std::error::Error::source(&error).unwrap();
+    assert!(source.is::<serde_json::Error>(), "{source:?}");
+
+    // And its message is included, so it is not lost when only the error is
+    // displayed
+    assert_eq!(error.to_string(), format!("Failed to parse JSON: {source}"));
+}
+
+#[test]
+fn io_error_has_source() {
+    let result = public_api::Builder::from_rustdoc_json("does/not/exist.json").build();
+    let error = result.unwrap_err();
+    assert!(matches!(error, Error::IoError(_)));
+    let source = std::error::Error::source(&error).unwrap();
+    assert_eq!(
+        source.downcast_ref::<std::io::Error>().unwrap().kind(),
+        std::io::ErrorKind::NotFound
+    );
+}
+
+#[test]
+fn public_api_from_rustdoc_json_bytes() {
+    // Create independent build dir so all tests can run in parallel
+    let build_dir = tempdir().unwrap();
+
+    let json_path = rustdoc_json_path_for_crate("../test-apis/example_api-v0.3.0", &build_dir);
+    let from_bytes =
+        public_api::public_api_from_rustdoc_json_bytes(&fs::read(&json_path).unwrap()).unwrap();
+    let from_builder = public_api::Builder::from_rustdoc_json(json_path)
+        .build()
+        .unwrap();
+    assert_eq!(from_bytes.to_string(), from_builder.to_string());
+}
+
+#[test]
+fn public_api_from_invalid_utf8_bytes() {
+    let result = public_api::public_api_from_rustdoc_json_bytes(b"{\"root\": \"\xff\"}");
+    assert!(matches!(result, Err(Error::SerdeJsonError(_))));
+}
+
+#[test]
+fn diff_between_strings() {
+    let build_dir = tempdir().unwrap();
+    let old_json = fs::read_to_string(rustdoc_json_path_for_crate(
+        "../test-apis/example_api-v0.1.0",
+        &build_dir,
+    ))
+    .unwrap();
+    let build_dir = tempdir().unwrap();
+    let new_json = fs::read_to_string(rustdoc_json_path_for_crate(
+        "../test-apis/example_api-v0.2.0",
+        &build_dir,
+    ))
+    .unwrap();
+
+    let from_strings = public_api::diff::PublicApiDiff::between_strings(
+        &old_json,
+        &new_json,
+        public_api::diff::DiffOptions::default(),
+    )
+    .unwrap();
+    let manually = public_api::diff::PublicApiDiff::between(
+        public_api::public_api_from_rustdoc_json_bytes(old_json.as_bytes()).unwrap(),
+        public_api::public_api_from_rustdoc_json_bytes(new_json.as_bytes()).unwrap(),
+    );
+    assert_eq!(from_strings, manually);
+    assert!(!from_strings.is_empty());
+
+    let result = public_api::diff::PublicApiDiff::between_strings(
+        &old_json,
+        "{",
+        public_api::diff::DiffOptions::default(),
+    );
+    assert!(matches!(result, Err(Error::SerdeJsonError(_))));
+}
+
+#[test]
+fn diff_between_snapshots() {
+    let build_dir = tempdir().unwrap();
+    let old_json = rustdoc_json_path_for_crate("../test-apis/example_api-v0.1.0", &build_dir);
+    let build_dir = tempdir().unwrap();
+    let new_json = rustdoc_json_path_for_crate("../test-apis/example_api-v0.2.0", &build_dir);
+
+    let snapshot = |json_path| {
+        public_api::Builder::from_rustdoc_json(json_path)
+            .build_snapshot("nightly")
+            .unwrap()
+    };
+    let old = snapshot(&old_json);
+    let new = snapshot(&new_json);
+    assert_eq!(old.name_and_version(), "example_api 0.1.0");
+    assert_eq!(new.name_and_version(), "example_api 0.2.0");
+    assert_eq!(old.toolchain, "nightly");
+
+    let build = |json_path| {
+        public_api::Builder::from_rustdoc_json(json_path)
+            .build()
+            .unwrap()
+    };
+    assert_eq!(
+        public_api::diff::PublicApiDiff::between_snapshots(&old, &new),
+        public_api::diff::PublicApiDiff::between(build(&old_json), build(&new_json)),
+    );
+}
+
+#[test]
+fn reference_tokens() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub struct T;\n\
+        pub fn owned(t: T) {}\n\
+        pub fn shared(t: &T) {}\n\
+        pub fn mutable(t: &mut T) {}\n\
+        pub fn boxed(t: Box<T>) {}\n\
+        ",
+    );
+    let api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .build()
+        .unwrap();
+    let param_tokens = |name: &str| -> Vec<Token> {
+        api.items()
+            .find(|item| item.path().last().map(String::as_str) == Some(name))
+            .unwrap()
+            .tokens()
+            .skip_while(|token| token.text() != "(")
+            .skip(4) // `(`, `t`, `:` and ` `
+            .take_while(|token| token.text() != ")")
+            .cloned()
+            .collect()
+    };
+    let t = || {
+        [
+            Token::Identifier("lib".into()),
+            Token::PathSeparator,
+            Token::Type("T".into()),
+        ]
+    };
+
+    assert_eq!(param_tokens("owned"), t());
+    assert_eq!(
+        param_tokens("shared"),
+        [&[Token::SharedRef][..], &t()].concat()
+    );
+    assert_eq!(
+        param_tokens("mutable"),
+        [
+            &[
+                Token::MutRef,
+                Token::Keyword("mut".into()),
+                Token::Whitespace
+            ][..],
+            &t()
+        ]
+        .concat()
+    );
+    assert_eq!(
+        param_tokens("boxed").first(),
+        Some(&Token::Identifier("alloc".into()))
+    );
+    assert!(!param_tokens("boxed").contains(&Token::SharedRef));
+}
+
+#[test]
+fn generic_tokens() {
+    let lib = rustdoc_json_for_lib("pub fn generic<T>(v: Vec<T>) {}");
+    let api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .build()
+        .unwrap();
+    let item = api
+        .items()
+        .find(|item| item.path().last().map(String::as_str) == Some("generic"))
+        .unwrap();
+    let angle_brackets: Vec<_> = item
+        .tokens()
+        .filter(|token| matches!(token.text(), "<" | ">"))
+        .collect();
+
+    assert_eq!(
+        angle_brackets,
+        [
+            &Token::GenericOpen,
+            &Token::GenericClose,
+            &Token::GenericOpen,
+            &Token::GenericClose
+        ]
+    );
+}
+
+#[test]
+fn impl_trait_tokens() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub trait Trait {}\n\
+        pub struct Trait2;\n\
+        pub fn arg(_: impl Trait) {}\n\
+        pub fn ret() -> impl Iterator<Item = Trait2> { std::iter::empty() }\n\
+        pub fn concrete(_: Trait2) {}\n\
+        pub fn maybe_sized(_: &(impl Trait + ?Sized)) {}\n\
+        ",
+    );
+    let api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .build()
+        .unwrap();
+    let item = |name: &str| {
+        api.items()
+            .find(|item| item.path().last().map(String::as_str) == Some(name))
+            .unwrap()
+    };
+    let impl_traits = |name: &str| -> Vec<Token> {
+        item(name)
+            .tokens()
+            .filter(|token| matches!(token, Token::ImplTrait(_)))
+            .cloned()
+            .collect()
+    };
+
+    assert_eq!(impl_traits("arg"), [Token::ImplTrait("Trait".into())]);
+    assert_eq!(impl_traits("ret"), [Token::ImplTrait("Iterator".into())]);
+    assert_eq!(impl_traits("concrete"), []);
+    assert_eq!(
+        item("maybe_sized").to_string(),
+        "pub fn lib::maybe_sized(&impl lib::Trait + ?core::marker::Sized)"
+    );
+}
+
+#[test]
+fn tuple_field_tokens() {
+    let build = |src: &str| {
+        let lib = rustdoc_json_for_lib(src);
+        public_api::Builder::from_rustdoc_json(&lib.json_path)
+            .build()
+            .unwrap()
+    };
+    let api = build(
+        "\
+        pub struct Struct(pub i32, pub Vec<u8>);\n\
+        pub enum Enum { Variant(bool) }\n\
+        pub fn f(t: (i32, bool)) {}\n\
+        ",
+    );
+    let tuple_tokens = |path: &str| -> Vec<Token> {
+        let item = api
+            .items()
+            .find(|item| item.path().join("::") == path)
+            .unwrap();
+        item.tokens()
+            .skip_while(|token| **token != Token::TupleOpen)
+            .cloned()
+            .collect()
+    };
+
+    assert_eq!(
+        tuple_tokens("lib::Struct"),
+        [
+            Token::TupleOpen,
+            Token::Qualifier("pub".into()),
+            Token::Whitespace,
+            Token::Primitive("i32".into()),
+            Token::Symbol(",".into()),
+            Token::Whitespace,
+            Token::Qualifier("pub".into()),
+            Token::Whitespace,
+            Token::Identifier("alloc".into()),
+            Token::PathSeparator,
+            Token::Identifier("vec".into()),
+            Token::PathSeparator,
+            Token::Type("Vec".into()),
+            Token::GenericOpen,
+            Token::Primitive("u8".into()),
+            Token::GenericClose,
+            Token::TupleClose,
+        ]
+    );
+    assert_eq!(
+        tuple_tokens("lib::Enum::Variant"),
+        [
+            Token::TupleOpen,
+            Token::Primitive("bool".into()),
+            Token::TupleClose
+        ]
+    );
+    assert_eq!(tuple_tokens("lib::f"), []);
+
+    // The types of tuple fields are ordinary tokens, so changes to them are
+    // classified like changes to other types
+    let diff = public_api::diff::PublicApiDiff::between(
+        build("pub struct Key(pub [u8; 16]);"),
+        build("pub struct Key(pub [u8; 32]);"),
+    );
+    assert_eq!(
+        diff.changed[0].change_kind(),
+        public_api::diff::ChangeKind::ArraySizeChanged {
+            old_size: "16".to_owned(),
+            new_size: "32".to_owned(),
+        }
+    );
+}
+
+#[test]
+fn include_docs() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        /// A documented function.\n\
+        ///\n\
+        /// With two paragraphs.\n\
+        pub fn documented() {}\n\
+        pub fn undocumented() {}\n\
+        ",
+    );
+    assert_public_api(
+        public_api::Builder::from_rustdoc_json(&lib.json_path).include_docs(true),
+        "./expected-output/include_docs.txt",
+    );
+}
+
+#[test]
+fn doc_comment() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        /// A documented function.\n\
+        ///\n\
+        /// With two paragraphs.\n\
+        pub fn documented() {}\n\
+        pub fn undocumented() {}\n\
+        ",
+    );
+    let doc_comment = |include_docs, name: &str| {
+        public_api::Builder::from_rustdoc_json(&lib.json_path)
+            .include_docs(include_docs)
+            .build()
+            .unwrap()
+            .items()
+            .find(|item| item.path().last().map(String::as_str) == Some(name))
+            .unwrap()
+            .doc_comment()
+    };
+    assert_eq!(
+        doc_comment(true, "documented").as_deref(),
+        Some("A documented function.")
+    );
+    assert_eq!(doc_comment(true, "undocumented"), None);
+    assert_eq!(doc_comment(false, "documented"), None);
+}
+
+#[test]
+fn redact_doc_comments() {
+    let old = rustdoc_json_for_lib(
+        "\
+        /// Internal project name.\n\
+        pub fn documented() {}\n\
+        pub fn undocumented() {}\n\
+        ",
+    );
+    let new = rustdoc_json_for_lib(
+        "\
+        /// Other internal project name.\n\
+        pub fn documented() {}\n\
+        /// Newly documented.\n\
+        pub fn undocumented() {}\n\
+        ",
+    );
+    let build = |lib: &Path| {
+        public_api::Builder::from_rustdoc_json(lib)
+            .include_docs(true)
+            .redact_doc_comments(true)
+            .build()
+            .unwrap()
+    };
+    let old = build(&old.json_path);
+    let new = build(&new.json_path);
+
+    assert!(!old.to_string().contains("project"));
+    let documented = old
+        .items()
+        .find(|item| item.doc_comment().is_some())
+        .unwrap();
+    assert_eq!(documented.doc_comment().as_deref(), Some("[redacted]"));
+
+    let diff = public_api::diff::PublicApiDiff::between(old, new);
+    let changed: Vec<_> = diff.changed.iter().map(|c| c.new.to_string()).collect();
+    assert_eq!(changed, ["/// [redacted]\npub fn lib::undocumented()"]);
+}
+
+#[test]
+fn bounds() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub fn zero<T>(t: T) {}\n\
+        pub fn one<T: Clone>(t: T) {}\n\
+        pub fn many<T: Clone + std::fmt::Debug, const N: usize>(t: [T; N]) {}\n\
+        pub fn where_clause<T, U>(t: T, u: U) where T: Copy, U: Send + Sync {}\n\
+        ",
+    );
+    let public_api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .build()
+        .unwrap();
+    let bounds = |name: &str| {
+        public_api
+            .items()
+            .find(|item| item.path().last().map(String::as_str) == Some(name))
+            .unwrap()
+            .bounds()
+    };
+    let bound = |param: &str, traits: &[&str]| public_api::Bound {
+        param: param.to_owned(),
+        traits: traits.iter().map(|t| (*t).to_owned()).collect(),
+    };
+
+    assert_eq!(bounds("zero"), []);
+    assert_eq!(bounds("one"), [bound("T", &["core::clone::Clone"])]);
+    assert_eq!(
+        bounds("many"),
+        [bound("T", &["core::clone::Clone", "core::fmt::Debug"])]
+    );
+    assert_eq!(
+        bounds("where_clause"),
+        [
+            bound("T", &["core::marker::Copy"]),
+            bound("U", &["core::marker::Send", "core::marker::Sync"]),
+        ]
+    );
+}
+
+#[test]
+fn as_trait_impl() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub trait Trait {}\n\
+        pub trait Generic<T> {}\n\
+        pub struct Type;\n\
+        impl Type { pub fn f() {} }\n\
+        impl Trait for Type {}\n\
+        impl<T> Generic<T> for Type {}\n\
+        impl Trait for &Type {}\n\
+        ",
+    );
+    let public_api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .omit_blanket_impls(true)
+        .omit_auto_trait_impls(true)
+        .build()
+        .unwrap();
+    let as_trait_impl = |text: &str| {
+        public_api
+            .items()
+            .find(|item| item.to_string() == text)
+            .unwrap()
+            .as_trait_impl()
+    };
+    let trait_impl = |trait_path: &[&str], for_type: &str| public_api::TraitImpl {
+        trait_path: trait_path.iter().map(|t| (*t).to_owned()).collect(),
+        for_type: for_type.to_owned(),
+    };
+
+    assert_eq!(
+        as_trait_impl("impl lib::Trait for lib::Type"),
+        Some(trait_impl(&["lib", "Trait"], "lib::Type"))
+    );
+    assert_eq!(
+        as_trait_impl("impl<T> lib::Generic<T> for lib::Type"),
+        Some(trait_impl(&["lib", "Generic"], "lib::Type"))
+    );
+    assert_eq!(
+        as_trait_impl("impl lib::Trait for &lib::Type"),
+        Some(trait_impl(&["lib", "Trait"], "&lib::Type"))
+    );
+    assert_eq!(as_trait_impl("impl lib::Type"), None);
+    assert_eq!(as_trait_impl("pub fn lib::Type::f()"), None);
+}
+
+#[test]
+fn deprecated_items() {
+    let build = |src: &str| {
+        let lib = rustdoc_json_for_lib(src);
+        public_api::Builder::from_rustdoc_json(&lib.json_path)
+            .omit_blanket_impls(true)
+            .omit_auto_trait_impls(true)
+            .build()
+            .unwrap()
+    };
+    let old = build("pub fn f() {}\npub struct S;");
+    let new = build("#[deprecated(note = \"Use g\")] pub fn f() {}\n#[deprecated] pub struct S;");
+    assert_eq!(
+        new.to_string(),
+        "\
+pub mod lib
+#[deprecated] pub struct lib::S
+#[deprecated(note = \"Use g\")] pub fn lib::f()
+"
+    );
+
+    let diff = public_api::diff::PublicApiDiff::between(old, new);
+    assert_eq!(diff.changed.len(), 2);
+    for changed in &diff.changed {
+        assert_eq!(
+            changed.severity(),
+            public_api::diff::ChangeSeverity::Deprecation
+        );
+    }
+}
+
+#[test]
+fn lifetime_params() {
+    let build = |src: &str| {
+        let lib = rustdoc_json_for_lib(src);
+        public_api::Builder::from_rustdoc_json(&lib.json_path)
+            .build()
+            .unwrap()
+    };
+    let old = build("pub fn f<'a>(x: &'a u8, y: &'a u8, z: &'static u8) {}");
+    let new = build("pub fn f<'a, 'b>(x: &'a u8, y: &'b u8, z: &'static u8) {}");
+    let f = |api: &public_api::PublicApi| api.items().find(|item| item.path_depth() == 1).cloned();
+
+    let old_f = f(&old).unwrap();
+    assert!(old_f.has_lifetime_params());
+    assert_eq!(old_f.lifetime_param_count(), 1);
+    assert_eq!(f(&new).unwrap().lifetime_param_count(), 2);
+
+    let diff = public_api::diff::PublicApiDiff::between(old, new);
+    assert_eq!(
+        diff.changed[0].change_kind(),
+        public_api::diff::ChangeKind::LifetimeParamsChanged {
+            old_count: 1,
+            new_count: 2
+        }
+    );
+}
+
+#[test]
+fn public_api_graph() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub mod m {\n\
+            pub struct S;\n\
+            impl S { pub fn new() -> S { S } }\n\
+        }\n\
+        pub fn f(s: m::S) {}\n\
+        ",
+    );
+    let public_api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .omit_auto_trait_impls(true)
+        .omit_blanket_impls(true)
+        .build()
+        .unwrap();
+    let graph = public_api::PublicApiGraph::from_items(public_api.into_items().collect());
+    let graph_ = graph.graph();
+    let mut edges: Vec<_> = graph_
+        .raw_edges()
+        .iter()
+        .map(|edge| {
+            let from = &graph_[edge.source()];
+            let to = &graph_[edge.target()];
+            format!("{from} --{:?}--> {to}", edge.weight)
+        })
+        .collect();
+    edges.sort();
+
+    assert_eq!(
+        edges,
+        [
+            "pub fn lib::f(s: lib::m::S) --References--> pub struct lib::m::S",
+            "pub fn lib::m::S::new() -> lib::m::S --References--> pub struct lib::m::S",
+            "pub mod lib --Contains--> pub fn lib::f(s: lib::m::S)",
+            "pub mod lib --Contains--> pub mod lib::m",
+            "pub mod lib::m --Contains--> pub struct lib::m::S",
+            "pub struct lib::m::S --Contains--> pub fn lib::m::S::new() -> lib::m::S",
+            "pub struct lib::m::S --Implements--> impl lib::m::S",
+        ]
+    );
+    assert_eq!(graph.reachable_from_root().len(), graph_.node_count());
+    // `lib::m::S` contains `lib::m::S::new`, which references `lib::m::S`
+    assert_eq!(graph.cycles().len(), 1);
+    // E.g. `lib` -> `lib::m` -> `lib::m::S` -> `lib::m::S::new`
+    assert_eq!(graph.diameter(), 3);
+}
+
+#[test]
+fn public_api_graph_cycles() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub struct A { pub b: Option<Box<B>> }\n\
+        pub struct B { pub a: Option<A> }\n\
+        pub struct C;\n\
+        ",
+    );
+    let public_api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .omit_auto_trait_impls(true)
+        .omit_blanket_impls(true)
+        .strip_derives(Vec::<String>::new())
+        .build()
+        .unwrap();
+    let graph = public_api::PublicApiGraph::from_items(public_api.into_items().collect());
+    let cycles: Vec<Vec<_>> = graph
+        .cycles()
+        .into_iter()
+        .map(|cycle| {
+            let mut cycle: Vec<_> = cycle.into_iter().map(ToString::to_string).collect();
+            cycle.sort();
+            cycle
+        })
+        .collect();
+
+    assert_eq!(
+        cycles,
+        [[
+            "pub lib::A::b: core::option::Option<alloc::boxed::Box<lib::B>>",
+            "pub lib::B::a: core::option::Option<lib::A>",
+            "pub struct lib::A",
+            "pub struct lib::B",
+        ]]
+    );
+}
+
+#[test]
+fn public_api_module_tree() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub mod outer {\n\
+            pub mod inner {\n\
+                pub struct S;\n\
+                impl S { pub fn new() -> S { S } }\n\
+            }\n\
+            pub fn g() {}\n\
+        }\n\
+        pub mod other {}\n\
+        pub fn f() {}\n\
+        ",
+    );
+    let json = fs::read_to_string(&lib.json_path).unwrap();
+    let tree = public_api::public_api_module_tree(&json).unwrap();
+
+    fn render(tree: &public_api::ModuleTree, lines: &mut Vec<String>, indent: usize) {
+        lines.push(format!("{:indent$}{}", "", tree.name));
+        // Keep the expected output short by skipping impls and their items
+        let items = (tree.items.iter())
+            .filter(|item| item.associated_item_of().is_none())
+            .filter(|item| !item.to_string().starts_with("impl"));
+        for item in items {
+            lines.push(format!("{:indent$}  {item}", ""));
+        }
+        for child in &tree.children {
+            render(child, lines, indent + 2);
+        }
+    }
+    let mut lines = vec![];
+    render(&tree, &mut lines, 0);
+    assert_eq!(
+        lines,
+        [
+            "lib",
+            "  pub mod lib",
+            "  pub fn lib::f()",
+            "  other",
+            "    pub mod lib::other",
+            "  outer",
+            "    pub mod lib::outer",
+            "    pub fn lib::outer::g()",
+            "    inner",
+            "      pub mod lib::outer::inner",
+            "      pub struct lib::outer::inner::S",
+        ]
+    );
+
+    let public_api = public_api::public_api_from_rustdoc_json_bytes(json.as_bytes()).unwrap();
+    assert_eq!(tree.flatten(), public_api.into_items().collect::<Vec<_>>());
+}
+
+#[test]
+fn build_module_tree_uses_builder_options() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub mod m {\n\
+            #[derive(Clone)]\n\
+            pub struct S;\n\
+        }\n\
+        ",
+    );
+    let builder = || {
+        public_api::Builder::from_rustdoc_json(&lib.json_path)
+            .omit_auto_trait_impls(true)
+            .omit_blanket_impls(true)
+            .strip_derives(Vec::<String>::new())
+    };
+    let tree = builder().build_module_tree().unwrap();
+
+    let m = &tree.children[0];
+    let items: Vec<_> = m.items.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        items,
+        [
+            "pub mod lib::m",
+            "pub struct lib::m::S",
+            "impl core::clone::Clone for lib::m::S",
+            "pub fn lib::m::S::clone(&self) -> lib::m::S",
+        ]
+    );
+    let public_api = builder().build().unwrap();
+    assert_eq!(tree.flatten(), public_api.into_items().collect::<Vec<_>>());
+}
+
+#[test]
+fn feature_gates() {
+    let build_dir = tempdir().unwrap();
+    let public_api = builder_for_crate("../test-apis/features", &build_dir)
+        .build()
+        .unwrap();
+    let feature_gates = |path: &str| {
+        public_api
+            .items()
+            .find(|item| item.path().join("::") == path)
+            .map(|item| (item.cfg_conditions().to_vec(), item.feature_gates()))
+            .unwrap()
+    };
+
+    assert_eq!(feature_gates("features::AStruct"), (vec![], vec![]));
+    assert_eq!(
+        feature_gates("features::AStruct::feature_a"),
+        (
+            vec!["feature = \"feature_a\"".to_owned()],
+            vec!["feature_a".to_owned()]
+        )
+    );
+}
+
+#[test]
+fn expand_macros() {
+    // Use a target dir within the crate, so that the source files can be found
+    // relative to the rustdoc JSON
+    let json_path = rustdoc_json_path_for_crate(
+        "../test-apis/macro_expansions",
+        "../test-apis/macro_expansions/target",
+    );
+    let items = |expand_macros| {
+        public_api::Builder::from_rustdoc_json(&json_path)
+            .omit_blanket_impls(true)
+            .omit_auto_trait_impls(true)
+            .strip_derives(Vec::<String>::new())
+            .expand_macros(expand_macros)
+            .build()
+            .unwrap()
+            .items()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        items(true),
+        [
+            "pub mod macro_expansions",
+            "pub struct macro_expansions::Derived",
+            "impl core::clone::Clone for macro_expansions::Derived",
+            "pub fn macro_expansions::Derived::clone(&self) -> macro_expansions::Derived",
+            "pub struct macro_expansions::FromPublicDeriveMacro",
+            "pub fn macro_expansions::from_macro_rules()",
+            "pub fn macro_expansions::not_from_macro()",
+        ]
+    );
+    assert_eq!(
+        items(false),
+        [
+            "pub mod macro_expansions",
+            "pub struct macro_expansions::Derived",
+            "pub fn macro_expansions::not_from_macro()",
+        ]
+    );
+
+    // Outside of the workspace, the source files can't be found
+    let dir = tempdir().unwrap();
+    let moved_json_path = dir.path().join("macro_expansions.json");
+    std::fs::copy(&json_path, &moved_json_path).unwrap();
+    assert!(matches!(
+        public_api::Builder::from_rustdoc_json(moved_json_path)
+            .expand_macros(false)
+            .build(),
+        Err(public_api::Error::SourceFileUnreadable(_))
+    ));
+}
+
+#[test]
+fn max_depth() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub fn root_fn() {}\n\
+        pub struct RootStruct;\n\
+        impl RootStruct {\n\
+            pub fn method() {}\n\
+        }\n\
+        pub mod nested {\n\
+            pub fn nested_fn() {}\n\
+        }\n\
+        ",
+    );
+    assert_public_api(
+        public_api::Builder::from_rustdoc_json(&lib.json_path)
+            .max_depth(Some(1))
+            .omit_blanket_impls(true)
+            .omit_auto_trait_impls(true),
+        "./expected-output/max_depth_1.txt",
+    );
+}
+
+#[test]
+fn associated_item_of() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub fn free_fn() {}\n\
+        pub struct Struct;\n\
+        impl Struct {\n\
+            pub fn method() {}\n\
+        }\n\
+        ",
+    );
+    let api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .build()
+        .unwrap();
+    let parent_of = |name: &str| {
+        api.items()
+            .find(|item| item.to_string() == format!("pub fn lib::{name}()"))
+            .unwrap()
+            .associated_item_of()
+    };
+
+    assert_eq!(parent_of("free_fn"), None);
+    assert_eq!(
+        parent_of("Struct::method"),
+        Some(&["lib".to_owned(), "Struct".to_owned()][..])
+    );
+}
+
+#[test]
+fn enclosing_type() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub fn free_fn() {}\n\
+        pub enum Enum { Variant }\n\
+        pub struct Struct { pub field: u8 }\n\
+        impl Struct {\n\
+            pub fn method() {}\n\
+        }\n\
+        pub trait Trait {\n\
+            type Associated;\n\
+        }\n\
+        ",
+    );
+    let api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .build()
+        .unwrap();
+    let enclosing_type_of = |path: &str| {
+        let item = api
+            .items()
+            .find(|item| item.path().join("::") == path)
+            .unwrap();
+        item.enclosing_type().map(|path| path.join("::"))
+    };
+
+    assert_eq!(enclosing_type_of("lib::free_fn"), None);
+    assert_eq!(enclosing_type_of("lib::Enum"), None);
+    assert_eq!(
+        enclosing_type_of("lib::Enum::Variant"),
+        Some("lib::Enum".into())
+    );
+    assert_eq!(
+        enclosing_type_of("lib::Struct::field"),
+        Some("lib::Struct".into())
+    );
+    assert_eq!(
+        enclosing_type_of("lib::Struct::method"),
+        Some("lib::Struct".into())
+    );
+    assert_eq!(
+        enclosing_type_of("lib::Trait::Associated"),
+        Some("lib::Trait".into())
+    );
+}
+
+#[test]
+fn item_kind_predicates() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub fn function() {}\n\
+        pub struct Struct;\n\
+        pub enum Enum {}\n\
+        pub trait Trait {}\n\
+        pub type TypeAlias = u8;\n\
+        pub mod module {}\n\
+        ",
+    );
+    let api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .omit_blanket_impls(true)
+        .omit_auto_trait_impls(true)
+        .build()
+        .unwrap();
+    let predicates = |path: &str| {
+        let item = api
+            .items()
+            .find(|item| item.path_depth() == 1 && item.to_string().contains(path))
+            .unwrap();
+        [
+            item.is_function(),
+            item.is_struct(),
+            item.is_enum(),
+            item.is_trait(),
+            item.is_type_alias(),
+        ]
+    };
+
+    assert_eq!(
+        predicates("lib::function"),
+        [true, false, false, false, false]
+    );
+    assert_eq!(
+        predicates("lib::Struct"),
+        [false, true, false, false, false]
+    );
+    assert_eq!(predicates("lib::Enum"), [false, false, true, false, false]);
+    assert_eq!(predicates("lib::Trait"), [false, false, false, true, false]);
+    assert_eq!(
+        predicates("lib::TypeAlias"),
+        [false, false, false, false, true]
+    );
+    assert_eq!(
+        predicates("lib::module"),
+        [false, false, false, false, false]
+    );
+}
+
+#[test]
+fn param_count() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub fn no_params() {}\n\
+        pub fn two_params<F: Fn(u8, u8)>(_f: F, _t: (u8, u8)) {}\n\
+        pub struct Struct;\n\
+        impl Struct {\n\
+            pub fn by_ref(&self) {}\n\
+            pub fn by_mut_ref(&mut self, _a: [u8; 2], _b: Option<Vec<u8>>) {}\n\
+            pub fn by_value(self) {}\n\
+        }\n\
+        ",
+    );
+    let api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .omit_blanket_impls(true)
+        .omit_auto_trait_impls(true)
+        .build()
+        .unwrap();
+    let param_count = |path: &str| {
+        api.items()
+            .find(|item| item.path().join("::") == path)
+            .unwrap()
+            .param_count()
+    };
+
+    assert_eq!(param_count("lib::no_params"), Some(0));
+    assert_eq!(param_count("lib::two_params"), Some(2));
+    assert_eq!(param_count("lib::Struct::by_ref"), Some(1));
+    assert_eq!(param_count("lib::Struct::by_mut_ref"), Some(3));
+    assert_eq!(param_count("lib::Struct::by_value"), Some(1));
+    assert_eq!(param_count("lib::Struct"), None);
+}
+
+#[test]
+fn return_type_tokens() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub fn unit() {}\n\
+        pub fn generic<T>(t: T) -> T where T: Copy { t }\n\
+        pub fn result(f: impl Fn() -> u8) -> Result<u8, ()> { Ok(f()) }\n\
+        pub fn impl_trait() -> impl Iterator<Item = u8> { std::iter::empty() }\n\
+        pub fn dyn_trait() -> Box<dyn std::fmt::Debug> { Box::new(0) }\n\
+        pub struct Struct;\n\
+        ",
+    );
+    let api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .build()
+        .unwrap();
+    let return_type = |path: &str| {
+        api.items()
+            .find(|item| item.path().join("::") == path)
+            .unwrap()
+            .return_type_tokens()
+            .map(|tokens| tokens.iter().map(Token::text).collect::<String>())
+    };
+
+    assert_eq!(return_type("lib::unit"), None);
+    assert_eq!(return_type("lib::generic").as_deref(), Some("T"));
+    assert_eq!(
+        return_type("lib::result").as_deref(),
+        Some("core::result::Result<u8, ()>")
+    );
+    assert_eq!(
+        return_type("lib::impl_trait").as_deref(),
+        Some("impl core::iter::traits::iterator::Iterator<Item = u8>")
+    );
+    assert_eq!(
+        return_type("lib::dyn_trait").as_deref(),
+        Some("alloc::boxed::Box<dyn core::fmt::Debug>")
+    );
+    assert_eq!(return_type("lib::Struct"), None);
+}
+
+#[test]
+fn normalize_expands_self_type() {
+    let lib = rustdoc_json_for_lib(
+        "\
+        pub struct Wrapper<T>(T);\n\
+        impl<T> Wrapper<T> { pub fn new(t: T) -> Self { Self(t) } }\n\
+        pub trait Make { fn make() -> Self; }\n\
+        ",
+    );
+    let api = public_api::Builder::from_rustdoc_json(&lib.json_path)
+        .omit_blanket_impls(true)
+        .omit_auto_trait_impls(true)
+        .build()
+        .unwrap();
+    let normalized = |path: &str| {
+        let mut item = api
+            .items()
+            .find(|item| item.path().join("::") == path)
+            .unwrap()
+            .clone();
+        item.normalize();
+        item.to_string()
+    };
+
+    assert_eq!(
+        normalized("lib::Wrapper::new"),
+        "pub fn lib::Wrapper<T>::new(t: T) -> lib::Wrapper<T>"
+    );
+    assert_eq!(
+        normalized("lib::Make::make"),
+        "pub fn lib::Make::make() -> Self"
+    );
+}
+
+#[test]
+fn normalize_phantom_data() {
+    let build = |lib: &str, normalize_phantom_data: bool| {
+        let lib = rustdoc_json_for_lib(lib);
+        public_api::Builder::from_rustdoc_json(&lib.json_path)
+            .omit_blanket_impls(true)
+            .omit_auto_trait_impls(true)
+            .normalize_phantom_data(normalize_phantom_data)
+            .build()
+            .unwrap()
+    };
+    let tuple = "pub struct Foo<T>(pub std::marker::PhantomData<T>);";
+    let named = "pub struct Foo<T> { pub _marker: std::marker::PhantomData<T> }";
+    let private = "pub struct Foo<T> { _marker: core::marker::PhantomData<T> }";
+
+    for other in [named, private] {
+        let diff = public_api::diff::PublicApiDiff::between(build(tuple, true), build(other, true));
+        assert!(diff.is_empty(), "{diff:?}");
+    }
+    assert_eq!(
+        build(tuple, true).to_string(),
+        build(named, true).to_string()
+    );
+
+    let diff = public_api::diff::PublicApiDiff::between(build(tuple, false), build(named, false));
+    assert!(!diff.is_empty());
+
+    // Other fields are kept
+    let api = build(
+        "pub struct Bar<T>(pub u8, pub std::marker::PhantomData<T>);",
+        true,
+    );
+    assert!(api
+        .items()
+        .any(|item| item.to_string() == "pub struct lib::Bar<T>(pub u8)"));
+
+    // Also for tuple variants
+    let api = build(
+        "pub enum E<T> { W(u8, std::marker::PhantomData<T>), P(std::marker::PhantomData<T>) }",
+        true,
+    );
+    let items: Vec<_> = api.items().map(ToString::to_string).collect();
+    assert!(
+        items.contains(&"pub lib::E::W(u8)".to_owned()),
+        "{items:#?}"
+    );
+    assert!(items.contains(&"pub lib::E::P".to_owned()), "{items:#?}");
+    assert!(!items.iter().any(|item| item.contains("PhantomData")));
+}
+
+#[test]
+fn json_format_version_mismatch() {
+    let json = NamedTempFile::new().unwrap();
+    write!(json.as_file(), r#"{{"format_version": 1}}"#).unwrap();
+    let result = public_api::Builder::from_rustdoc_json(json.path())
+        .json_format_version(Some(2))
+        .build();
+    assert!(matches!(
+        result,
+        Err(Error::FormatVersionMismatch {
+            expected: 2,
+            actual: 1
+        })
+    ));
+}
+
+#[test]
+fn parse_with_matching_format_version() {
+    let build_dir = tempdir().unwrap();
+    let json_path = rustdoc_json_path_for_crate("../test-apis/example_api-v0.1.0", &build_dir);
+    let json = fs::read_to_string(json_path).unwrap();
+    let version = rustdoc_types::FORMAT_VERSION;
+    assert!(public_api::parse_with_format_version(&json, version).is_ok());
+    assert!(matches!(
+        public_api::parse_with_format_version(&json, version + 1),
+        Err(Error::FormatVersionMismatch { .. })
+    ));
+}
+
+#[test]
+fn public_api_items_count_matches_full_listing() {
+    for crate_ in [
+        "../test-apis/example_api-v0.1.0",
+        "../test-apis/example_api-v0.2.0",
+        "../test-apis/comprehensive_api",
+    ] {
+        let build_dir = tempdir().unwrap();
+        let json_path = rustdoc_json_path_for_crate(crate_, &build_dir);
+
+        let json = fs::read_to_string(&json_path).unwrap();
+        let count = public_api::public_api_items_count(&json).unwrap();
+
+        let public_api = public_api::Builder::from_rustdoc_json(json_path)
+            .build()
+            .unwrap();
+        assert_eq!(count, public_api.items().count(), "{crate_}");
+    }
+}
+
+#[cfg(feature = "monitor")]
+#[test]
+fn monitor_reports_diff_when_file_changes() {
+    // Create independent build dirs so all tests can run in parallel
+    let build_dir = tempdir().unwrap();
+    let build_dir2 = tempdir().unwrap();
+    let old = rustdoc_json_path_for_crate("../test-apis/example_api-v0.1.0", &build_dir);
+    let new = rustdoc_json_path_for_crate("../test-apis/example_api-v0.2.0", &build_dir2);
+
+    let watched_dir = tempdir().unwrap();
+    let watched = watched_dir.path().join("watched.json");
+    fs::copy(old, &watched).unwrap();
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let handle = public_api::PublicApiMonitor::new(&watched)
+        .watch(move |diff| sender.send(diff).unwrap())
+        .unwrap();
+
+    // Replace the file in one step so that a half written file is never seen.
+    // The timeout only guards against hanging forever if the test is broken
+    let staged = watched_dir.path().join("staged.json");
+    fs::copy(new, &staged).unwrap();
+    fs::rename(&staged, &watched).unwrap();
+    let diff = receiver
+        .recv_timeout(std::time::Duration::from_secs(60))
+        .unwrap();
+    assert_eq!(diff.changed.len(), 2, "{diff:#?}");
+    assert!(!diff.added.is_empty());
+
+    // Stopping ends the background thread, which drops the sender
+    handle.stop();
+    assert_eq!(
+        receiver.try_recv().unwrap_err(),
+        std::sync::mpsc::TryRecvError::Disconnected
+    );
 }
 
 struct LibWithJson {
@@ -297,7 +1701,45 @@ fn assert_public_api_diff(
         .unwrap();
 
     let diff = public_api::diff::PublicApiDiff::between(old, new);
-    expect_file![expected.as_ref()].assert_debug_eq(&diff);
+    expect_file![expected.as_ref()].assert_debug_eq(&DiffSnapshot(&diff));
+}
+
+/// Pretty-prints a diff like `{:#?}` does, but with each item shown with
+/// `Display` rather than with every token, to keep expected output readable.
+struct DiffSnapshot<'a>(&'a public_api::diff::PublicApiDiff);
+
+impl std::fmt::Debug for DiffSnapshot<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        struct Displayed<'a>(&'a public_api::PublicItem);
+        impl std::fmt::Debug for Displayed<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(self.0, f)
+            }
+        }
+
+        struct Changed<'a>(&'a public_api::diff::ChangedPublicItem);
+        impl std::fmt::Debug for Changed<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct("ChangedPublicItem")
+                    .field("old", &Displayed(&self.0.old))
+                    .field("new", &Displayed(&self.0.new))
+                    .finish()
+            }
+        }
+
+        fn displayed(items: &[public_api::PublicItem]) -> Vec<Displayed<'_>> {
+            items.iter().map(Displayed).collect()
+        }
+
+        f.debug_struct("PublicApiDiff")
+            .field("removed", &displayed(&self.0.removed))
+            .field(
+                "changed",
+                &self.0.changed.iter().map(Changed).collect::<Vec<_>>(),
+            )
+            .field("added", &displayed(&self.0.added))
+            .finish()
+    }
 }
 
 // PublicApiDiff::between() is smarter than a textual diff, but in some cases we
@@ -305,10 +1747,12 @@ fn assert_public_api_diff(
 // this function.
 fn assert_no_textual_public_api_diff(old_json: impl Into<PathBuf>, new_json: impl Into<PathBuf>) {
     let old = public_api::Builder::from_rustdoc_json(old_json)
+        .strip_derives(Vec::<String>::new())
         .build()
         .unwrap()
         .to_string();
     let new = public_api::Builder::from_rustdoc_json(new_json)
+        .strip_derives(Vec::<String>::new())
         .build()
         .unwrap()
         .to_string();
@@ -49,6 +49,35 @@ where
         })?,
     };
 
+    rustdoc_json_path_after_build(options, status)
+}
+
+/// Like [`run_cargo_rustdoc`], but runs `cargo rustdoc` with
+/// [`tokio::process::Command`]. The process is killed if the returned future
+/// is dropped before it completes.
+#[cfg(feature = "async")]
+async fn run_cargo_rustdoc_async(options: Builder) -> Result<PathBuf, BuildError> {
+    let mut cmd = tokio::process::Command::from(cargo_rustdoc_command(&options)?);
+    cmd.kill_on_drop(true);
+    info!("Running {cmd:?}");
+
+    let status = cmd
+        .status()
+        .await
+        .map_err(|e| BuildError::CommandExecutionError(format!("Failed to run `{cmd:?}`: {e}")))?;
+
+    // Finding the path runs `cargo metadata`, which blocks
+    tokio::task::spawn_blocking(move || rustdoc_json_path_after_build(options, status))
+        .await
+        .map_err(|e| BuildError::General(format!("Failed to find rustdoc JSON: {e}")))?
+}
+
+/// Returns the path to the built rustdoc JSON if `cargo rustdoc` exited with
+/// `status` successfully, and otherwise the reason it failed.
+fn rustdoc_json_path_after_build(
+    options: Builder,
+    status: std::process::ExitStatus,
+) -> Result<PathBuf, BuildError> {
     if status.success() {
         rustdoc_json_path_for_manifest_path(
             &options.manifest_path,
@@ -86,6 +115,7 @@ fn cargo_rustdoc_command(options: &Builder) -> Result<Command, BuildError> {
         features,
         package,
         package_target,
+        profile,
         document_private_items,
         cap_lints,
     } = options;
@@ -146,6 +176,9 @@ fn cargo_rustdoc_command(options: &Builder) -> Result<Command, BuildError> {
     if let Some(package) = package {
         command.args(["--package", package]);
     }
+    if let Some(profile) = profile {
+        command.args(["--profile", profile]);
+    }
     command.arg("--");
     command.args(["-Z", "unstable-options"]);
     command.args(["--output-format", "json"]);
@@ -276,6 +309,7 @@ pub struct Builder {
     features: Vec<String>,
     package: Option<String>,
     package_target: PackageTarget,
+    profile: Option<String>,
     document_private_items: bool,
     cap_lints: Option<String>,
 }
@@ -295,6 +329,7 @@ impl Default for Builder {
             features: vec![],
             package: None,
             package_target: PackageTarget::default(),
+            profile: None,
             document_private_items: false,
             cap_lints: Some(String::from("warn")),
         }
@@ -416,6 +451,14 @@ impl Builder {
         self
     }
 
+    /// Cargo profile to use for `cargo rustdoc` via `--profile`, e.g. a custom
+    /// `[profile.docs]`. Default: `None`
+    #[must_use]
+    pub fn profile(mut self, profile: impl AsRef<str>) -> Self {
+        self.profile = Some(profile.as_ref().to_owned());
+        self
+    }
+
     /// Whether to pass `--document-private-items` to `cargo rustdoc`. Default: `false`
     #[must_use]
     pub fn document_private_items(mut self, document_private_items: bool) -> Self {
@@ -485,6 +528,23 @@ impl Builder {
         let capture_output = CaptureOutput { stdout, stderr };
         run_cargo_rustdoc(self, Some(capture_output))
     }
+
+    /// Generate rustdoc JSON for a crate without blocking the current thread.
+    /// This works like [`Builder::build()`], but runs `cargo rustdoc` as a
+    /// [`tokio`] process. Dropping the returned future before it completes,
+    /// e.g. because a `tokio::time::timeout()` elapsed, kills the process.
+    ///
+    /// Must be polled within a Tokio runtime. Requires the `async` feature.
+    ///
+    /// # Errors
+    ///
+    /// See [`Builder::build()`].
+    #[cfg(feature = "async")]
+    pub fn build_async(
+        self,
+    ) -> impl std::future::Future<Output = Result<PathBuf, BuildError>> + Send {
+        run_cargo_rustdoc_async(self)
+    }
 }
 
 /// The part of the package to document
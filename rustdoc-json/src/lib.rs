@@ -56,15 +56,18 @@ pub enum BuildError {
     #[error("Failed to execute: {0}")]
     CommandExecutionError(String),
 
-    /// An error originating from `cargo-manifest`.
-    #[error(transparent)]
+    /// An error originating from `cargo-manifest`, which is the
+    /// [`std::error::Error::source`].
+    #[error("Failed to read the Cargo manifest: {0}")]
     CargoManifestError(#[from] cargo_manifest::Error),
 
-    /// An error originating from `cargo_metadata`.
-    #[error(transparent)]
+    /// An error originating from `cargo_metadata`, which is the
+    /// [`std::error::Error::source`].
+    #[error("Failed to get cargo metadata: {0}")]
     CargoMetadataError(#[from] cargo_metadata::Error),
 
-    /// Some kind of IO error occurred.
-    #[error(transparent)]
+    /// Some kind of IO error occurred. The [`std::error::Error::source`] is
+    /// the [`std::io::Error`].
+    #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 }
@@ -6,7 +6,9 @@ fn public_api() -> Result<(), Box<dyn std::error::Error>> {
         .toolchain("nightly")
         .build()?;
 
-    let public_api = public_api::Builder::from_rustdoc_json(rustdoc_json).build()?;
+    let public_api = public_api::Builder::from_rustdoc_json(rustdoc_json)
+        .strip_derives(Vec::<String>::new())
+        .build()?;
 
     expect_test::expect_file!["public-api.txt"].assert_eq(&public_api.to_string());
 
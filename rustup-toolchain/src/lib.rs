@@ -12,8 +12,9 @@
 #[non_exhaustive]
 /// Enumerates all errors that can currently occur within this crate.
 pub enum Error {
-    /// Some kind of IO error occurred.
-    #[error(transparent)]
+    /// Some kind of IO error occurred, e.g. because `rustup` could not be
+    /// run. The [`std::error::Error::source`] is the [`std::io::Error`].
+    #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
     /// The lock used to work around <https://github.com/rust-lang/rustup/issues/988> has been poisoned
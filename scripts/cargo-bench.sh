@@ -0,0 +1,32 @@
+#!/usr/bin/env bash
+set -o nounset -o pipefail -o errexit -o xtrace
+
+# Runs the benchmarks of `public-api` on the baseline commit given as the first
+# arg, e.g. the base of a PR, and then on the current commit. Warns about every
+# benchmark that got more than MAX_SLOWDOWN (15% by default) slower. Timings on
+# shared CI runners are too noisy to fail on, so this is only informational.
+baseline_commit="$1"
+max_slowdown="${MAX_SLOWDOWN:-0.15}"
+current_commit="$(git rev-parse HEAD)"
+
+rm -rf target/criterion
+
+git checkout --quiet "${baseline_commit}"
+if [ ! -f public-api/benches/diff.rs ]; then
+    echo "No benchmarks in ${baseline_commit}, nothing to compare against"
+    git checkout --quiet "${current_commit}"
+    exit 0
+fi
+cargo bench --locked --package public-api --bench diff -- --save-baseline baseline
+
+git checkout --quiet "${current_commit}"
+cargo bench --locked --package public-api --bench diff -- --baseline baseline
+
+# Criterion stores the relative change of the mean of each benchmark in
+# change/estimates.json, e.g. 0.05 means 5% slower
+while IFS= read -r -d '' estimates; do
+    if jq --exit-status --argjson max "${max_slowdown}" '.mean.point_estimate > $max' "${estimates}" >/dev/null; then
+        # Shows up as an annotation on GitHub
+        echo "::warning::Possible regression in ${estimates%/change/estimates.json}"
+    fi
+done < <(find target/criterion -path '*/change/estimates.json' -print0)
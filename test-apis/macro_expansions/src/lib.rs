@@ -0,0 +1,15 @@
+use macro_expansions_proc_macro::PublicDeriveMacro;
+
+macro_rules! public_fn {
+    ($name:ident) => {
+        pub fn $name() {}
+    };
+}
+
+public_fn!(from_macro_rules);
+
+/// Derives a struct that is not visible in this file.
+#[derive(PublicDeriveMacro, Clone)]
+pub struct Derived;
+
+pub fn not_from_macro() {}
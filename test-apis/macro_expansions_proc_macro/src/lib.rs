@@ -0,0 +1,9 @@
+extern crate proc_macro;
+use proc_macro::TokenStream;
+
+/// Generates a public item, so that it shows up in the public API of crates
+/// that use the derive.
+#[proc_macro_derive(PublicDeriveMacro)]
+pub fn derive_public(_input: TokenStream) -> TokenStream {
+    "pub struct FromPublicDeriveMacro;".parse().unwrap()
+}